@@ -0,0 +1,78 @@
+//! Compares the `insert_all` throughput of the file engines, for records generated by [`gen_records`]
+//!
+//! Run with `cargo bench --features full,testing`.
+
+use lupabase::{prelude::*, testing::*};
+use serde::{Deserialize, Serialize};
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+const SIZES: [u64; 3] = [100, 1_000, 10_000];
+const ITERATIONS: u32 = 10;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct BenchRecord {
+    id: u64,
+    name: String,
+    tags: Vec<String>,
+}
+
+impl DatabaseRecord for BenchRecord {
+    type Unique = u64;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
+impl DatabaseRecordPartitioned for BenchRecord {
+    const PARTITION: &str = "BenchRecord";
+}
+
+impl TestGen for BenchRecord {
+    fn generate(seed: u64) -> Self {
+        Self {
+            id: seed,
+            name: format!("Record {seed}"),
+            tags: (0..seed % 4).map(|tag| format!("tag-{tag}")).collect(),
+        }
+    }
+}
+
+fn bench_insert_all<DB: Database>(size: u64) -> Duration {
+    let records = gen_records::<BenchRecord>(size);
+
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let temp_dir = TempDir::new().expect("Temporary directory creation failed");
+        let db = DB::new(temp_dir.path());
+        db.try_initialize_storage::<BenchRecord, Vec<BenchRecord>>(vec![])
+            .expect("Storage initialization failed");
+
+        let start = Instant::now();
+        db.insert_all(black_box(&records))
+            .expect("Inserting the records failed");
+        elapsed += start.elapsed();
+    }
+
+    return elapsed / ITERATIONS;
+}
+
+fn report<DB: Database>(size: u64) {
+    let elapsed = bench_insert_all::<DB>(size);
+    let throughput = size as f64 / elapsed.as_secs_f64();
+
+    println!(
+        "insert_all/{}-{}/{size:<6} time: [{elapsed:>12.3?}] thrpt: [{throughput:>12.0} records/s]",
+        DB::SERDE_FORMAT,
+        DB::NAME,
+    );
+}
+
+fn main() {
+    for size in SIZES {
+        report::<DiskDB<JsonSerde>>(size);
+        report::<DiskDB<CborSerde>>(size);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::utils::with_added_extension;
+use std::path::{Path, PathBuf};
+
+/// A backup created by [`DatabaseIO::try_backup_storage`](crate::database::DatabaseIO::try_backup_storage)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BackupInfo {
+    /// The backed-up storage path
+    pub path: PathBuf,
+    /// The UNIX timestamp (in seconds) of when the backup was created
+    pub timestamp: i64,
+    /// The reason given when the backup was created
+    pub reason: String,
+}
+
+impl BackupInfo {
+    /// Parses the backup created from `storage_path` out of `backup_path`'s file name,
+    /// returning [`None`] if `backup_path` isn't one of its backups
+    pub fn parse(storage_path: impl AsRef<Path>, backup_path: impl AsRef<Path>) -> Option<Self> {
+        let storage_name = storage_path.as_ref().file_name()?.to_str()?;
+        let backup_path = backup_path.as_ref();
+
+        let (timestamp, reason) = backup_path
+            .file_name()?
+            .to_str()?
+            .strip_prefix(storage_name)?
+            .strip_prefix('.')?
+            .strip_suffix(".bak")?
+            .split_once('-')?;
+
+        return Some(Self {
+            path: backup_path.to_path_buf(),
+            timestamp: timestamp.parse().ok()?,
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Returns the path of the backup of `storage_path`, the inverse of [`BackupInfo::parse`]
+    pub(crate) fn path_for(
+        storage_path: impl AsRef<Path>,
+        timestamp: i64,
+        reason: &str,
+    ) -> PathBuf {
+        return with_added_extension(storage_path, format!("{timestamp}-{reason}.bak"));
+    }
+}
+
+/// The source of the timestamps used to name backups, see [`DatabaseIO::clock`](crate::database::DatabaseIO::clock)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Clock {
+    /// The current local time
+    #[default]
+    Local,
+    /// A fixed UNIX timestamp (in seconds), e.g. for deterministic backup names in tests
+    Fixed(i64),
+}
+
+impl Clock {
+    /// Returns the current UNIX timestamp (in seconds) according to the clock
+    pub fn timestamp(&self) -> i64 {
+        return match self {
+            Self::Local => chrono::Local::now().timestamp(),
+            Self::Fixed(timestamp) => *timestamp,
+        };
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{Result, database::*, record::*};
+
+/// Collects inserts, updates, and deletes of [`DatabaseRecordPartitioned`] to apply them at once,
+/// see [`DatabaseOps::batch`]
+///
+/// Nothing is read nor written until [`BatchBuilder::apply`], which applies the whole batch
+/// with [`DatabaseOps::apply_changes`].
+///
+/// # Example
+/// ```rust
+/// # use lupabase::prelude::*;
+/// # use serde::{Deserialize, Serialize};
+/// #
+/// # #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// # struct User { id: u8, name: char }
+/// #
+/// # impl DatabaseRecord for User {
+/// #    type Unique = u8;
+/// #
+/// #    fn unique_value(&self) -> Self::Unique { self.id }
+/// # }
+/// #
+/// # impl DatabaseRecordPartitioned for User {
+/// #    const PARTITION: &str = "Users";
+/// # }
+/// let db = MemoryDB::<JsonSerde>::new("Batched");
+/// db.try_initialize_storage::<User, _>(vec![
+///     User { id: 1, name: 'a' },
+///     User { id: 2, name: 'b' },
+/// ])?;
+///
+/// db.batch::<User>()
+///     .insert(User { id: 3, name: 'c' })
+///     .update(User { id: 1, name: 'z' })
+///     .delete(2)
+///     .apply()?;
+///
+/// assert_eq!(
+///     db.get_all::<User>()?,
+///     vec![User { id: 1, name: 'z' }, User { id: 3, name: 'c' }]
+/// );
+/// # Ok::<(), lupabase::Error>(())
+/// ```
+pub struct BatchBuilder<'a, DB: DatabaseOps + ?Sized, T: DatabaseRecordPartitioned> {
+    database: &'a DB,
+    inserts: Vec<T>,
+    updates: Vec<T>,
+    deletes: Vec<T::Unique>,
+}
+
+impl<'a, DB: DatabaseOps + ?Sized, T: DatabaseRecordPartitioned> BatchBuilder<'a, DB, T> {
+    pub(crate) fn new(database: &'a DB) -> Self {
+        return Self {
+            database,
+            inserts: vec![],
+            updates: vec![],
+            deletes: vec![],
+        };
+    }
+
+    /// Adds a record to be inserted, appended after the existing records
+    pub fn insert(mut self, new_record: T) -> Self {
+        self.inserts.push(new_record);
+
+        return self;
+    }
+
+    /// Adds a record to be updated, keeping its position
+    pub fn update(mut self, updated_record: T) -> Self {
+        self.updates.push(updated_record);
+
+        return self;
+    }
+
+    /// Adds the unique identifier of a record to be deleted
+    pub fn delete(mut self, unique: T::Unique) -> Self {
+        self.deletes.push(unique);
+
+        return self;
+    }
+
+    /// Validates the whole batch, then applies it with a single write
+    ///
+    /// Nothing is written if any operation of the batch is invalid.
+    ///
+    /// See [`DatabaseOps::apply_changes`] for details and the list of possible errors.
+    pub fn apply(self) -> Result<()> {
+        return self
+            .database
+            .apply_changes(self.inserts, self.updates, self.deletes);
+    }
+}
@@ -0,0 +1,15 @@
+use crate::{Result, database::*, record::*};
+
+/// Returns whether both databases hold equal records in the partition of `T`
+///
+/// Records are compared in their stored order.
+///
+/// # Errors
+/// - I/O
+/// - Parsing failure
+pub fn databases_equal<A: DatabaseOps, B: DatabaseOps, T: DatabaseRecordPartitioned + PartialEq>(
+    a: &A,
+    b: &B,
+) -> Result<bool> {
+    return Ok(a.get_all::<T>()? == b.get_all::<T>()?);
+}
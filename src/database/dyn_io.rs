@@ -0,0 +1,48 @@
+use crate::{Result, database::*};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// An object-safe subset of [`DatabaseIO`], usable as `Arc<dyn DynDatabaseIO>`
+///
+/// Data is read as raw bytes or as a [`Value`], and written as a [`Value`],
+/// so an engine can be chosen at runtime. Every [`DatabaseIO`] implements it.
+pub trait DynDatabaseIO {
+    /// See [`DatabaseIO::dir`]
+    fn dyn_dir(&self) -> PathBuf;
+
+    /// See [`DatabaseIO::file_path`]
+    fn dyn_file_path(&self, file_name: &Path) -> PathBuf;
+
+    /// See [`DatabaseIO::list_partitions`] for details and the list of possible errors.
+    fn dyn_list_partitions(&self) -> Result<Vec<String>>;
+
+    /// See [`DatabaseIO::storage_exists`] for details and the list of possible errors.
+    fn dyn_storage_exists(&self, path: &Path) -> Result<bool>;
+
+    /// See [`DatabaseIO::try_read_storage_bytes`] for details and the list of possible errors.
+    fn dyn_read_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+
+    /// See [`DatabaseIO::try_read_storage`] for details and the list of possible errors.
+    fn dyn_read_value(&self, path: &Path) -> Result<Value>;
+
+    /// See [`DatabaseIO::try_write_storage`] for details and the list of possible errors.
+    fn dyn_write_value(&self, data: &Value, path: &Path) -> Result<()>;
+}
+
+impl<D: DatabaseIO> DynDatabaseIO for D {
+    fn dyn_dir(&self) -> PathBuf { self.dir() }
+
+    fn dyn_file_path(&self, file_name: &Path) -> PathBuf { self.file_path(file_name) }
+
+    fn dyn_list_partitions(&self) -> Result<Vec<String>> { self.list_partitions() }
+
+    fn dyn_storage_exists(&self, path: &Path) -> Result<bool> { self.storage_exists(path) }
+
+    fn dyn_read_bytes(&self, path: &Path) -> Result<Vec<u8>> { self.try_read_storage_bytes(path) }
+
+    fn dyn_read_value(&self, path: &Path) -> Result<Value> { self.try_read_storage(path) }
+
+    fn dyn_write_value(&self, data: &Value, path: &Path) -> Result<()> {
+        self.try_write_storage(data, path)
+    }
+}
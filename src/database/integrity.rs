@@ -0,0 +1,63 @@
+use crate::record::DatabaseRecord;
+use derive_more::Display;
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    path::PathBuf,
+};
+
+/// An issue found with a partition while checking the [`Database`](crate::database::Database)'s integrity
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Display)]
+pub enum IntegrityIssue {
+    /// The partition could not be read from storage
+    #[display("Partition [{partition}] is unreadable, caused by: [{reason}]")]
+    Unreadable { partition: String, reason: String },
+
+    /// The partition was read, but its content could not be parsed
+    #[display("Partition [{partition}] is corrupt, caused by: [{reason}]")]
+    Corrupt { partition: String, reason: String },
+}
+
+/// How [`Database::repair_partition`](crate::database::Database::repair_partition) handles records sharing a unique identifier
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum DuplicatePolicy {
+    /// Keep the first record, dropping every later duplicate
+    #[default]
+    KeepFirst,
+
+    /// Keep the last record, in place of the first duplicate
+    KeepLast,
+}
+
+impl DuplicatePolicy {
+    pub(crate) fn deduplicate<T: DatabaseRecord>(&self, records: Vec<T>) -> Vec<T> {
+        let mut positions = HashMap::with_capacity(records.len());
+        let mut deduplicated: Vec<T> = Vec::with_capacity(records.len());
+
+        for record in records {
+            match positions.entry(record.unique_value()) {
+                Entry::Vacant(entry) => {
+                    let _ = entry.insert(deduplicated.len());
+                    deduplicated.push(record);
+                }
+                Entry::Occupied(entry) => {
+                    if *self == Self::KeepLast {
+                        deduplicated[*entry.get()] = record;
+                    }
+                }
+            }
+        }
+
+        return deduplicated;
+    }
+}
+
+/// The outcome of [`Database::repair_partition`](crate::database::Database::repair_partition)
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RepairReport {
+    /// The number of records kept in the partition
+    pub kept: usize,
+    /// The number of duplicate records removed from the partition
+    pub removed: usize,
+    /// The backup of the original partition, [`None`] if it didn't need repairing
+    pub backup_path: Option<PathBuf>,
+}
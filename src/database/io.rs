@@ -1,5 +1,12 @@
-use crate::{Deserialize, Result, Serialize};
-use std::path::{Path, PathBuf};
+use crate::{
+    Deserialize, Error, Result, Serialize,
+    database::{BackupInfo, Clock},
+    utils::with_added_extension,
+};
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
 
 /// Provides operations for database I/O
 pub trait DatabaseIO {
@@ -19,11 +26,31 @@ pub trait DatabaseIO {
         return std::path::absolute(&dir).unwrap_or(dir);
     }
 
+    /// Returns the canonical path of the storage's base directory, with every symlink resolved
+    ///
+    /// Unlike [`DatabaseIO::dir_absolute`], which only joins a relative directory onto the current one,
+    /// two databases reaching the same directory through different links return the same path.
+    /// Falls back to [`DatabaseIO::dir_absolute`] if the directory doesn't exist yet,
+    /// and engines not storing files (e.g. in memory) always return it.
+    ///
+    /// # Errors
+    /// - I/O (other than the directory not existing)
+    fn dir_canonical(&self) -> Result<PathBuf> {
+        let dir = self.dir();
+
+        return match std::fs::canonicalize(&dir) {
+            Ok(canonical_dir) => Ok(canonical_dir),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(self.dir_absolute()),
+            Err(e) => Err(Error::DBInaccessible {
+                path: dir,
+                reason: e,
+            }),
+        };
+    }
+
     /// Returns a storage path with the provided file name
     fn file_path(&self, file_name: impl AsRef<Path>) -> PathBuf {
-        self.dir()
-            .join(file_name)
-            .with_added_extension(Self::EXTENSION)
+        with_added_extension(self.dir().join(file_name), Self::EXTENSION)
     }
 
     /// Returns the absolute storage path with the provided file name    
@@ -31,24 +58,39 @@ pub trait DatabaseIO {
     /// This method attempts to convert the relative path into an absolute path.
     /// If obtaining an absolute path fails, it falls back to returning the original path.
     fn file_path_absolute(&self, file_name: impl AsRef<Path>) -> PathBuf {
-        let path = self
-            .dir()
-            .join(file_name)
-            .with_added_extension(Self::EXTENSION);
+        let path = with_added_extension(self.dir().join(file_name), Self::EXTENSION);
 
         return std::path::absolute(&path).unwrap_or(path);
     }
 
     /// Attemps to copy the storage to the destination
     ///
+    /// Copying a storage onto itself is refused, as it is most likely a mistake (and would truncate files).
+    ///
     /// # Errors
     /// - I/O
+    /// - The source and destination are identical
     fn try_copy_storage(
         &self,
         source: impl AsRef<Path>,
         destination: impl AsRef<Path>,
     ) -> Result<()>;
 
+    /// Attemps to move the storage to the destination, replacing the destination if it exists
+    ///
+    /// Cheaper than copying, as file engines rename the file whenever possible.
+    ///
+    /// # Errors
+    /// - I/O
+    fn try_move_storage(
+        &self,
+        source: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<()>;
+
+    /// Returns the clock used to timestamp backups, the current local time by default
+    fn clock(&self) -> Clock { Clock::Local }
+
     /// Attempts to backup the storage, returning the backed-up storage path
     ///
     /// # Errors
@@ -60,17 +102,120 @@ pub trait DatabaseIO {
     ) -> Result<PathBuf> {
         let path = path.as_ref();
 
-        let backup_path = path.with_added_extension(format!(
-            "{}-{}.bak",
-            &chrono::Local::now().timestamp(),
-            reason.as_ref()
-        ));
+        let backup_path = BackupInfo::path_for(path, self.clock().timestamp(), reason.as_ref());
 
         self.try_copy_storage(path, &backup_path)?;
 
         return Ok(backup_path);
     }
 
+    /// Returns every backup of the storage, oldest first
+    ///
+    /// # Errors
+    /// - I/O
+    fn list_backups(&self, path: impl AsRef<Path>) -> Result<Vec<BackupInfo>> {
+        let path = path.as_ref();
+
+        let mut backups = self
+            .list_storages(path.parent().unwrap_or(Path::new("")))?
+            .into_iter()
+            .filter_map(|backup_path| BackupInfo::parse(path, backup_path))
+            .collect::<Vec<_>>();
+        backups.sort_by_key(|backup| backup.timestamp);
+
+        return Ok(backups);
+    }
+
+    /// Returns every backup of the storage created with the given reason, oldest first
+    ///
+    /// # Errors
+    /// - I/O
+    fn list_backups_with_reason(
+        &self,
+        path: impl AsRef<Path>,
+        reason: impl AsRef<str>,
+    ) -> Result<Vec<BackupInfo>> {
+        let mut backups = self.list_backups(path)?;
+        backups.retain(|backup| backup.reason == reason.as_ref());
+
+        return Ok(backups);
+    }
+
+    /// Removes the oldest backups of the storage, keeping only the `keep` most recent ones,
+    /// returning the number of backups removed
+    ///
+    /// # Errors
+    /// - I/O
+    fn prune_backups(&self, path: impl AsRef<Path>, keep: usize) -> Result<usize> {
+        let backups = self.list_backups(path)?;
+        let pruned = backups.len().saturating_sub(keep);
+
+        for backup in &backups[..pruned] {
+            self.try_remove_storage(&backup.path)?;
+        }
+
+        return Ok(pruned);
+    }
+
+    /// Removes the backups of the storage created before the `cutoff` Unix timestamp (in seconds),
+    /// returning the paths of the backups removed, oldest first
+    ///
+    /// The timestamps are parsed from the backups' names, see [`DatabaseIO::clock`].
+    ///
+    /// # Errors
+    /// - I/O
+    fn prune_backups_older_than(
+        &self,
+        path: impl AsRef<Path>,
+        cutoff: i64,
+    ) -> Result<Vec<PathBuf>> {
+        let mut pruned = vec![];
+
+        for backup in self.list_backups(path)? {
+            if backup.timestamp >= cutoff {
+                continue;
+            }
+
+            self.try_remove_storage(&backup.path)?;
+            pruned.push(backup.path);
+        }
+
+        return Ok(pruned);
+    }
+
+    /// Returns the path of every storage directly inside the given directory, including backups, sorted by path
+    ///
+    /// # Errors
+    /// - I/O
+    fn list_storages(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>>;
+
+    /// Returns the name of every partition stored in the storage's base directory, sorted by name
+    ///
+    /// A partition is any storage directly inside [`DatabaseIO::dir`] with the [`DatabaseIO::EXTENSION`],
+    /// backups are excluded.
+    ///
+    /// # Errors
+    /// - I/O
+    fn list_partitions(&self) -> Result<Vec<String>>;
+
+    /// Returns the size of the storage in bytes
+    ///
+    /// # Errors
+    /// - I/O
+    fn storage_size(&self, path: impl AsRef<Path>) -> Result<u64>;
+
+    /// Removes the storage
+    ///
+    /// # Errors
+    /// - I/O
+    fn try_remove_storage(&self, path: impl AsRef<Path>) -> Result<()>;
+
+    /// Checks whether the storage exists, without reading it
+    ///
+    /// # Errors
+    /// - I/O (the existence can't be determined, e.g. due to permissions)
+    fn storage_exists(&self, path: impl AsRef<Path>) -> Result<bool>;
+
     /// Attempts to write the provided data to storage
     ///
     /// # Errors
@@ -78,10 +223,174 @@ pub trait DatabaseIO {
     /// - Parsing failure
     fn try_write_storage(&self, data: impl Serialize, path: impl AsRef<Path>) -> Result<()>;
 
+    /// Writes bytes already serialized in the engine's format (e.g. read by [`DatabaseIO::try_read_storage_bytes`])
+    /// to storage, like [`DatabaseIO::try_write_storage`] without serializing them
+    ///
+    /// The bytes aren't checked, see [`DatabaseIO::check_storage`].
+    ///
+    /// # Errors
+    /// - I/O
+    fn try_write_storage_bytes(&self, bytes: &[u8], path: impl AsRef<Path>) -> Result<()>;
+
+    /// Returns the version of the storage, incremented by every [`DatabaseIO::try_write_storage`]
+    ///
+    /// Polling the version detects writes (e.g. from another process sharing a [`DiskDB`](crate::prelude::DiskDB))
+    /// without reading the storage. Storages never written return `0`. Versions survive the storage being removed,
+    /// so they never go backwards. Copying or moving a storage onto another sets the destination's version past
+    /// both the source's and its own. Concurrent writes from separate processes each increment the version.
+    /// Writes bypassing the engine's serializer (e.g. [`DatabaseIO::open_append`]) don't increment versions.
+    ///
+    /// # Errors
+    /// - I/O
+    fn version(&self, path: impl AsRef<Path>) -> Result<u64>;
+
+    /// Opens the storage for appending raw bytes (e.g. an audit log), creating it if missing
+    ///
+    /// The bytes are written as-is, bypassing the engine's serializer, so the storage may no longer
+    /// be readable as records afterwards.
+    ///
+    /// # Errors
+    /// - I/O
+    fn open_append(&self, path: impl AsRef<Path>) -> Result<Box<dyn Write>>;
+
+    /// Attempts to serialize the provided data into the writer (e.g. stdout or a pipe), in the engine's format
+    ///
+    /// Nothing is written to storage, see [`DatabaseIO::read_storage_from`] to read the data back.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    fn write_storage_to<W: Write>(&self, data: impl Serialize, writer: &mut W) -> Result<()>;
+
+    /// Attempts to read all data from the reader (e.g. stdin or a pipe) and deserialize it, in the engine's format
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    fn read_storage_from<R: Read, O: for<'a> Deserialize<'a>>(&self, reader: &mut R) -> Result<O>;
+
+    /// Attempts to read a sequence from the reader in the engine's format, passing every item to `on_item` as it's read
+    ///
+    /// Unlike [`DatabaseIO::read_storage_from`], the items are never collected, nor is the whole input held in memory,
+    /// see [`BytesSerde::try_deserialize_seq_from_reader`](crate::serdes::BytesSerde::try_deserialize_seq_from_reader) for details.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    /// - Any error returned by `on_item`, which stops the reading
+    fn read_seq_from<R: Read, O: for<'a> Deserialize<'a>>(
+        &self,
+        reader: &mut R,
+        on_item: &mut dyn FnMut(O) -> Result<()>,
+    ) -> Result<()>;
+
+    /// Attempts to read the raw bytes of the storage, as serialized by the engine
+    ///
+    /// # Errors
+    /// - I/O
+    fn try_read_storage_bytes(&self, path: impl AsRef<Path>) -> Result<Vec<u8>>;
+
+    /// Computes the SHA-256 hash of the storage's raw bytes, see [`DatabaseIO::try_read_storage_bytes`]
+    ///
+    /// Equal hashes mean identical stored bytes, so two storages can be compared (or a storage checked for
+    /// changes) without parsing them. The same data may hash differently across serde formats.
+    ///
+    /// # Errors
+    /// - I/O
+    #[cfg(feature = "hash")]
+    fn content_hash(&self, path: impl AsRef<Path>) -> Result<[u8; 32]> {
+        use sha2::{Digest, Sha256};
+
+        return Ok(Sha256::digest(self.try_read_storage_bytes(path)?).into());
+    }
+
+    /// Attempts to write the provided data to storage, only if the storage's raw bytes still match the expected bytes
+    ///
+    /// An `expected_current` of [`None`] expects the storage to not exist. This is a low-level compare-and-swap
+    /// for optimistic concurrency, the expected bytes usually come from [`DatabaseIO::try_read_storage_bytes`].
+    ///
+    /// The default implementation reads, compares, then writes without holding any lock, so it is **not** atomic:
+    /// concurrent callers may all succeed. Every engine of this crate overrides it with an atomic implementation.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    /// - The storage's raw bytes don't match the expected bytes ([`Error::DBTransactionConflict`])
+    fn compare_and_write(
+        &self,
+        expected_current: Option<&[u8]>,
+        data: impl Serialize,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let current = match self.try_read_storage_bytes(&path) {
+            Ok(bytes) => Some(bytes),
+            Err(Error::DBNotFound { .. }) => None,
+            Err(e) => return Err(e),
+        };
+
+        if current.as_deref() != expected_current {
+            return Err(Error::DBTransactionConflict {
+                file_path: path.as_ref().to_path_buf(),
+            });
+        }
+
+        return self.try_write_storage(data, path);
+    }
+
     /// Attempts to read data from storage and deserialize it into the specified type of data
     ///
     /// # Errors
     /// - I/O
     /// - Parsing failure
     fn try_read_storage<O: for<'a> Deserialize<'a>>(&self, path: impl AsRef<Path>) -> Result<O>;
+
+    /// Attempts to read an optional value from storage, stored as `Option<O>` (e.g. a config which may be absent)
+    ///
+    /// Both a stored [`None`] (e.g. written by `try_initialize_storage_with_path(None::<O>, path)`, serialized as `null`)
+    /// and a missing storage are read as [`None`], instead of failing with [`Error::DBNotFound`].
+    ///
+    /// See [`DatabaseIO::try_read_storage`] for details and the list of possible errors.
+    fn try_read_storage_optional<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Option<O>> {
+        return match self.try_read_storage::<Option<O>>(path) {
+            Err(Error::DBNotFound { .. }) => Ok(None),
+            result => result,
+        };
+    }
+
+    /// Checks that the storage can be read and parsed in the engine's format, without knowing its type
+    ///
+    /// Unlike [`DatabaseIO::try_read_storage`], a storage failing to parse is neither backed up nor logged,
+    /// so it can be checked repeatedly (e.g. by [`Database::integrity_check`](crate::database::Database::integrity_check)) without side effects.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure ([`Error::DBCorrupt`])
+    fn check_storage(&self, path: impl AsRef<Path>) -> Result<()>;
+
+    /// Attempts to read data from storage like [`DatabaseIO::try_read_storage`], additionally returning the path
+    /// of every unknown field that was ignored while deserializing (e.g. `0.legacy_field`)
+    ///
+    /// Engines that can't report ignored fields return an empty list.
+    ///
+    /// See [`DatabaseIO::try_read_storage`] for details and the list of possible errors.
+    fn try_read_storage_lenient<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(O, Vec<String>)> {
+        return Ok((self.try_read_storage(path)?, vec![]));
+    }
+
+    /// Attempts to read data from storage, returning it along with the raw bytes it was deserialized from
+    ///
+    /// The storage is read once, so the bytes match the data (unlike a separate
+    /// [`DatabaseIO::try_read_storage_bytes`], which may see a concurrent write), e.g. to cache or hash them.
+    ///
+    /// See [`DatabaseIO::try_read_storage`] for details and the list of possible errors.
+    fn read_with_raw<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(O, Vec<u8>)>;
 }
@@ -6,8 +6,28 @@ mod operation_operatable;
 pub use operation_operatable::*;
 mod io;
 pub use io::*;
+mod integrity;
+pub use integrity::*;
+mod compare;
+pub use compare::*;
+mod backup;
+pub use backup::*;
+mod seeder;
+pub use seeder::*;
+mod batch;
+pub use batch::*;
+#[cfg(feature = "json")]
+mod dyn_io;
+#[cfg(feature = "json")]
+pub use dyn_io::*;
 
-use std::path::Path;
+use crate::{
+    Error, Result,
+    record::{DatabaseRecordPartitioned, validate_partition_name},
+    utils::repair_partition,
+};
+use serde_core::de::IgnoredAny;
+use std::{collections::BTreeMap, path::Path};
 
 /// Represents a database that provides operations for managing records,
 /// built upon the functionality provided by [`DatabaseOps`] and [`DatabaseIO`]
@@ -20,4 +40,149 @@ pub trait Database: DatabaseOps + DatabaseIO {
 
     /// Creates a new instance of [`Database`] with the specified base directory where files will be stored
     fn new(dir: impl AsRef<Path>) -> Self;
+
+    /// Returns a database for the namespace, whose base directory is the namespace inside [`DatabaseIO::dir`]
+    ///
+    /// Every partition path nests inside the namespace (e.g. one per tenant), isolating it from the same
+    /// partition in other namespaces. Engines keep sharing their state (e.g. [`MemoryDB`](crate::prelude::MemoryDB)'s
+    /// store) and settings with the namespaced database, the default creates a new instance with [`Database::new`].
+    fn with_namespace(&self, namespace: impl AsRef<str>) -> Self
+    where
+        Self: Sized, {
+        return Self::new(self.dir().join(namespace.as_ref()));
+    }
+
+    /// Checks that every partition listed by [`DatabaseIO::list_partitions`] can be read and parsed
+    ///
+    /// Partitions are parsed without knowing their record type, so only the storage format is validated.
+    /// Every partition is checked, returning all issues found instead of stopping at the first one.
+    /// Unlike failed reads, corrupt partitions are neither backed up nor logged, see [`DatabaseIO::check_storage`].
+    ///
+    /// # Errors
+    /// - I/O (listing the partitions)
+    fn integrity_check(&self) -> Result<Vec<IntegrityIssue>> {
+        let issues = self
+            .list_partitions()?
+            .into_iter()
+            .filter_map(|partition| {
+                return match self.check_storage(self.file_path(&partition)) {
+                    Ok(()) => None,
+                    Err(Error::DBCorrupt { reason, .. }) => {
+                        Some(IntegrityIssue::Corrupt { partition, reason })
+                    }
+                    Err(e) => Some(IntegrityIssue::Unreadable {
+                        partition,
+                        reason: e.to_string(),
+                    }),
+                };
+            })
+            .collect();
+
+        return Ok(issues);
+    }
+
+    /// Repairs the partition of the [`DatabaseRecordPartitioned`] by removing records with duplicate
+    /// unique identifiers according to the policy
+    ///
+    /// The partition is read leniently (see [`DatabaseOps::get_all_lenient`]), and the remaining records keep their order.
+    /// The original partition is backed up before the repaired records are written, nothing is written
+    /// (nor backed up) if the partition has no duplicates.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    fn repair_partition<T: DatabaseRecordPartitioned>(
+        &self,
+        policy: DuplicatePolicy,
+    ) -> Result<RepairReport> {
+        return repair_partition::<_, T>(self, policy, |_| {});
+    }
+
+    /// Repairs the partition of the [`DatabaseRecordPartitioned`], then rewrites all records sorted by unique identifier
+    ///
+    /// Unlike [`Database::repair_partition`], a partition without duplicates is still repaired if it isn't sorted.
+    ///
+    /// See [`Database::repair_partition`] for details and the list of possible errors.
+    fn repair_partition_sorted<T: DatabaseRecordPartitioned>(
+        &self,
+        policy: DuplicatePolicy,
+    ) -> Result<RepairReport>
+    where
+        T::Unique: Ord, {
+        return repair_partition(self, policy, |records: &mut Vec<T>| {
+            records.sort_by_key(|record| record.unique_value());
+        });
+    }
+
+    /// Copies the partition `from` into a new partition `to` (e.g. to snapshot it before a risky migration)
+    ///
+    /// The copy is independent of the original, see [`DatabaseIO::try_copy_storage`].
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Either partition name is invalid
+    /// - The partition `to` already exists
+    fn clone_partition(&self, from: &str, to: &str) -> Result<()> {
+        validate_partition_name(from)?;
+        validate_partition_name(to)?;
+
+        let destination = self.file_path(to);
+        if self.storage_exists(&destination)? {
+            return Err(Error::DBOperationFailure {
+                path: destination.display().to_string(),
+                reason: format!("Partition [{to}] already exists when cloning [{from}]."),
+            });
+        }
+
+        return self.try_copy_storage(self.file_path(from), destination);
+    }
+
+    /// Counts the records in every partition listed by [`DatabaseIO::list_partitions`]
+    ///
+    /// Partitions are parsed without knowing their record type, as a list of arbitrary values.
+    /// Backups are not partitions, and are never counted.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    fn partition_stats(&self) -> Result<BTreeMap<String, usize>> {
+        return self
+            .list_partitions()?
+            .into_iter()
+            .map(|partition| {
+                let records =
+                    self.try_read_storage::<Vec<IgnoredAny>>(self.file_path(&partition))?;
+
+                return Ok((partition, records.len()));
+            })
+            .collect();
+    }
+
+    /// Returns the total size in bytes of every backup in the storage's base directory
+    ///
+    /// # Errors
+    /// - I/O
+    fn backups_total_size(&self) -> Result<u64> {
+        return self
+            .list_storages(self.dir())?
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|extension| extension == "bak"))
+            .map(|path| self.storage_size(path))
+            .sum();
+    }
+
+    /// Prunes the backups of every partition listed by [`DatabaseIO::list_partitions`], keeping only the
+    /// `keep_per_partition` most recent backups of each, returning the number of backups removed
+    ///
+    /// See [`DatabaseIO::prune_backups`] for details.
+    ///
+    /// # Errors
+    /// - I/O
+    fn prune_all_backups(&self, keep_per_partition: usize) -> Result<usize> {
+        return self
+            .list_partitions()?
+            .into_iter()
+            .map(|partition| self.prune_backups(self.file_path(partition), keep_per_partition))
+            .sum();
+    }
 }
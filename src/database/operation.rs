@@ -1,33 +1,465 @@
-use crate::{Deserialize, Result, Serialize, database::*, record::*};
+use crate::{
+    Deserialize, Error, Result, Serialize,
+    database::*,
+    record::*,
+    utils::{check_is_all_new_uniques, try_read_record_batches_from},
+};
+use serde_core::de::IgnoredAny;
+use std::{
+    collections::BTreeMap,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// The count of records inserted at once by [`DatabaseOps::import_stream`]
+pub const IMPORT_BATCH_SIZE: usize = 1_000;
 
 /// Provides common database operations using [`DatabaseRecordPartitioned::PARTITION`] as path for [`DatabaseOpsCustom`]
 ///
 /// See [`DatabaseOpsCustom`] for details and the list of possible errors.
 pub trait DatabaseOps: DatabaseOpsCustom {
+    /// Returns the path of the [`DatabaseRecordPartitioned`]'s partition
+    ///
+    /// # Errors
+    /// - Invalid partition name, see [`validate_partition_name`]
+    fn partition_path<T: DatabaseRecordPartitioned>(&self) -> Result<PathBuf> {
+        validate_partition_name(T::PARTITION)?;
+
+        return Ok(self.file_path(T::PARTITION));
+    }
+
     /// Retrieves all [`DatabaseRecordPartitioned`] from storage
     ///
+    /// For [`DatabaseRecordPartitioned::ROUTED`] records, every routed partition is read and combined in
+    /// the order of their names.
+    ///
     /// # Errors
     /// - I/O
-    /// - Duplicate unique identifiers
+    /// - Duplicate unique identifiers, with [`DatabaseOpsCustom::strict_reads`]
     fn get_all<T: DatabaseRecordPartitioned>(&self) -> Result<Vec<T>> {
-        return self.get_all_with_path(self.file_path(T::PARTITION));
+        if !T::ROUTED {
+            return self.get_all_with_path(self.partition_path::<T>()?);
+        }
+
+        let mut partitions = self
+            .list_partitions()?
+            .into_iter()
+            .filter(|p| is_routed_partition::<T>(p))
+            .collect::<Vec<_>>();
+        if partitions.is_empty() {
+            return self.get_all_with_path(self.partition_path::<T>()?);
+        }
+        partitions.sort();
+
+        let mut records = vec![];
+        for partition in partitions {
+            records.extend(self.get_all_with_path::<T>(self.file_path(partition))?);
+        }
+
+        return Ok(records);
+    }
+
+    /// Retrieves all [`DatabaseRecordPartitioned`] from storage, indexed by unique value for constant time lookups
+    ///
+    /// Useful for many lookups after reading, instead of a linear scan each. Storage is unchanged.
+    ///
+    /// See [`DatabaseOps::get_all`] for details and the list of possible errors.
+    fn get_all_indexed<T: DatabaseRecordPartitioned>(&self) -> Result<Records<T>> {
+        return Ok(Records::new(self.get_all()?));
+    }
+
+    /// Retrieves all [`DatabaseRecordPartitioned`] from storage, logging every unknown field that was dropped
+    ///
+    /// Unknown fields never fail a read, they are silently ignored by [`DatabaseOps::get_all`] as well
+    /// (unless the record opts out with `#[serde(deny_unknown_fields)]`, which the engine can't override).
+    /// This surfaces the dropped fields instead, e.g. to spot data written by a newer schema before it's lost on the next write.
+    ///
+    /// See [`DatabaseOps::get_all`] for details and the list of possible errors.
+    fn get_all_lenient<T: DatabaseRecordPartitioned>(&self) -> Result<Vec<T>> {
+        return self.get_all_lenient_with_path(self.partition_path::<T>()?);
+    }
+
+    /// Validates all [`DatabaseRecordPartitioned`] in storage
+    ///
+    /// Every record must parse, with failures attributed to the offending record and field
+    /// (e.g. `[0].id: invalid value: integer 0, expected a nonzero u64` for a zero [`NonZero`](std::num::NonZero) unique),
+    /// and no unique identifier may be duplicated. Like [`Database::integrity_check`], validating has no side effects,
+    /// a storage failing to parse isn't backed up.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure ([`Error::DBCorrupt`])
+    /// - Duplicate unique identifiers
+    fn validate<T: DatabaseRecordPartitioned>(&self) -> Result<()> {
+        return self.validate_with_path::<T>(self.partition_path::<T>()?);
+    }
+
+    /// Retrieves all records stored in the given partition as the specified [`DatabaseRecord`]
+    ///
+    /// Unlike [`DatabaseOps::get_all`], the record type is decoupled from [`DatabaseRecordPartitioned::PARTITION`],
+    /// allowing a partition to be read as any structurally compatible type (e.g. when probing a schema migration).
+    ///
+    /// See [`DatabaseOps::get_all`] for details and the list of possible errors.
+    fn get_all_as<T: DatabaseRecord>(&self, partition: &str) -> Result<Vec<T>> {
+        return self.get_all_by_name(partition);
+    }
+
+    /// Retrieves all [`DatabaseRecordPartitioned`] from a backup of their partition, e.g. to preview it before restoring
+    ///
+    /// The backup must be one of [`DatabaseIO::list_backups`] for [`DatabaseRecordPartitioned::PARTITION`],
+    /// so a backup of another partition isn't mistakenly read as `T`.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    /// - The backup isn't a backup of the partition
+    fn read_backup<T: DatabaseRecordPartitioned>(
+        &self,
+        backup_path: impl AsRef<Path>,
+    ) -> Result<Vec<T>> {
+        if BackupInfo::parse(self.partition_path::<T>()?, &backup_path).is_none() {
+            return Err(Error::DBOperationFailure {
+                path: backup_path.as_ref().display().to_string(),
+                reason: format!(
+                    "Found a storage that isn't a backup of partition [{}] when reading backup.",
+                    T::PARTITION
+                ),
+            });
+        }
+
+        return self.get_all_with_path(backup_path);
+    }
+
+    /// Retrieves all [`DatabaseRecordPartitioned`] from storage, deserialized as a projection `P` of the record
+    ///
+    /// `P` must be structurally compatible with the record: a subset of its fields with the same names and types,
+    /// every other field is skipped while deserializing instead of being parsed (and allocated).
+    /// Unlike [`DatabaseOps::get_all`], only [`DatabaseRecordPartitioned::PARTITION`] is read, even for
+    /// [`DatabaseRecordPartitioned::ROUTED`] records.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure (e.g. `P` isn't structurally compatible)
+    fn project_all<T: DatabaseRecordPartitioned, P: for<'a> Deserialize<'a>>(
+        &self,
+    ) -> Result<Vec<P>> {
+        return self.try_read_storage::<Vec<P>>(self.partition_path::<T>()?);
+    }
+
+    /// Retrieves all [`RangeQueryable`] from storage whose unique identifier is within the inclusive range from `lo` to `hi`
+    ///
+    /// See [`DatabaseOps::get_all`] for details and the list of possible errors.
+    fn get_range<T: RangeQueryable>(&self, lo: &T::Unique, hi: &T::Unique) -> Result<Vec<T>> {
+        return Ok(self
+            .get_all::<T>()?
+            .into_iter()
+            .filter(|r| T::unique_in_range(&r.unique_value(), lo, hi))
+            .collect());
+    }
+
+    /// Aggregates all [`DatabaseRecordPartitioned`] in storage, folding each record into the accumulator in order
+    ///
+    /// The partition is read in full like [`DatabaseOps::get_all`], but the records are dropped as soon as
+    /// the fold completes instead of being returned.
+    ///
+    /// See [`DatabaseOps::get_all`] for details and the list of possible errors.
+    fn fold_all<T: DatabaseRecordPartitioned, A, F: FnMut(A, &T) -> A>(
+        &self,
+        init: A,
+        f: F,
+    ) -> Result<A> {
+        return Ok(self.get_all::<T>()?.iter().fold(init, f));
+    }
+
+    /// Counts the [`DatabaseRecordPartitioned`] in storage matching the predicate, without collecting them
+    ///
+    /// See [`DatabaseOps::fold_all`] for details and the list of possible errors.
+    fn count_where<T: DatabaseRecordPartitioned, F: Fn(&T) -> bool>(
+        &self,
+        pred: F,
+    ) -> Result<usize> {
+        return self.fold_all::<T, _, _>(0, |count, r| count + usize::from(pred(r)));
+    }
+
+    /// Retrieves the first [`DatabaseRecordPartitioned`] in storage matching the predicate, in the order of [`DatabaseOps::get_all`]
+    ///
+    /// The partition is read in full like [`DatabaseOps::get_all`], but the scan stops at the first match.
+    ///
+    /// See [`DatabaseOps::get_all`] for details and the list of possible errors.
+    fn find_first<T: DatabaseRecordPartitioned, F: Fn(&T) -> bool>(
+        &self,
+        pred: F,
+    ) -> Result<Option<T>> {
+        return Ok(self.get_all::<T>()?.into_iter().find(|r| pred(r)));
+    }
+
+    /// Checks whether storage has no [`DatabaseRecordPartitioned`], true for a missing partition as well
+    ///
+    /// The records are skipped while deserializing instead of being parsed. A partition is still read in full,
+    /// but [`DatabaseRecordPartitioned::ROUTED`] records stop at the first routed partition with any record.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure (e.g. the storage isn't a list)
+    fn is_empty<T: DatabaseRecordPartitioned>(&self) -> Result<bool> {
+        let mut paths = vec![self.partition_path::<T>()?];
+        if T::ROUTED {
+            paths.extend(
+                self.list_partitions()?
+                    .into_iter()
+                    .filter(|p| p != T::PARTITION && is_routed_partition::<T>(p))
+                    .map(|p| self.file_path(p)),
+            );
+        }
+
+        for path in paths {
+            if !self.storage_exists(&path)? {
+                continue;
+            }
+
+            if !self.try_read_storage::<Vec<IgnoredAny>>(&path)?.is_empty() {
+                return Ok(false);
+            }
+        }
+
+        return Ok(true);
     }
 
     /// Inserts a single [`DatabaseRecordPartitioned`] into storage.
     /// The record is wrapped into a slice and passed to [`DatabaseOps::insert_all`].
     ///
     /// See [`DatabaseOps::insert_all`] for details and the list of possible errors.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use lupabase::prelude::*;
+    /// # use serde::{Deserialize, Serialize};
+    /// #
+    /// # #[derive(Serialize, Deserialize)]
+    /// # struct User { id: u32 }
+    /// #
+    /// # impl DatabaseRecord for User {
+    /// #    type Unique = u32;
+    /// #
+    /// #    fn unique_value(&self) -> Self::Unique { self.id }
+    /// # }
+    /// #
+    /// # impl DatabaseRecordPartitioned for User {
+    /// #    const PARTITION: &str = "users";
+    /// # }
+    /// #
+    /// fn register(db: &impl Database, id: u32) -> lupabase::Result<()> {
+    ///     db.insert(User { id })?;
+    ///
+    ///     return Ok(());
+    /// }
+    ///
+    /// # fn main() -> lupabase::Result<()> {
+    /// let db = MemoryDB::<JsonSerde>::new("Users");
+    /// db.try_initialize_storage::<User, _>(vec![User { id: 1 }])?;
+    ///
+    /// register(&db, 2)?;
+    /// assert_eq!(db.get_all::<User>()?.len(), 2);
+    ///
+    /// // Inserting an existing unique identifier fails, without writing anything
+    /// let error = register(&db, 2).unwrap_err();
+    /// assert!(matches!(error, lupabase::Error::DBOperationFailure { .. }));
+    /// assert_eq!(db.get_all::<User>()?.len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
     fn insert<T: DatabaseRecordPartitioned>(&self, new_record: T) -> Result<()> {
-        return self.insert_with_path(new_record, self.file_path(T::PARTITION));
+        if T::ROUTED {
+            return self.insert_all([new_record]);
+        }
+
+        return self.insert_with_path(new_record, self.partition_path::<T>()?);
+    }
+
+    /// Inserts a single [`DatabaseRecordPartitioned`] into storage, unless its unique identifier already exists
+    ///
+    /// Returns `Ok(Err(existing))` with the stored record on conflict, instead of failing like [`DatabaseOps::insert`].
+    /// [`DatabaseRecordPartitioned::ROUTED`] records are inserted into their routed partition, which is created
+    /// if missing, as [`DatabaseOps::insert_all`] does.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    fn try_insert<T: DatabaseRecordPartitioned>(
+        &self,
+        new_record: T,
+    ) -> Result<std::result::Result<(), T>> {
+        if !T::ROUTED {
+            return self.try_insert_with_path(new_record, self.partition_path::<T>()?);
+        }
+
+        let route = new_record.route();
+        validate_route::<T>(&route)?;
+        let path = self.file_path(route);
+
+        if !self.storage_exists(&path)? {
+            self.try_write_storage(vec![new_record], path)?;
+
+            return Ok(Ok(()));
+        }
+
+        return self.try_insert_with_path(new_record, path);
+    }
+
+    /// Replaces the [`DatabaseRecordPartitioned`] with the old unique identifier by a record with a new
+    /// unique identifier, in a single write
+    ///
+    /// The new record takes the position of the old record.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Unmatched old unique identifier
+    /// - The new unique identifier already exists, and differs from the old unique identifier
+    fn rekey<T: DatabaseRecordPartitioned>(
+        &self,
+        old_unique: &T::Unique,
+        new_record: T,
+    ) -> Result<()> {
+        return self.rekey_with_path(
+            old_unique,
+            new_record,
+            unrouted_partition_path::<_, T>(self, "rekeying")?,
+        );
+    }
+
+    /// Mutates the [`DatabaseRecordPartitioned`] with the unique identifier in place (e.g. to bump a `last_accessed` field)
+    ///
+    /// Unlike [`DatabaseOps::upsert`], the record must already exist, and the mutation can't change its unique identifier.
+    /// [`MemoryDB`](crate::prelude::MemoryDB) only writes the mutated record if the partition wasn't written
+    /// since it was read, so concurrent writes to the partition can't be lost.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Unmatched unique identifier
+    /// - The mutation changes the unique identifier
+    /// - The partition was written concurrently, with [`MemoryDB`](crate::prelude::MemoryDB) ([`Error::DBTransactionConflict`])
+    fn touch<T: DatabaseRecordPartitioned, F: FnOnce(&mut T)>(
+        &self,
+        unique: &T::Unique,
+        f: F,
+    ) -> Result<()> {
+        return self.touch_with_path(
+            unique,
+            f,
+            unrouted_partition_path::<_, T>(self, "touching")?,
+        );
+    }
+
+    /// Mutates the [`DatabaseRecordPartitioned`] with the unique identifier in place, inserting `default()` first if it's missing
+    ///
+    /// Useful for counters (e.g. incrementing a field, starting from a zeroed record). `update` is applied to the
+    /// inserted record as well, and a missing partition is created. Nothing is written if either `default` or
+    /// `update` produces a record with another unique identifier.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    /// - The default record has another unique identifier
+    /// - The mutation changes the unique identifier
+    fn upsert_with<T: DatabaseRecordPartitioned, F: FnOnce(&mut T), D: FnOnce() -> T>(
+        &self,
+        unique: &T::Unique,
+        update: F,
+        default: D,
+    ) -> Result<()> {
+        return self.upsert_in_place_with_path(
+            unique,
+            update,
+            default,
+            unrouted_partition_path::<_, T>(self, "upserting in place")?,
+        );
     }
 
     /// Inserts multiple [`DatabaseRecordPartitioned`] into storage
     ///
+    /// [`DatabaseRecordPartitioned::ROUTED`] records are grouped by [`DatabaseRecordPartitioned::route`],
+    /// each group being inserted into its own partition, which is created if missing.
+    /// Uniques are only checked within each routed partition.
+    ///
     /// # Errors
     /// - I/O
     /// - Duplicate unique identifier is found among the new records
     fn insert_all<T: DatabaseRecordPartitioned>(&self, new_records: impl AsRef<[T]>) -> Result<()> {
-        return self.insert_all_with_path(new_records, self.file_path(T::PARTITION));
+        if !T::ROUTED {
+            return self.insert_all_with_path(new_records, self.partition_path::<T>()?);
+        }
+
+        let mut routes = BTreeMap::<String, Vec<&T>>::new();
+        for record in new_records.as_ref() {
+            routes.entry(record.route()).or_default().push(record);
+        }
+
+        // Every route is checked before any is written, so a conflict doesn't leave a partial insert behind
+        let mut writes = vec![];
+        for (route, new_records) in routes {
+            validate_route::<T>(&route)?;
+            let path = self.file_path(route);
+            let records = match self.get_all_with_path::<T>(&path) {
+                Ok(records) => records,
+                Err(Error::DBNotFound { .. }) => vec![],
+                Err(e) => return Err(e),
+            };
+
+            let uniques = new_records
+                .iter()
+                .map(|r| r.unique_value())
+                .collect::<Vec<_>>();
+            check_is_all_new_uniques(&records, &uniques, &path, self.verbose_errors())?;
+
+            writes.push((path, records, new_records));
+        }
+
+        for (path, records, new_records) in writes {
+            self.try_write_storage(records.iter().chain(new_records).collect::<Vec<&T>>(), path)?;
+        }
+
+        return Ok(());
+    }
+
+    /// Loads multiple [`DatabaseRecordPartitioned`] into an empty (or missing) storage
+    ///
+    /// Unlike [`DatabaseOps::insert_all`], the existing records aren't read to be merged with the new records,
+    /// only duplicates among the new records are checked before writing them directly.
+    /// A storage holding the engine's own encoding of no records is recognized as empty without being deserialized.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - The storage already contains records
+    /// - Duplicate unique identifier is found among the new records
+    fn bulk_load<T: DatabaseRecordPartitioned>(&self, new_records: impl AsRef<[T]>) -> Result<()> {
+        return self.bulk_load_with_path(
+            new_records,
+            unrouted_partition_path::<_, T>(self, "bulk loading")?,
+        );
+    }
+
+    /// Imports [`DatabaseRecordPartitioned`] streamed from the reader (e.g. a large external dump) into storage
+    ///
+    /// The reader must contain a sequence of records in the engine's format (a JSON or CBOR array).
+    /// The records are parsed as they're read, and inserted in batches of [`IMPORT_BATCH_SIZE`] records
+    /// like [`DatabaseOps::insert_all`], so neither the input nor the parsed records are ever held whole in memory.
+    /// Returns the count of records imported.
+    ///
+    /// A batch is only inserted once all of its records are new, but the batches inserted before a failure
+    /// (e.g. a duplicate unique identifier, or malformed input) stay imported.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    /// - Duplicate unique identifier is found among the new records
+    fn import_stream<T: DatabaseRecordPartitioned, R: Read>(&self, mut reader: R) -> Result<usize> {
+        if !T::ROUTED {
+            return self.import_stream_with_path::<T, R>(reader, self.partition_path::<T>()?);
+        }
+
+        return try_read_record_batches_from(self, &mut reader, &mut |batch: Vec<T>| {
+            return self.insert_all(batch);
+        });
     }
 
     /// Updates a single [`DatabaseRecordPartitioned`] in storage.
@@ -35,7 +467,10 @@ pub trait DatabaseOps: DatabaseOpsCustom {
     ///
     /// See [`DatabaseOps::update_all`] for details and the list of possible errors.
     fn update<T: DatabaseRecordPartitioned>(&self, updated_record: T) -> Result<()> {
-        return self.update_with_path(updated_record, self.file_path(T::PARTITION));
+        return self.update_with_path(
+            updated_record,
+            unrouted_partition_path::<_, T>(self, "updating")?,
+        );
     }
 
     /// Updates multiple [`DatabaseRecordPartitioned`] in storage
@@ -48,7 +483,10 @@ pub trait DatabaseOps: DatabaseOpsCustom {
         &self,
         updated_records: impl IntoIterator<Item = T>,
     ) -> Result<()> {
-        return self.update_all_with_path(updated_records, self.file_path(T::PARTITION));
+        return self.update_all_with_path(
+            updated_records,
+            unrouted_partition_path::<_, T>(self, "updating")?,
+        );
     }
 
     /// Updates or inserts a single [`DatabaseRecordPartitioned`] into storage.
@@ -56,11 +494,18 @@ pub trait DatabaseOps: DatabaseOpsCustom {
     ///
     /// See [`DatabaseOps::upsert_all`] for details and the list of possible errors.
     fn upsert<T: DatabaseRecordPartitioned>(&self, upserted_record: T) -> Result<()> {
-        return self.upsert_with_path(upserted_record, self.file_path(T::PARTITION));
+        return self.upsert_with_path(
+            upserted_record,
+            unrouted_partition_path::<_, T>(self, "upserting")?,
+        );
     }
 
     /// Updates or inserts multiple [`DatabaseRecordPartitioned`] into storage
     ///
+    /// Updated records keep their position, while inserted records are appended in the order they are given.
+    /// A missing partition is created.
+    /// Use [`DatabaseOps::upsert_all_sorted`] when the stored order should not depend on the upsert history.
+    ///
     /// # Errors
     /// - I/O
     /// - Duplicate unique identifier is found among the upserted records
@@ -68,7 +513,28 @@ pub trait DatabaseOps: DatabaseOpsCustom {
         &self,
         upserted_records: impl IntoIterator<Item = T>,
     ) -> Result<()> {
-        return self.upsert_all_with_path(upserted_records, self.file_path(T::PARTITION));
+        return self.upsert_all_with_path(
+            upserted_records,
+            unrouted_partition_path::<_, T>(self, "upserting")?,
+        );
+    }
+
+    /// Updates or inserts multiple [`DatabaseRecordPartitioned`] into storage, then rewrites all records
+    /// sorted by unique identifier
+    ///
+    /// The stored order is deterministic regardless of the order in which records were inserted or upserted.
+    ///
+    /// See [`DatabaseOps::upsert_all`] for details and the list of possible errors.
+    fn upsert_all_sorted<T: DatabaseRecordPartitioned>(
+        &self,
+        upserted_records: impl IntoIterator<Item = T>,
+    ) -> Result<()>
+    where
+        T::Unique: Ord, {
+        return self.upsert_all_sorted_with_path(
+            upserted_records,
+            unrouted_partition_path::<_, T>(self, "upserting")?,
+        );
     }
 
     /// Replace all [`DatabaseRecordPartitioned`] in storage with the provided [`DatabaseRecordPartitioned`]
@@ -80,7 +546,81 @@ pub trait DatabaseOps: DatabaseOpsCustom {
         &self,
         replaced_records: impl IntoIterator<Item = T>,
     ) -> Result<()> {
-        return self.replace_all_with_path(replaced_records, self.file_path(T::PARTITION));
+        return self.replace_all_with_path(
+            replaced_records,
+            unrouted_partition_path::<_, T>(self, "replacing")?,
+        );
+    }
+
+    /// Replace all [`DatabaseRecordPartitioned`] in storage with the provided [`DatabaseRecordPartitioned`],
+    /// only if they differ from the stored records, returning whether storage was written
+    ///
+    /// Records are compared regardless of their order, by unique identifier and then by content.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Duplicate unique identifier is found among the replaced records
+    fn replace_all_if_changed<T: DatabaseRecordPartitioned + PartialEq>(
+        &self,
+        replaced_records: impl IntoIterator<Item = T>,
+    ) -> Result<bool> {
+        return self.replace_all_if_changed_with_path(
+            replaced_records,
+            unrouted_partition_path::<_, T>(self, "replacing")?,
+        );
+    }
+
+    /// Replace all [`DatabaseRecordPartitioned`] in storage with the provided [`DatabaseRecordPartitioned`],
+    /// only if `guard` accepts the stored records, returning whether storage was written
+    ///
+    /// A coarse compare-and-swap, e.g. to only overwrite if the stored records still match what they were
+    /// computed from. A missing partition is passed to `guard` as empty. The check and the write aren't atomic,
+    /// see [`DatabaseIO::compare_and_write`] for a byte-level compare-and-swap.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    /// - Duplicate unique identifier is found among the replaced records
+    fn replace_all_if<T: DatabaseRecordPartitioned, G: FnOnce(&[T]) -> bool>(
+        &self,
+        replaced_records: impl IntoIterator<Item = T>,
+        guard: G,
+    ) -> Result<bool> {
+        return self.replace_all_if_with_path(
+            replaced_records,
+            guard,
+            unrouted_partition_path::<_, T>(self, "replacing")?,
+        );
+    }
+
+    /// Applies a changeset of inserts, updates, and deletes of [`DatabaseRecordPartitioned`] into storage
+    ///
+    /// The whole changeset is validated before anything is applied, and storage is written only once.
+    /// Updated records keep their position, deleted records are removed, and inserted records are appended.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Duplicate unique identifier is found among the inserted records
+    /// - Unmatched unique identifier is found among the updated records or deleted uniques
+    fn apply_changes<T: DatabaseRecordPartitioned>(
+        &self,
+        inserts: Vec<T>,
+        updates: Vec<T>,
+        deletes: Vec<T::Unique>,
+    ) -> Result<()> {
+        return self.apply_changes_with_path(
+            inserts,
+            updates,
+            deletes,
+            unrouted_partition_path::<_, T>(self, "applying changes")?,
+        );
+    }
+
+    /// Starts a batch of inserts, updates, and deletes of [`DatabaseRecordPartitioned`], applied at once
+    ///
+    /// See [`BatchBuilder`] for details.
+    fn batch<T: DatabaseRecordPartitioned>(&self) -> BatchBuilder<'_, Self, T> {
+        return BatchBuilder::new(self);
     }
 
     /// Attempts to initialize the provided default [`DatabaseRecordPartitioned`] into storage
@@ -100,6 +640,62 @@ pub trait DatabaseOps: DatabaseOpsCustom {
         default_data: O,
     ) -> Result<()> {
         return self
-            .try_initialize_storage_with_path::<O>(default_data, self.file_path(T::PARTITION));
+            .try_initialize_storage_with_path::<O>(default_data, self.partition_path::<T>()?);
     }
 }
+
+/// Returns whether the partition is [`DatabaseRecordPartitioned::PARTITION`] or one of its routed partitions
+fn is_routed_partition<T: DatabaseRecordPartitioned>(partition: &str) -> bool {
+    return match partition.split_once(ROUTE_SEPARATOR) {
+        Some((partition, _)) => partition == T::PARTITION,
+        None => partition == T::PARTITION,
+    };
+}
+
+/// Checks that the route of a [`DatabaseRecordPartitioned::ROUTED`] record is a partition of its type
+///
+/// # Errors
+/// - Invalid partition name, see [`validate_partition_name`]
+/// - The route isn't [`DatabaseRecordPartitioned::PARTITION`], nor prefixed by it and [`ROUTE_SEPARATOR`]
+fn validate_route<T: DatabaseRecordPartitioned>(route: &str) -> Result<()> {
+    validate_partition_name(T::PARTITION)?;
+
+    let is_valid = match route.split_once(ROUTE_SEPARATOR) {
+        Some((partition, suffix)) => {
+            partition == T::PARTITION && validate_partition_name(suffix).is_ok()
+        }
+        None => route == T::PARTITION,
+    };
+    if !is_valid {
+        return Err(Error::DBInvalidPartition {
+            partition: route.to_string(),
+        });
+    }
+
+    return Ok(());
+}
+
+/// Returns the path of the [`DatabaseRecordPartitioned`]'s partition, for operations which don't follow
+/// [`DatabaseRecordPartitioned::route`]
+///
+/// # Errors
+/// - Invalid partition name, see [`validate_partition_name`]
+/// - The records are [`DatabaseRecordPartitioned::ROUTED`]
+fn unrouted_partition_path<DB: DatabaseOps + ?Sized, T: DatabaseRecordPartitioned>(
+    database: &DB,
+    operation: &str,
+) -> Result<PathBuf> {
+    let path = database.partition_path::<T>()?;
+
+    if T::ROUTED {
+        return Err(Error::DBOperationFailure {
+            path: path.display().to_string(),
+            reason: format!(
+                "Found routed records of partition [{}] when {operation}, which only supports a single partition.",
+                T::PARTITION
+            ),
+        });
+    }
+
+    return Ok(path);
+}
@@ -74,6 +74,27 @@ pub trait DatabaseOpsOperatable: DatabaseOps + Sized {
     ) -> Result<()> {
         return R::try_initialize_storage(self, default_data);
     }
+
+    /// See [`DatabaseStorageOperatable::get`] for details and the list of possible errors.
+    fn get_with_storage_operatable<R: DatabaseStorageOperatable>(&self) -> Result<R::Collection> {
+        return R::get(self);
+    }
+
+    /// See [`DatabaseStorageOperatable::write`] for details and the list of possible errors.
+    fn write_with_storage_operatable<R: DatabaseStorageOperatable>(
+        &self,
+        data: R::Collection,
+    ) -> Result<()> {
+        return R::write(self, data);
+    }
+
+    /// See [`DatabaseStorageOperatable::try_initialize_storage`] for details and the list of possible errors.
+    fn try_initialize_storage_with_storage_operatable<R: DatabaseStorageOperatable>(
+        &self,
+        default_data: R::Collection,
+    ) -> Result<()> {
+        return R::try_initialize_storage(self, default_data);
+    }
 }
 
 impl<DB: DatabaseOps> DatabaseOpsOperatable for DB {}
@@ -1,21 +1,94 @@
+#[cfg(feature = "json")]
+use crate::error::DeserializeError;
 use crate::{
     Deserialize, Error, Result, Serialize,
     database::*,
     record::{utils::*, *},
     utils::*,
 };
-use std::{borrow::Borrow, path::Path};
+use std::{borrow::Borrow, collections::HashSet, io::Read, path::Path};
+use tracing::{Span, field, instrument, warn};
 
 /// Provides common database operations with arbritary paths for [`DatabaseIO`]
 ///
 /// This trait supplies generic implementations for initializing, retrieving,
 /// inserting, updating, and replacing records in the database.
 pub trait DatabaseOpsCustom: DatabaseIO {
+    /// Returns whether errors list every offending unique value in full
+    ///
+    /// By default, only the first few unique values are listed to keep errors (and logs) bounded in size.
+    fn verbose_errors(&self) -> bool { false }
+
+    /// Returns whether reads check the records for duplicate unique identifiers
+    ///
+    /// Off by default, as every read then pays for the check. When on, [`DatabaseOpsCustom::get_all_with_path`]
+    /// (and every operation reading through it) and [`DatabaseOpsCustom::read_modify_write_records`]
+    /// (e.g. upserts and merge-commits) fail with [`Error::DBCorrupt`] on duplicates,
+    /// while [`DatabaseOpsCustom::get_all_lenient_with_path`] still reads them.
+    fn strict_reads(&self) -> bool { false }
+
     /// Read all [`DatabaseRecord`] from the given path
     ///
     /// See [`DatabaseOps::get_all`] for details and the list of possible errors.
+    #[instrument(level = "debug", skip_all, fields(path = %path.as_ref().display(), records = field::Empty))]
     fn get_all_with_path<T: DatabaseRecord>(&self, path: impl AsRef<Path>) -> Result<Vec<T>> {
-        return self.try_read_storage::<Vec<T>>(path);
+        #[cfg(feature = "metrics")]
+        let _metrics = OperationMetrics::start("get_all", &path);
+
+        let records = self.try_read_storage::<Vec<T>>(&path)?;
+        Span::current().record("records", records.len());
+
+        if self.strict_reads() {
+            check_is_all_unique_records(&records, &path, self.verbose_errors())?;
+        }
+
+        return Ok(records);
+    }
+
+    /// Read all [`DatabaseRecord`] from the given path, logging every unknown field that was ignored
+    ///
+    /// See [`DatabaseOps::get_all_lenient`] for details and the list of possible errors.
+    fn get_all_lenient_with_path<T: DatabaseRecord>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<T>> {
+        let (records, ignored) = self.try_read_storage_lenient::<Vec<T>>(&path)?;
+        for field in ignored {
+            warn!(
+                "Ignored unknown field [{field}] in partition at [{}]",
+                path.as_ref().display()
+            );
+        }
+
+        return Ok(records);
+    }
+
+    /// Read all [`DatabaseRecord`] from the storage with the given name
+    ///
+    /// Unlike [`DatabaseOpsCustom::get_all_with_path`], which expects a full path (e.g. built with [`DatabaseIO::file_path`]),
+    /// the name is resolved through [`DatabaseIO::file_path`], so the extension is added automatically.
+    ///
+    /// See [`DatabaseOps::get_all`] for details and the list of possible errors.
+    fn get_all_by_name<T: DatabaseRecord>(&self, name: impl AsRef<Path>) -> Result<Vec<T>> {
+        return self.get_all_with_path(self.file_path(name));
+    }
+
+    /// Validates all [`DatabaseRecord`] stored in the given path
+    ///
+    /// See [`DatabaseOps::validate`] for details and the list of possible errors.
+    fn validate_with_path<T: DatabaseRecord>(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+
+        // Parsed from the raw bytes, as failed reads back up the storage (see `DatabaseIO::check_storage`)
+        let bytes = self.try_read_storage_bytes(path)?;
+        let records = self
+            .read_storage_from::<_, Vec<T>>(&mut bytes.as_slice())
+            .map_err(|e| Error::DBCorrupt {
+                file_path: path.to_path_buf(),
+                reason: e.to_string(),
+            })?;
+
+        return check_is_all_unique_records(&records, path, self.verbose_errors());
     }
 
     /// Inserts a single [`DatabaseRecord`] into the given path
@@ -29,18 +102,78 @@ pub trait DatabaseOpsCustom: DatabaseIO {
         return self.insert_all_with_path([updated_record], path);
     }
 
+    /// Inserts a single [`DatabaseRecord`] into the given path, returning the existing record on conflict
+    ///
+    /// See [`DatabaseOps::try_insert`] for details and the list of possible errors.
+    fn try_insert_with_path<T: DatabaseRecord>(
+        &self,
+        new_record: T,
+        path: impl AsRef<Path>,
+    ) -> Result<std::result::Result<(), T>> {
+        let mut records = self.get_all_with_path::<T>(&path)?;
+
+        let unique_value = new_record.unique_value();
+        if let Some(index) = records.iter().position(|r| r.unique_eq(&unique_value)) {
+            return Ok(Err(records.swap_remove(index)));
+        }
+
+        records.push(new_record);
+        self.try_write_storage(records, path)?;
+
+        return Ok(Ok(()));
+    }
+
+    /// Replaces the record with the old unique identifier by a record with a new unique identifier in the given path
+    ///
+    /// See [`DatabaseOps::rekey`] for details and the list of possible errors.
+    fn rekey_with_path<T: DatabaseRecord>(
+        &self,
+        old_unique: &T::Unique,
+        new_record: T,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let mut records = self.get_all_with_path::<T>(&path)?;
+
+        let Some(index) = records.iter().position(|r| r.unique_eq(old_unique)) else {
+            return Err(Error::DBOperationFailure {
+                path: path.as_ref().display().to_string(),
+                reason: format!(
+                    "Found non-matching Unique value in record when rekeying: [{old_unique:?}]."
+                ),
+            });
+        };
+
+        let new_unique = new_record.unique_value();
+        if new_unique != *old_unique && records.find_by_unique(&new_unique).is_some() {
+            return Err(Error::DBOperationFailure {
+                path: path.as_ref().display().to_string(),
+                reason: format!(
+                    "Found duplicate Unique value in record when rekeying: [{new_unique:?}]."
+                ),
+            });
+        }
+
+        records[index] = new_record;
+
+        return self.try_write_storage(records, path);
+    }
+
     /// Inserts multiple [`DatabaseRecord`] into the given path
     ///
     /// See [`DatabaseOps::insert_all`] for details and the list of possible errors.
+    #[instrument(level = "debug", skip_all, fields(path = %path.as_ref().display(), records = new_records.as_ref().len()))]
     fn insert_all_with_path<T: DatabaseRecord>(
         &self,
         new_records: impl AsRef<[T]>,
         path: impl AsRef<Path>,
     ) -> Result<()> {
+        #[cfg(feature = "metrics")]
+        let _metrics = OperationMetrics::start("insert_all", &path);
+
         let records = self.get_all_with_path(&path)?;
         let new_records = new_records.as_ref();
 
-        check_is_all_new_records(&records, new_records, &path)?;
+        check_is_all_new_records(&records, new_records, &path, self.verbose_errors())?;
 
         return self.try_write_storage(
             records
@@ -51,6 +184,47 @@ pub trait DatabaseOpsCustom: DatabaseIO {
         );
     }
 
+    /// Loads multiple [`DatabaseRecord`] into the given empty path
+    ///
+    /// See [`DatabaseOps::bulk_load`] for details and the list of possible errors.
+    #[instrument(level = "debug", skip_all, fields(path = %path.as_ref().display(), records = new_records.as_ref().len()))]
+    fn bulk_load_with_path<T: DatabaseRecord>(
+        &self,
+        new_records: impl AsRef<[T]>,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        #[cfg(feature = "metrics")]
+        let _metrics = OperationMetrics::start("bulk_load", &path);
+
+        let new_records = new_records.as_ref();
+
+        let existing = try_count_stored_records(self, path.as_ref())?;
+        if existing > 0 {
+            return Err(Error::DBOperationFailure {
+                path: path.as_ref().display().to_string(),
+                reason: format!("Found [{existing}] existing record(s) when bulk loading."),
+            });
+        }
+
+        check_is_all_new_records(&[], new_records, &path, self.verbose_errors())?;
+
+        return self.try_write_storage(new_records, path);
+    }
+
+    /// Imports [`DatabaseRecord`] streamed from the reader into the given path
+    ///
+    /// See [`DatabaseOps::import_stream`] for details and the list of possible errors.
+    #[instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))]
+    fn import_stream_with_path<T: DatabaseRecord, R: Read>(
+        &self,
+        mut reader: R,
+        path: impl AsRef<Path>,
+    ) -> Result<usize> {
+        return try_read_record_batches_from(self, &mut reader, &mut |batch: Vec<T>| {
+            return self.insert_all_with_path(batch, &path);
+        });
+    }
+
     /// Updates a single [`DatabaseRecord`] into the given path
     ///
     /// See [`DatabaseOps::update`] for details and the list of possible errors.
@@ -65,15 +239,20 @@ pub trait DatabaseOpsCustom: DatabaseIO {
     /// Updates multiple [`DatabaseRecord`] into the given path
     ///
     /// See [`DatabaseOps::update_all`] for details and the list of possible errors.
+    #[instrument(level = "debug", skip_all, fields(path = %path.as_ref().display(), records = field::Empty))]
     fn update_all_with_path<T: DatabaseRecord>(
         &self,
         updated_records: impl IntoIterator<Item = T>,
         path: impl AsRef<Path>,
     ) -> Result<()> {
+        #[cfg(feature = "metrics")]
+        let _metrics = OperationMetrics::start("update_all", &path);
+
         let mut records = self.get_all_with_path(&path)?;
         let updated_records: Vec<T> = updated_records.into_iter().collect();
+        Span::current().record("records", updated_records.len());
 
-        check_is_all_existing_records(&records, &updated_records, &path)?;
+        check_is_all_existing_records(&records, &updated_records, &path, self.verbose_errors())?;
 
         updated_records.into_iter().for_each(|ur| {
             let record = records
@@ -98,32 +277,58 @@ pub trait DatabaseOpsCustom: DatabaseIO {
     /// Updates or inserts multiple [`DatabaseRecord`] into the given path
     ///
     /// See [`DatabaseOps::upsert_all`] for details and the list of possible errors.
+    #[instrument(level = "debug", skip_all, fields(path = %path.as_ref().display(), records = field::Empty))]
     fn upsert_all_with_path<T: DatabaseRecord>(
         &self,
         upserted_records: impl IntoIterator<Item = T>,
         path: impl AsRef<Path>,
     ) -> Result<()> {
-        let mut records = self.get_all_with_path(&path)?;
+        #[cfg(feature = "metrics")]
+        let _metrics = OperationMetrics::start("upsert_all", &path);
 
-        for upserted_record in upserted_records.into_iter() {
-            if let Some(record) = records.find_by_unique_mut(&upserted_record.unique_value()) {
-                *record = upserted_record;
-            } else {
-                records.push(upserted_record);
-            }
-        }
+        return self.read_modify_write_records(path, |records| {
+            let upserted_count = upsert_records(records, upserted_records);
+            Span::current().record("records", upserted_count);
 
-        return self.try_write_storage(records, path);
+            return Ok(());
+        });
+    }
+
+    /// Updates or inserts multiple [`DatabaseRecord`] into the given path, then sorts all records by unique identifier
+    ///
+    /// See [`DatabaseOps::upsert_all_sorted`] for details and the list of possible errors.
+    #[instrument(level = "debug", skip_all, fields(path = %path.as_ref().display(), records = field::Empty))]
+    fn upsert_all_sorted_with_path<T: DatabaseRecord>(
+        &self,
+        upserted_records: impl IntoIterator<Item = T>,
+        path: impl AsRef<Path>,
+    ) -> Result<()>
+    where
+        T::Unique: Ord, {
+        #[cfg(feature = "metrics")]
+        let _metrics = OperationMetrics::start("upsert_all", &path);
+
+        return self.read_modify_write_records(path, |records| {
+            let upserted_count = upsert_records(records, upserted_records);
+            Span::current().record("records", upserted_count);
+
+            records.sort_by_key(|r| r.unique_value());
+            return Ok(());
+        });
     }
 
     /// Replace all [`DatabaseRecord`] into the given path with the provided [`DatabaseRecord`]
     ///
     /// See [`DatabaseOps::replace_all`] for details and the list of possible errors.
+    #[instrument(level = "debug", skip_all, fields(path = %path.as_ref().display(), records = field::Empty))]
     fn replace_all_with_path<T: DatabaseRecord>(
         &self,
         updated_records: impl IntoIterator<Item = T>,
         path: impl AsRef<Path>,
     ) -> Result<()> {
+        #[cfg(feature = "metrics")]
+        let _metrics = OperationMetrics::start("replace_all", &path);
+
         let mut records: Vec<T> = vec![];
 
         for ur in updated_records.into_iter() {
@@ -139,10 +344,235 @@ pub trait DatabaseOpsCustom: DatabaseIO {
 
             records.push(ur);
         }
+        Span::current().record("records", records.len());
 
         return self.try_write_storage(records, path);
     }
 
+    /// Attempts to write the provided [`DatabaseRecord`] to the given path, rejecting them if any unique identifier is duplicated
+    ///
+    /// A typed [`DatabaseIO::try_write_storage`], so hand-built lists can't persist duplicates
+    /// that later break record operations (e.g. [`DatabaseOps::update_all`]).
+    /// Nothing is written if the records are rejected.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Duplicate unique identifier is found among the records
+    fn try_write_records<T: DatabaseRecord>(
+        &self,
+        records: &[T],
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        check_is_all_unique_new_records(records, &path, self.verbose_errors())?;
+
+        return self.try_write_storage(records, path);
+    }
+
+    /// Replace all [`DatabaseRecord`] into the given path, only if they differ from the stored records
+    ///
+    /// See [`DatabaseOps::replace_all_if_changed`] for details and the list of possible errors.
+    fn replace_all_if_changed_with_path<T: DatabaseRecord + PartialEq>(
+        &self,
+        replaced_records: impl IntoIterator<Item = T>,
+        path: impl AsRef<Path>,
+    ) -> Result<bool> {
+        let replaced_records: Vec<T> = replaced_records.into_iter().collect();
+
+        check_is_all_new_records(&[], &replaced_records, &path, self.verbose_errors())?;
+
+        let is_unchanged = match self.get_all_with_path::<T>(&path) {
+            Ok(records) => {
+                records.len() == replaced_records.len()
+                    && replaced_records.iter().all(|rr| {
+                        records
+                            .find_by_unique(&rr.unique_value())
+                            .is_some_and(|r| r == rr)
+                    })
+            }
+            Err(Error::DBNotFound { .. }) => false,
+            Err(e) => return Err(e),
+        };
+
+        if is_unchanged {
+            return Ok(false);
+        }
+
+        self.replace_all_with_path(replaced_records, path)?;
+        return Ok(true);
+    }
+
+    /// Replace all [`DatabaseRecord`] into the given path, only if the guard accepts the stored records
+    ///
+    /// See [`DatabaseOps::replace_all_if`] for details and the list of possible errors.
+    fn replace_all_if_with_path<T: DatabaseRecord, G: FnOnce(&[T]) -> bool>(
+        &self,
+        replaced_records: impl IntoIterator<Item = T>,
+        guard: G,
+        path: impl AsRef<Path>,
+    ) -> Result<bool> {
+        let records = match self.get_all_with_path::<T>(&path) {
+            Ok(records) => records,
+            Err(Error::DBNotFound { .. }) => vec![],
+            Err(e) => return Err(e),
+        };
+
+        if !guard(&records) {
+            return Ok(false);
+        }
+
+        self.replace_all_with_path(replaced_records, path)?;
+        return Ok(true);
+    }
+
+    /// Applies inserts, updates, and deletes of [`DatabaseRecord`] into the given path
+    ///
+    /// See [`DatabaseOps::apply_changes`] for details and the list of possible errors.
+    #[instrument(level = "debug", skip_all, fields(path = %path.as_ref().display(), inserts = inserts.len(), updates = updates.len(), deletes = deletes.len()))]
+    fn apply_changes_with_path<T: DatabaseRecord>(
+        &self,
+        inserts: Vec<T>,
+        updates: Vec<T>,
+        deletes: Vec<T::Unique>,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        #[cfg(feature = "metrics")]
+        let _metrics = OperationMetrics::start("apply_changes", &path);
+
+        let mut records = self.get_all_with_path(&path)?;
+
+        check_is_all_new_records(&records, &inserts, &path, self.verbose_errors())?;
+        check_is_all_existing_records(&records, &updates, &path, self.verbose_errors())?;
+        check_is_all_existing_uniques(&records, &deletes, &path, self.verbose_errors())?;
+
+        updates.into_iter().for_each(|ur| {
+            let record = records
+                .find_by_unique_mut(&ur.unique_value())
+                .expect("All records should exist as it was checked before.");
+            *record = ur;
+        });
+
+        // Hashing the deletes keeps this linear, borrowing the uniques of records where they can be
+        let deletes = deletes.into_iter().collect::<HashSet<_>>();
+        if !deletes.is_empty() {
+            records.retain(|r| match r.unique_ref() {
+                Some(unique) => !deletes.contains(unique),
+                None => !deletes.contains(&r.unique_value()),
+            });
+        }
+        records.extend(inserts);
+
+        return self.try_write_storage(records, path);
+    }
+
+    /// Mutates the [`DatabaseRecord`] with the unique identifier in the given path
+    ///
+    /// See [`DatabaseOps::touch`] for details and the list of possible errors.
+    fn touch_with_path<T: DatabaseRecord, F: FnOnce(&mut T)>(
+        &self,
+        unique: &T::Unique,
+        f: F,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let mut records = self.get_all_with_path::<T>(&path)?;
+
+        touch_record(&mut records, unique, f, &path, self.verbose_errors())?;
+
+        return self.try_write_storage(records, path);
+    }
+
+    /// Mutates the [`DatabaseRecord`] with the unique identifier in the given path, inserting a default one if missing
+    ///
+    /// See [`DatabaseOps::upsert_with`] for details and the list of possible errors.
+    fn upsert_in_place_with_path<T: DatabaseRecord, F: FnOnce(&mut T), D: FnOnce() -> T>(
+        &self,
+        unique: &T::Unique,
+        update: F,
+        default: D,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        return self.read_modify_write_records::<T, _>(&path, |records| {
+            return upsert_record_with(
+                records,
+                unique,
+                update,
+                default,
+                &path,
+                self.verbose_errors(),
+            );
+        });
+    }
+
+    /// Reads the list stored in the given path, mutates it, and writes it back
+    ///
+    /// A missing storage is read as an empty list, and is created by the write. Nothing is written if the
+    /// mutation fails, its error is returned as-is.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    /// - The mutation fails
+    fn read_modify_write<T, F>(&self, path: impl AsRef<Path>, f: F) -> Result<()>
+    where
+        T: Serialize + for<'a> Deserialize<'a>,
+        F: FnOnce(&mut Vec<T>) -> Result<()>, {
+        let mut records = match self.try_read_storage::<Vec<T>>(&path) {
+            Ok(records) => records,
+            Err(Error::DBNotFound { .. }) => vec![],
+            Err(e) => return Err(e),
+        };
+
+        f(&mut records)?;
+
+        return self.try_write_storage(records, path);
+    }
+
+    /// Reads the [`DatabaseRecord`] stored in the given path, mutates them, and writes them back
+    ///
+    /// Like [`DatabaseOpsCustom::read_modify_write`], but the records read are checked for duplicate
+    /// unique identifiers with [`DatabaseOpsCustom::strict_reads`], as with [`DatabaseOpsCustom::get_all_with_path`].
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    /// - Duplicate unique identifiers, with [`DatabaseOpsCustom::strict_reads`]
+    /// - The mutation fails
+    fn read_modify_write_records<T, F>(&self, path: impl AsRef<Path>, f: F) -> Result<()>
+    where
+        T: DatabaseRecord,
+        F: FnOnce(&mut Vec<T>) -> Result<()>, {
+        return self.read_modify_write::<T, _>(&path, |records| {
+            if self.strict_reads() {
+                check_is_all_unique_records(records, &path, self.verbose_errors())?;
+            }
+
+            return f(records);
+        });
+    }
+
+    /// Applies a JSON Merge Patch (RFC 7386) onto the data stored in the given path
+    ///
+    /// The stored data is read as a [`serde_json::Value`], patched, and must still deserialize into `O`
+    /// before being written back. Fields absent from the patch are left untouched, and `null` removes a field.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure (including when the patched data no longer matches `O`)
+    #[cfg(feature = "json")]
+    fn merge_patch<O: Serialize + for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+        patch: serde_json::Value,
+    ) -> Result<()> {
+        let mut value = self.try_read_storage::<serde_json::Value>(&path)?;
+
+        apply_merge_patch(&mut value, patch);
+
+        let data = serde_json::from_value::<O>(value)
+            .map_err(|e| with_path_context(DeserializeError(e).into(), &path))?;
+
+        return self.try_write_storage(data, path);
+    }
+
     /// Attempts to initialize the provided default data into the given storage path
     ///
     /// See [`DatabaseOps::try_initialize_storage`] for details and the list of possible errors.
@@ -0,0 +1,95 @@
+use crate::{Result, database::*, record::*};
+
+type Seed<DB> = Box<dyn Fn(&DB) -> Result<()>>;
+
+/// Initializes multiple partitions at once, each with its own default records
+///
+/// Partitions which already exist are left untouched, see [`DatabaseOps::try_initialize_storage`].
+///
+/// # Example
+/// ```rust
+/// # use lupabase::prelude::*;
+/// # use serde::{Deserialize, Serialize};
+/// #
+/// # #[derive(Clone, Serialize, Deserialize)]
+/// # struct User { id: u8 }
+/// #
+/// # impl DatabaseRecord for User {
+/// #    type Unique = u8;
+/// #
+/// #    fn unique_value(&self) -> Self::Unique { self.id }
+/// # }
+/// #
+/// # impl DatabaseRecordPartitioned for User {
+/// #    const PARTITION: &str = "Users";
+/// # }
+/// #
+/// # #[derive(Clone, Serialize, Deserialize)]
+/// # struct Role { id: u8 }
+/// #
+/// # impl DatabaseRecord for Role {
+/// #    type Unique = u8;
+/// #
+/// #    fn unique_value(&self) -> Self::Unique { self.id }
+/// # }
+/// #
+/// # impl DatabaseRecordPartitioned for Role {
+/// #    const PARTITION: &str = "Roles";
+/// # }
+/// let db = MemoryDB::<CborSerde>::new("Seeded");
+///
+/// Seeder::new()
+///     .with(vec![User { id: 1 }])
+///     .with(vec![Role { id: 1 }, Role { id: 2 }])
+///     .apply(&db)?;
+///
+/// assert_eq!(db.get_all::<Role>()?.len(), 2);
+/// # Ok::<(), lupabase::Error>(())
+/// ```
+pub struct Seeder<DB> {
+    seeds: Vec<(&'static str, Seed<DB>)>,
+}
+
+impl<DB: DatabaseOps> Seeder<DB> {
+    /// Creates a new [`Seeder`] without any partition
+    pub fn new() -> Self { return Self { seeds: vec![] }; }
+
+    /// Adds the partition of `T`, initialized with the provided default records
+    pub fn with<T: DatabaseRecordPartitioned + Clone + 'static>(
+        mut self,
+        default_records: impl Into<Vec<T>>,
+    ) -> Self {
+        let default_records = default_records.into();
+
+        self.seeds.push((
+            T::PARTITION,
+            Box::new(move |db: &DB| {
+                return db.try_initialize_storage::<T, Vec<T>>(default_records.clone());
+            }),
+        ));
+
+        return self;
+    }
+
+    /// Returns the partitions to be initialized, in the order they were added
+    pub fn partitions(&self) -> Vec<&'static str> {
+        return self.seeds.iter().map(|(partition, _)| *partition).collect();
+    }
+
+    /// Initializes every partition which doesn't exist yet, in the order they were added
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    pub fn apply(&self, db: &DB) -> Result<()> {
+        for (_, seed) in &self.seeds {
+            seed(db)?;
+        }
+
+        return Ok(());
+    }
+}
+
+impl<DB: DatabaseOps> Default for Seeder<DB> {
+    fn default() -> Self { return Self::new(); }
+}
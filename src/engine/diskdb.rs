@@ -1,41 +1,383 @@
-use crate::{Deserialize, Error, Result, Serialize, prelude::*, utils::*};
+use crate::{Deserialize, Error, Result, Serialize, prelude::*, transaction::*, utils::*};
+use hashbrown::HashMap;
+use itertools::Itertools;
+use parking_lot::{Mutex, RwLock};
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
 use std::{
+    any::{Any, TypeId},
     borrow::Borrow,
-    fs::create_dir_all,
+    cmp::Ordering,
+    fs::{self, create_dir_all},
+    hash::{Hash, Hasher},
+    io::{Read, Write},
     marker::PhantomData,
     path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
 };
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+/// Persists storage to disk, in the format of the provided [`BytesSerde`]
+///
+/// Clones share the same state (e.g. the file cache), as with [`MemoryDB`](crate::prelude::MemoryDB).
+#[derive(Debug)]
 pub struct DiskDB<S> {
     db_dir: PathBuf,
+    cache: Arc<RwLock<FileCache>>,
+    file_cache_max_bytes: Option<u64>,
+    write_locks: Arc<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>>,
+    verbose_errors: bool,
+    strict_reads: bool,
+    clock: Clock,
+    max_read_bytes: Option<u64>,
+    file_mode: Option<u32>,
     _serde_marker: PhantomData<S>,
 }
 
+/// The cached files, bounded in total size, see [`DiskDB::with_file_cache`]
+#[derive(Debug, Default)]
+struct FileCache {
+    files: HashMap<PathBuf, CachedFile>,
+    total_bytes: u64,
+}
+
+impl FileCache {
+    fn remove(&mut self, path: &Path) {
+        if let Some(cached) = self.files.remove(path) {
+            self.total_bytes -= cached.bytes.len() as u64;
+        }
+    }
+
+    /// Caches the file, evicting arbitrary files until it fits, files larger than the limit are never cached
+    fn insert(&mut self, path: &Path, cached: CachedFile, max_bytes: u64) {
+        self.remove(path);
+
+        let size = cached.bytes.len() as u64;
+        if size > max_bytes {
+            return;
+        }
+
+        while self.total_bytes + size > max_bytes {
+            let Some(evicted) = self.files.keys().next().cloned() else {
+                break;
+            };
+            self.remove(&evicted);
+        }
+
+        self.total_bytes += size;
+        let _ = self.files.insert(path.to_path_buf(), cached);
+    }
+}
+
+/// The bytes of a file, valid as long as the file's [`FileStamp`] remains unchanged
+///
+/// Also holds the records parsed from the bytes, keyed by the type they were parsed as.
+#[derive(Clone, Debug)]
+struct CachedFile {
+    stamp: FileStamp,
+    bytes: Vec<u8>,
+    parsed: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+/// Identifies a version of a file from its metadata
+///
+/// On Unix, the inode and the status change time are included as well. Unlike the modification time,
+/// the change time can't be set back (e.g. by `cp -p`, `rsync -t` or a backup restore),
+/// and a file replaced through a rename has another inode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+    #[cfg(unix)]
+    inode: u64,
+    #[cfg(unix)]
+    changed: (i64, i64),
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+
+        return Some(Self {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+            #[cfg(unix)]
+            inode: metadata.ino(),
+            #[cfg(unix)]
+            changed: (metadata.ctime(), metadata.ctime_nsec()),
+        });
+    }
+}
+
+impl<S> Clone for DiskDB<S> {
+    fn clone(&self) -> Self {
+        Self {
+            db_dir: self.db_dir.clone(),
+            cache: self.cache.clone(),
+            file_cache_max_bytes: self.file_cache_max_bytes,
+            write_locks: self.write_locks.clone(),
+            verbose_errors: self.verbose_errors,
+            strict_reads: self.strict_reads,
+            clock: self.clock,
+            max_read_bytes: self.max_read_bytes,
+            file_mode: self.file_mode,
+            _serde_marker: PhantomData,
+        }
+    }
+}
+
+impl<S> PartialEq for DiskDB<S> {
+    fn eq(&self, other: &Self) -> bool { self.db_dir == other.db_dir }
+}
+
+impl<S> Eq for DiskDB<S> {}
+
+impl<S> PartialOrd for DiskDB<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<S> Ord for DiskDB<S> {
+    fn cmp(&self, other: &Self) -> Ordering { self.db_dir.cmp(&other.db_dir) }
+}
+
+impl<S> Hash for DiskDB<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.db_dir.hash(state); }
+}
+
+impl<S> DiskDB<S> {
+    /// Reads the file, reusing the cached bytes if the file cache is enabled and the file hasn't changed since
+    fn try_read_file_cached(&self, path: &Path) -> Result<Vec<u8>> {
+        if self.file_cache_max_bytes.is_none() {
+            return try_read_file(path, self.max_read_bytes);
+        }
+
+        let stamp = FileStamp::of(path);
+
+        if let Some(stamp) = stamp
+            && self
+                .max_read_bytes
+                .is_none_or(|max_bytes| stamp.len <= max_bytes)
+            && let Some(cached) = self.cache.read().files.get(path)
+            && cached.stamp == stamp
+        {
+            return Ok(cached.bytes.clone());
+        }
+
+        let bytes = try_read_file(path, self.max_read_bytes)?;
+        self.cache_file(path, stamp, &bytes);
+
+        return Ok(bytes);
+    }
+
+    /// Returns the lock serializing the writes of the file, shared by every clone of the database
+    fn write_lock(&self, path: &Path) -> Arc<Mutex<()>> {
+        return self
+            .write_locks
+            .lock()
+            .entry(path.to_path_buf())
+            .or_default()
+            .clone();
+    }
+
+    fn cache_file(&self, path: &Path, stamp: Option<FileStamp>, bytes: &[u8]) {
+        let Some(max_bytes) = self.file_cache_max_bytes else {
+            return;
+        };
+
+        let mut guard = self.cache.write();
+        match stamp {
+            Some(stamp) => guard.insert(
+                path,
+                CachedFile {
+                    stamp,
+                    bytes: bytes.to_vec(),
+                    parsed: HashMap::new(),
+                },
+                max_bytes,
+            ),
+            None => guard.remove(path),
+        }
+    }
+}
+
+impl<S: BytesSerde> DiskDB<S> {
+    /// Creates the database in the given directory, creating the directory if needed
+    ///
+    /// Unlike [`Database::new`], failures are returned instead of panicking. With `probe_writable`,
+    /// a marker file is written to and removed from the directory upfront, so an unwritable directory
+    /// (e.g. an existing directory on a read-only mount) is reported at startup rather than on the first write.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - The directory isn't writable ([`Error::DBInaccessible`])
+    pub fn try_new(dir: impl AsRef<Path>, probe_writable: bool) -> Result<Self> {
+        let dir = dir.as_ref();
+
+        create_dir_all(dir).map_err(|e| Error::IOCreateDirFailure {
+            path: dir.display().to_string(),
+            reason: e,
+        })?;
+
+        if probe_writable {
+            let marker = dir.join(format!(".lupabase-probe-{}", std::process::id()));
+            fs::write(&marker, [])
+                .and_then(|()| fs::remove_file(&marker))
+                .map_err(|e| Error::DBInaccessible {
+                    path: dir.to_path_buf(),
+                    reason: e,
+                })?;
+        }
+
+        return Ok(Self {
+            db_dir: dir.into(),
+            cache: Default::default(),
+            file_cache_max_bytes: None,
+            write_locks: Default::default(),
+            verbose_errors: false,
+            strict_reads: false,
+            clock: Clock::Local,
+            max_read_bytes: None,
+            file_mode: None,
+            _serde_marker: PhantomData,
+        });
+    }
+
+    /// Sets whether errors list every offending unique value in full, see [`DatabaseOpsCustom::verbose_errors`]
+    pub fn with_verbose_errors(mut self, verbose_errors: bool) -> Self {
+        self.verbose_errors = verbose_errors;
+
+        return self;
+    }
+
+    /// Sets whether reads check the records for duplicate unique identifiers, see [`DatabaseOpsCustom::strict_reads`]
+    pub fn with_strict_reads(mut self, strict_reads: bool) -> Self {
+        self.strict_reads = strict_reads;
+
+        return self;
+    }
+
+    /// Sets the clock used to timestamp backups, see [`DatabaseIO::clock`]
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+
+        return self;
+    }
+
+    /// Sets the size limit for files read from disk, unlimited by default
+    ///
+    /// Larger files (e.g. corrupt or tampered partitions) are refused with [`Error::DBTooLarge`]
+    /// before they are read into memory.
+    pub fn with_max_read_bytes(mut self, max_read_bytes: u64) -> Self {
+        self.max_read_bytes = Some(max_read_bytes);
+
+        return self;
+    }
+
+    /// Sets the Unix permissions mode (e.g. `0o600`) of the files written, ignored on other platforms
+    ///
+    /// The mode is applied when a file is created, and to existing files before they're written to,
+    /// so the data is never readable with broader permissions. Backups keep the mode of their storage.
+    pub fn with_file_mode(mut self, file_mode: u32) -> Self {
+        self.file_mode = Some(file_mode);
+
+        return self;
+    }
+
+    /// Enables caching the files read and written, up to `max_bytes` in total, disabled by default
+    ///
+    /// A cached file is reused as long as its metadata is unchanged: its modification time and length,
+    /// and on Unix its inode and status change time. Other platforms can't detect an external rewrite
+    /// which keeps the length and the modification time. Files larger than `max_bytes` are never cached.
+    pub fn with_file_cache(mut self, max_bytes: u64) -> Self {
+        self.file_cache_max_bytes = Some(max_bytes);
+
+        return self;
+    }
+
+    /// Read all [`DatabaseRecordPartitioned`], reusing the records parsed by a previous read
+    ///
+    /// See [`DiskDB::get_all_cached_with_path`] for details and the list of possible errors.
+    pub fn get_all_cached<T: DatabaseRecordPartitioned + Clone + Send + Sync + 'static>(
+        &self,
+    ) -> Result<Vec<T>> {
+        return self.get_all_cached_with_path(self.partition_path::<T>()?);
+    }
+
+    /// Read all [`DatabaseRecord`] from the given path, reusing the records parsed by a previous read
+    ///
+    /// Parsed records are cached per type alongside the file's bytes, and are discarded whenever the file changes.
+    /// Reading as a type which was not cached yet reparses the bytes.
+    /// Without [`DiskDB::with_file_cache`], or for files too large for it, the records are parsed on every read.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    pub fn get_all_cached_with_path<T: DatabaseRecord + Clone + Send + Sync + 'static>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<T>> {
+        let path = path.as_ref();
+        let bytes = self.try_read_file_cached(path)?;
+
+        if let Some(records) = self
+            .cache
+            .read()
+            .files
+            .get(path)
+            .and_then(|cached| cached.parsed.get(&TypeId::of::<Vec<T>>()))
+            .and_then(|parsed| parsed.downcast_ref::<Vec<T>>())
+        {
+            return Ok(records.clone());
+        }
+
+        let records = S::try_deserialize_from_bytes::<Vec<T>>(&bytes)
+            .map_err(|e| backup_failed_parse(self, path, e))?;
+
+        // The file may have changed since it was read, only cache records parsed from the cached bytes
+        if let Some(cached) = self.cache.write().files.get_mut(path)
+            && cached.bytes == bytes
+        {
+            let _ = cached
+                .parsed
+                .insert(TypeId::of::<Vec<T>>(), Arc::new(records.clone()));
+        }
+
+        return Ok(records);
+    }
+}
+
 impl<S: BytesSerde> Database for DiskDB<S> {
     const NAME: &str = "DiskDB";
     const SERDE_FORMAT: &str = S::FORMAT;
 
     fn new(dir: impl AsRef<Path>) -> Self {
-        let dir = dir.as_ref();
+        return Self::try_new(dir, false).expect("DiskDB directories creation should succeed.");
+    }
+
+    fn with_namespace(&self, namespace: impl AsRef<str>) -> Self {
+        let dir = self.db_dir.join(namespace.as_ref());
 
-        create_dir_all(dir)
+        create_dir_all(&dir)
             .map_err(|e| Error::IOCreateDirFailure {
                 path: dir.display().to_string(),
                 reason: e,
             })
             .expect("DiskDB directories creation should succeed.");
 
-        Self {
-            db_dir: dir.into(),
-            _serde_marker: PhantomData,
-        }
+        return Self {
+            db_dir: dir,
+            ..self.clone()
+        };
     }
 }
 
 impl<S: BytesSerde> DatabaseOps for DiskDB<S> {}
 
 impl<S: BytesSerde> DatabaseOpsCustom for DiskDB<S> {
+    fn verbose_errors(&self) -> bool { self.verbose_errors }
+
+    fn strict_reads(&self) -> bool { self.strict_reads }
+
     fn try_initialize_storage_with_path<O: Serialize + for<'a> Deserialize<'a> + Borrow<O>>(
         &self,
         default_data: O,
@@ -50,26 +392,228 @@ impl<S: BytesSerde> DatabaseIO for DiskDB<S> {
 
     fn dir(&self) -> PathBuf { self.db_dir.clone() }
 
+    fn clock(&self) -> Clock { self.clock }
+
     fn try_copy_storage(
         &self,
         source: impl AsRef<Path>,
         destination: impl AsRef<Path>,
     ) -> Result<()> {
-        return try_copy_file(source, destination);
+        try_copy_file(&source, &destination)?;
+
+        return try_carry_version_file(source, destination, self.file_mode);
+    }
+
+    fn try_move_storage(
+        &self,
+        source: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<()> {
+        let (source, destination) = (source.as_ref(), destination.as_ref());
+
+        try_move_file(source, destination)?;
+
+        // A renamed file keeps its metadata, which may match a stale entry of the destination
+        let mut guard = self.cache.write();
+        guard.remove(source);
+        guard.remove(destination);
+        drop(guard);
+
+        // The source's version stays behind, so it never goes backwards if the source is written again
+        return try_carry_version_file(source, destination, self.file_mode);
+    }
+
+    /// Copies the storage without versioning the backup, so backups don't leave version files behind
+    fn try_backup_storage(
+        &self,
+        path: impl AsRef<Path>,
+        reason: impl AsRef<str>,
+    ) -> Result<PathBuf> {
+        let path = path.as_ref();
+
+        let backup_path = BackupInfo::path_for(path, self.clock().timestamp(), reason.as_ref());
+
+        try_copy_file(path, &backup_path)?;
+
+        return Ok(backup_path);
+    }
+
+    fn list_storages(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        return Ok(try_list_dir(dir)?
+            .into_iter()
+            .filter(|path| path.is_file())
+            .collect());
+    }
+
+    fn list_partitions(&self) -> Result<Vec<String>> {
+        return Ok(try_list_dir(&self.db_dir)?
+            .into_iter()
+            .filter(|path| path.is_file())
+            .filter_map(|path| partition_name(path, Self::EXTENSION))
+            .sorted_unstable()
+            .collect());
+    }
+
+    fn storage_size(&self, path: impl AsRef<Path>) -> Result<u64> {
+        let path = path.as_ref();
+
+        return fs::metadata(path)
+            .map(|metadata| metadata.len())
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => Error::DBNotFound {
+                    file_path: path.to_path_buf(),
+                },
+                _ => Error::IOExistsFailure {
+                    path: path.display().to_string(),
+                    reason: e,
+                },
+            });
+    }
+
+    fn try_remove_storage(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+
+        fs::remove_file(path).map_err(|e| Error::IORemoveFailure {
+            path: path.display().to_string(),
+            reason: e,
+        })?;
+        self.cache.write().remove(path);
+
+        return Ok(());
+    }
+
+    fn storage_exists(&self, path: impl AsRef<Path>) -> Result<bool> {
+        let path = path.as_ref();
+
+        return path.try_exists().map_err(|e| Error::IOExistsFailure {
+            path: path.display().to_string(),
+            reason: e,
+        });
     }
 
     fn try_write_storage(&self, data: impl Serialize, path: impl AsRef<Path>) -> Result<()> {
-        let serialized = S::try_serialize_as_bytes(data)?;
+        let path = path.as_ref();
+        let serialized = S::try_serialize_as_bytes(data).map_err(|e| with_path_context(e, path))?;
 
-        return try_write_file(&serialized, path);
+        let write_lock = self.write_lock(path);
+        let _guard = write_lock.lock();
+
+        return self.write_file_locked(&serialized, path);
+    }
+
+    fn try_write_storage_bytes(&self, bytes: &[u8], path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+
+        let write_lock = self.write_lock(path);
+        let _guard = write_lock.lock();
+
+        return self.write_file_locked(bytes, path);
+    }
+
+    /// The compare and the write are atomic among the writes of this database and its clones,
+    /// but not against other processes writing the file
+    fn compare_and_write(
+        &self,
+        expected_current: Option<&[u8]>,
+        data: impl Serialize,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        let serialized = S::try_serialize_as_bytes(data).map_err(|e| with_path_context(e, path))?;
+
+        let write_lock = self.write_lock(path);
+        let _guard = write_lock.lock();
+
+        // Read from disk rather than the cache, which may lag behind external writes
+        let current = match try_read_file(path, self.max_read_bytes) {
+            Ok(bytes) => Some(bytes),
+            Err(Error::DBNotFound { .. }) => None,
+            Err(e) => return Err(e),
+        };
+        if current.as_deref() != expected_current {
+            return Err(Error::DBTransactionConflict {
+                file_path: path.to_path_buf(),
+            });
+        }
+
+        return self.write_file_locked(&serialized, path);
+    }
+
+    fn version(&self, path: impl AsRef<Path>) -> Result<u64> { return try_read_version_file(path); }
+
+    fn open_append(&self, path: impl AsRef<Path>) -> Result<Box<dyn Write>> {
+        return Ok(Box::new(try_open_append(path, self.file_mode)?));
+    }
+
+    fn write_storage_to<W: Write>(&self, data: impl Serialize, writer: &mut W) -> Result<()> {
+        return try_write_to::<S, _>(data, writer);
+    }
+
+    fn read_storage_from<R: Read, O: for<'a> Deserialize<'a>>(&self, reader: &mut R) -> Result<O> {
+        return try_read_from::<S, _, _>(reader);
+    }
+
+    fn read_seq_from<R: Read, O: for<'a> Deserialize<'a>>(
+        &self,
+        reader: &mut R,
+        on_item: &mut dyn FnMut(O) -> Result<()>,
+    ) -> Result<()> {
+        return S::try_deserialize_seq_from_reader(reader, on_item);
+    }
+
+    fn try_read_storage_bytes(&self, path: impl AsRef<Path>) -> Result<Vec<u8>> {
+        return self.try_read_file_cached(path.as_ref());
     }
 
     fn try_read_storage<O: for<'a> Deserialize<'a>>(&self, path: impl AsRef<Path>) -> Result<O> {
-        let bytes = try_read_file(&path)?;
+        let bytes = self.try_read_file_cached(path.as_ref())?;
 
         return S::try_deserialize_from_bytes(&bytes)
             .map_err(|e| backup_failed_parse(self, path, e));
     }
+
+    fn check_storage(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+
+        return check_storage_bytes::<S>(&self.try_read_file_cached(path)?, path);
+    }
+
+    fn try_read_storage_lenient<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(O, Vec<String>)> {
+        let bytes = self.try_read_file_cached(path.as_ref())?;
+
+        let mut ignored = vec![];
+        let data = S::try_deserialize_from_bytes_reporting_ignored(&bytes, &mut |field| {
+            ignored.push(field)
+        })
+        .map_err(|e| backup_failed_parse(self, path, e))?;
+
+        return Ok((data, ignored));
+    }
+
+    fn read_with_raw<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(O, Vec<u8>)> {
+        let bytes = self.try_read_file_cached(path.as_ref())?;
+
+        let data = S::try_deserialize_from_bytes(&bytes)
+            .map_err(|e| backup_failed_parse(self, path, e))?;
+
+        return Ok((data, bytes));
+    }
+}
+
+impl<S: BytesSerde> DiskDB<S> {
+    /// Writes the serialized bytes to the file, with its write lock held
+    fn write_file_locked(&self, serialized: &[u8], path: &Path) -> Result<()> {
+        try_write_file(serialized, path, self.file_mode)?;
+        self.cache_file(path, FileStamp::of(path), serialized);
+
+        return try_bump_version_file(path, self.file_mode);
+    }
 }
 
 impl<S: BytesSerde> DatabaseTransaction for DiskDB<S> {
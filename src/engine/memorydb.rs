@@ -1,21 +1,332 @@
-use crate::{Deserialize, Error, Result, Serialize, prelude::*, utils::try_populate_storage};
+use crate::{
+    Deserialize, Error, Result, Serialize,
+    prelude::*,
+    transaction::*,
+    utils::{
+        check_is_all_unique_records, check_storage_bytes, identical_copy_failure, partition_name,
+        touch_record, try_populate_storage, try_read_from, try_write_to, with_path_context,
+    },
+};
 use hashbrown::HashMap;
-use parking_lot::RwLock;
+use itertools::Itertools;
+use parking_lot::{Mutex, RwLock, RwLockWriteGuard};
 use std::{
+    any::{Any, TypeId},
     borrow::Borrow,
+    hash::{BuildHasher, BuildHasherDefault, DefaultHasher},
+    io::{self, Read, Write},
     marker::PhantomData,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
 };
-use tracing::warn;
+use tracing::{debug, warn};
+
+/// The number of independently locked shards in a [`MemoryDB`] store
+const SHARDS: usize = 16;
+
+type Shard = RwLock<HashMap<PathBuf, StoredEntry>>;
+type ShardWriteGuard<'a> = RwLockWriteGuard<'a, HashMap<PathBuf, StoredEntry>>;
+
+/// The stored bytes of a storage, along with the data parsed from them per type
+///
+/// Writing a storage replaces its entry, discarding the parsed data along with the previous bytes.
+#[derive(Debug, Default)]
+struct StoredEntry {
+    bytes: Vec<u8>,
+    parsed: RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl From<Vec<u8>> for StoredEntry {
+    fn from(bytes: Vec<u8>) -> Self {
+        return Self {
+            bytes,
+            parsed: Default::default(),
+        };
+    }
+}
+
+impl Clone for StoredEntry {
+    fn clone(&self) -> Self {
+        return Self {
+            bytes: self.bytes.clone(),
+            parsed: RwLock::new(self.parsed.read().clone()),
+        };
+    }
+}
+
+impl PartialEq for StoredEntry {
+    fn eq(&self, other: &Self) -> bool { self.bytes == other.bytes }
+}
+
+/// Tracks the last access and size of every storage, to evict the least recently used ones once over capacity
+///
+/// Nothing is tracked until a limit is set, see [`MemoryDB::with_max_entries`] and [`MemoryDB::with_max_bytes`].
+#[derive(Debug, Default)]
+struct Recency {
+    max_entries: Option<usize>,
+    max_bytes: Option<u64>,
+    tick: u64,
+    /// The last access tick and size of every storage
+    entries: HashMap<PathBuf, (u64, u64)>,
+    total_bytes: u64,
+}
+
+impl Recency {
+    fn is_bounded(&self) -> bool { self.max_entries.is_some() || self.max_bytes.is_some() }
+
+    fn is_over_capacity(&self) -> bool {
+        return self.max_entries.is_some_and(|max| self.entries.len() > max)
+            || self.max_bytes.is_some_and(|max| self.total_bytes > max);
+    }
+
+    fn touch(&mut self, path: &Path) {
+        if let Some((last_access, _)) = self.entries.get_mut(path) {
+            self.tick += 1;
+            *last_access = self.tick;
+        }
+    }
+
+    fn record(&mut self, path: &Path, size: u64) {
+        if !self.is_bounded() {
+            return;
+        }
+
+        self.forget(path);
+        self.tick += 1;
+        let _ = self.entries.insert(path.to_path_buf(), (self.tick, size));
+        self.total_bytes += size;
+    }
+
+    fn forget(&mut self, path: &Path) {
+        if let Some((_, size)) = self.entries.remove(path) {
+            self.total_bytes -= size;
+        }
+    }
+
+    /// Forgets the least recently used storages until within capacity, never the kept one, returning them
+    fn evict(&mut self, keep: &Path) -> Vec<PathBuf> {
+        let mut evicted = vec![];
+
+        while self.is_over_capacity() {
+            let Some(victim) = self
+                .entries
+                .iter()
+                .filter(|(path, _)| path.as_path() != keep)
+                .min_by_key(|(_, (last_access, _))| *last_access)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+
+            self.forget(&victim);
+            evicted.push(victim);
+        }
+
+        return evicted;
+    }
+}
+
+/// Stores every path and its stored bytes, sharded by path so that storages in different shards don't
+/// contend for the same lock
+///
+/// Locks are always taken shard first, then recency, so the recency lock is never held while locking a shard.
+/// Every write is tracked while holding the shard lock of the written storage, so that a storage written
+/// again after being evicted is never removed.
+#[derive(Debug, Default)]
+struct ShardedStore {
+    shards: [Shard; SHARDS],
+    versions: RwLock<HashMap<PathBuf, u64>>,
+    /// Whether a capacity limit is set, checked before taking the recency lock
+    bounded: AtomicBool,
+    recency: Mutex<Recency>,
+}
+
+impl ShardedStore {
+    fn shard_index(path: &Path) -> usize {
+        return BuildHasherDefault::<DefaultHasher>::default().hash_one(path) as usize % SHARDS;
+    }
+
+    fn shard(&self, path: &Path) -> &Shard { &self.shards[Self::shard_index(path)] }
+
+    fn version(&self, path: &Path) -> u64 { self.versions.read().get(path).copied().unwrap_or(0) }
+
+    fn bump_version(&self, path: &Path) {
+        *self.versions.write().entry(path.to_path_buf()).or_default() += 1;
+    }
+
+    /// Sets the version of the destination past the versions of both storages, after copying or moving the source onto it
+    fn carry_version(&self, source: &Path, destination: &Path) {
+        let mut versions = self.versions.write();
+        let version = versions
+            .get(source)
+            .copied()
+            .unwrap_or(0)
+            .max(versions.get(destination).copied().unwrap_or(0))
+            + 1;
+        let _ = versions.insert(destination.to_path_buf(), version);
+    }
+
+    fn is_bounded(&self) -> bool { self.bounded.load(Ordering::Acquire) }
+
+    /// Marks the storage as recently used
+    fn touch(&self, path: &Path) {
+        if self.is_bounded() {
+            self.recency.lock().touch(path);
+        }
+    }
+
+    /// Marks the storage as recently written with its new size, returning the least recently used storages
+    /// to evict if over capacity, must be called while holding the shard write lock of the storage
+    fn track_write(&self, path: &Path, size: usize) -> Vec<PathBuf> {
+        if !self.is_bounded() {
+            return vec![];
+        }
+
+        let mut recency = self.recency.lock();
+        recency.record(path, size as u64);
+
+        return recency.evict(path);
+    }
+
+    /// Tracks the write of the storage while holding its shard write lock, then releases the lock to evict
+    fn record_write(&self, guard: ShardWriteGuard<'_>, path: &Path, size: usize) {
+        let evicted = self.track_write(path, size);
+        drop(guard);
+
+        self.remove_evicted(evicted);
+    }
+
+    /// Removes the evicted storages from their shards, must be called without holding any shard lock
+    ///
+    /// A storage written again since being evicted is tracked again, and is thus kept.
+    fn remove_evicted(&self, evicted: Vec<PathBuf>) {
+        for evicted_path in evicted {
+            let mut guard = self.shard(&evicted_path).write();
+            if self.recency.lock().entries.contains_key(&evicted_path) {
+                continue;
+            }
+
+            debug!(
+                "Evicting [{}] from MemoryDB, as it's over capacity.",
+                evicted_path.display()
+            );
 
+            let _ = guard.remove(&evicted_path);
+        }
+    }
+
+    /// Stops tracking the storage, must be called while holding the shard write lock of the storage
+    fn forget(&self, path: &Path) {
+        if self.is_bounded() {
+            self.recency.lock().forget(path);
+        }
+    }
+
+    /// Sets the capacity limits, tracking every stored storage and evicting if already over capacity
+    fn set_capacity(&self, max_entries: Option<usize>, max_bytes: Option<u64>) {
+        {
+            let mut recency = self.recency.lock();
+            recency.max_entries = max_entries.or(recency.max_entries);
+            recency.max_bytes = max_bytes.or(recency.max_bytes);
+            recency.entries.clear();
+            recency.total_bytes = 0;
+            self.bounded.store(recency.is_bounded(), Ordering::Release);
+        }
+
+        let mut evicted = vec![];
+        for shard in &self.shards {
+            let guard = shard.write();
+            for (path, entry) in guard.iter().sorted_unstable_by_key(|(path, _)| *path) {
+                evicted.extend(self.track_write(path, entry.bytes.len()));
+            }
+        }
+
+        self.remove_evicted(evicted);
+    }
+
+    /// Copies (or moves) the storage to the destination, locking both shards in order to avoid deadlocks
+    fn transfer(&self, source: &Path, destination: &Path, remove_source: bool) -> Result<()> {
+        let source_index = Self::shard_index(source);
+        let destination_index = Self::shard_index(destination);
+
+        let mut guards = [source_index, destination_index]
+            .into_iter()
+            .sorted_unstable()
+            .dedup()
+            .map(|index| (index, self.shards[index].write()))
+            .collect::<Vec<_>>();
+
+        let (_, source_guard) = guards
+            .iter_mut()
+            .find(|(index, _)| *index == source_index)
+            .expect("Source shard should be locked");
+        let content = match remove_source {
+            true => source_guard.remove(source),
+            false => source_guard.get(source).cloned(),
+        }
+        .ok_or_else(|| Error::DBNotFound {
+            file_path: source.to_path_buf(),
+        })?;
+
+        let (_, destination_guard) = guards
+            .iter_mut()
+            .find(|(index, _)| *index == destination_index)
+            .expect("Destination shard should be locked");
+        let size = content.bytes.len();
+        let _ = destination_guard.insert(destination.to_path_buf(), content);
+        self.carry_version(source, destination);
+
+        if remove_source {
+            self.forget(source);
+        }
+        let evicted = self.track_write(destination, size);
+        drop(guards);
+
+        self.remove_evicted(evicted);
+
+        return Ok(());
+    }
+}
+
+/// Keeps storage in memory, in the format of the provided [`BytesSerde`]
+///
+/// Clones share the same store. The store is split into independently locked shards,
+/// so writes to different partitions rarely contend.
 #[derive(Clone, Debug)]
 pub struct MemoryDB<S> {
     dir: PathBuf,
-    store: Arc<RwLock<HashMap<PathBuf, Vec<u8>>>>,
+    store: Arc<ShardedStore>,
+    verbose_errors: bool,
+    strict_reads: bool,
+    clock: Clock,
     _serde_marker: PhantomData<S>,
 }
 
+/// Appends raw bytes to a storage of the store, see [`DatabaseIO::open_append`]
+struct MemoryAppender {
+    store: Arc<ShardedStore>,
+    path: PathBuf,
+}
+
+impl Write for MemoryAppender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut guard = self.store.shard(&self.path).write();
+        let entry = guard.entry(self.path.clone()).or_default();
+
+        entry.bytes.extend_from_slice(buf);
+        entry.parsed.get_mut().clear();
+        let size = entry.bytes.len();
+        self.store.record_write(guard, &self.path, size);
+
+        return Ok(buf.len());
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
 impl<S: BytesSerde> Database for MemoryDB<S> {
     const NAME: &str = "MemoryDB";
     const SERDE_FORMAT: &str = S::FORMAT;
@@ -24,14 +335,190 @@ impl<S: BytesSerde> Database for MemoryDB<S> {
         return Self {
             dir: dir.as_ref().to_path_buf(),
             store: Default::default(),
+            verbose_errors: false,
+            strict_reads: false,
+            clock: Clock::Local,
+            _serde_marker: PhantomData,
+        };
+    }
+
+    fn with_namespace(&self, namespace: impl AsRef<str>) -> Self {
+        return Self {
+            dir: self.dir.join(namespace.as_ref()),
+            store: self.store.clone(),
+            verbose_errors: self.verbose_errors,
+            strict_reads: self.strict_reads,
+            clock: self.clock,
             _serde_marker: PhantomData,
         };
     }
 }
 
+impl<S: BytesSerde> MemoryDB<S> {
+    /// Sets whether errors list every offending unique value in full, see [`DatabaseOpsCustom::verbose_errors`]
+    pub fn with_verbose_errors(mut self, verbose_errors: bool) -> Self {
+        self.verbose_errors = verbose_errors;
+
+        return self;
+    }
+
+    /// Sets whether reads check the records for duplicate unique identifiers, see [`DatabaseOpsCustom::strict_reads`]
+    pub fn with_strict_reads(mut self, strict_reads: bool) -> Self {
+        self.strict_reads = strict_reads;
+
+        return self;
+    }
+
+    /// Sets the clock used to timestamp backups, see [`DatabaseIO::clock`]
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+
+        return self;
+    }
+
+    /// Limits the number of storages in the store (including backups), unlimited by default
+    ///
+    /// Once a write exceeds the limit, the least recently used storages are evicted (removed) until within the limit.
+    /// Reading or writing a storage marks it as recently used, the written storage itself is never evicted.
+    /// The limit applies to the shared store, and thus to every clone.
+    pub fn with_max_entries(self, max_entries: usize) -> Self {
+        self.store.set_capacity(Some(max_entries), None);
+
+        return self;
+    }
+
+    /// Limits the total size of the stored bytes in the store (including backups), unlimited by default
+    ///
+    /// Evicts the least recently used storages once exceeded, see [`MemoryDB::with_max_entries`] for details.
+    pub fn with_max_bytes(self, max_bytes: u64) -> Self {
+        self.store.set_capacity(None, Some(max_bytes));
+
+        return self;
+    }
+
+    /// Read all [`DatabaseRecordPartitioned`], reusing the records parsed by a previous read
+    ///
+    /// See [`MemoryDB::get_all_cached_with_path`] for details and the list of possible errors.
+    pub fn get_all_cached<T: DatabaseRecordPartitioned + Clone + Send + Sync + 'static>(
+        &self,
+    ) -> Result<Vec<T>> {
+        return self.get_all_cached_with_path(self.partition_path::<T>()?);
+    }
+
+    /// Read all [`DatabaseRecord`] from the given path, reusing the records parsed by a previous read
+    ///
+    /// Parsed records are kept per type alongside the stored bytes, and are discarded whenever the storage is written.
+    /// Reading as a type which was not cached yet deserializes the bytes, as [`DatabaseOps::get_all`] always does.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    pub fn get_all_cached_with_path<T: DatabaseRecord + Clone + Send + Sync + 'static>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<T>> {
+        let path = path.as_ref();
+
+        let guard = self.store.shard(path).read();
+        let entry = guard.get(path).ok_or_else(|| Error::DBNotFound {
+            file_path: path.to_path_buf(),
+        })?;
+        self.store.touch(path);
+
+        if let Some(records) = entry
+            .parsed
+            .read()
+            .get(&TypeId::of::<Vec<T>>())
+            .and_then(|parsed| parsed.downcast_ref::<Vec<T>>())
+        {
+            return Ok(records.clone());
+        }
+
+        let records = S::try_deserialize_from_bytes::<Vec<T>>(&entry.bytes)
+            .map_err(|e| corrupt_partition(path, e))?;
+        let _ = entry
+            .parsed
+            .write()
+            .insert(TypeId::of::<Vec<T>>(), Arc::new(records.clone()));
+
+        return Ok(records);
+    }
+
+    /// Returns whether both databases hold the same content, every path and its stored bytes
+    ///
+    /// Clones sharing the same store are always equal.
+    pub fn content_eq(&self, other: &Self) -> bool {
+        if Arc::ptr_eq(&self.store, &other.store) {
+            return true;
+        }
+
+        return self
+            .store
+            .shards
+            .iter()
+            .zip(&other.store.shards)
+            .all(|(shard, other_shard)| *shard.read() == *other_shard.read());
+    }
+
+    /// Serializes the entire store, every path and its stored bytes, into a single blob
+    ///
+    /// The blob can be persisted and later restored with [`MemoryDB::load`].
+    /// Paths are sorted, so the same content always produces the same blob.
+    ///
+    /// # Errors
+    /// - Parsing failure
+    pub fn dump(&self) -> Result<Vec<u8>> {
+        let guards = self
+            .store
+            .shards
+            .iter()
+            .map(|shard| shard.read())
+            .collect::<Vec<_>>();
+
+        return S::try_serialize_as_bytes(
+            guards
+                .iter()
+                .flat_map(|guard| guard.iter())
+                .map(|(path, entry)| (path, &entry.bytes))
+                .sorted_unstable_by_key(|(path, _)| *path)
+                .collect::<Vec<(&PathBuf, &Vec<u8>)>>(),
+        );
+    }
+
+    /// Restores the entire store from a blob created with [`MemoryDB::dump`]
+    ///
+    /// The current content of the store is replaced by the content of the blob.
+    ///
+    /// # Errors
+    /// - Parsing failure
+    pub fn load(&self, blob: &[u8]) -> Result<()> {
+        let entries = S::try_deserialize_from_bytes::<Vec<(PathBuf, Vec<u8>)>>(blob)?;
+
+        let mut guards = self
+            .store
+            .shards
+            .iter()
+            .map(|shard| shard.write())
+            .collect::<Vec<_>>();
+        guards.iter_mut().for_each(|guard| guard.clear());
+        for (path, bytes) in entries {
+            let _ = guards[ShardedStore::shard_index(&path)].insert(path, bytes.into());
+        }
+        drop(guards);
+
+        self.store.set_capacity(None, None);
+
+        return Ok(());
+    }
+}
+
 impl<S: BytesSerde> DatabaseOps for MemoryDB<S> {}
 
 impl<S: BytesSerde> DatabaseOpsCustom for MemoryDB<S> {
+    fn verbose_errors(&self) -> bool { self.verbose_errors }
+
+    fn strict_reads(&self) -> bool { self.strict_reads }
+
     fn try_initialize_storage_with_path<O: Serialize + for<'a> Deserialize<'a> + Borrow<O>>(
         &self,
         default_data: O,
@@ -39,6 +526,26 @@ impl<S: BytesSerde> DatabaseOpsCustom for MemoryDB<S> {
     ) -> Result<()> {
         return try_populate_storage::<Self, O>(self, default_data, path);
     }
+
+    fn touch_with_path<T: DatabaseRecord, F: FnOnce(&mut T)>(
+        &self,
+        unique: &T::Unique,
+        f: F,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+
+        // `f` runs without holding any lock, so it may use the database, and the records are only written back
+        // if the storage is unchanged since it was read
+        let (mut records, current) = self.read_with_raw::<Vec<T>>(path)?;
+        if self.strict_reads {
+            check_is_all_unique_records(&records, path, self.verbose_errors)?;
+        }
+
+        touch_record(&mut records, unique, f, path, self.verbose_errors)?;
+
+        return self.compare_and_write(Some(&current), records, path);
+    }
 }
 
 impl<S: BytesSerde> DatabaseIO for MemoryDB<S> {
@@ -46,55 +553,278 @@ impl<S: BytesSerde> DatabaseIO for MemoryDB<S> {
 
     fn dir(&self) -> PathBuf { self.dir.clone() }
 
+    fn dir_canonical(&self) -> Result<PathBuf> { return Ok(self.dir_absolute()); }
+
+    fn clock(&self) -> Clock { self.clock }
+
     fn try_copy_storage(
         &self,
         source: impl AsRef<Path>,
         destination: impl AsRef<Path>,
     ) -> Result<()> {
-        let mut guard = self.store.write();
+        if source.as_ref() == destination.as_ref() {
+            return Err(identical_copy_failure(source));
+        }
+
+        return self
+            .store
+            .transfer(source.as_ref(), destination.as_ref(), false);
+    }
 
-        let content = guard
-            .get(source.as_ref())
-            .cloned()
+    fn try_move_storage(
+        &self,
+        source: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<()> {
+        return self
+            .store
+            .transfer(source.as_ref(), destination.as_ref(), true);
+    }
+
+    fn list_storages(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+
+        return Ok(self
+            .store
+            .shards
+            .iter()
+            .flat_map(|shard| {
+                shard
+                    .read()
+                    .keys()
+                    .filter(|path| path.parent() == Some(dir))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .sorted_unstable()
+            .collect());
+    }
+
+    fn list_partitions(&self) -> Result<Vec<String>> {
+        return Ok(self
+            .store
+            .shards
+            .iter()
+            .flat_map(|shard| {
+                shard
+                    .read()
+                    .keys()
+                    .filter(|path| path.parent() == Some(self.dir.as_path()))
+                    .filter_map(|path| partition_name(path, Self::EXTENSION))
+                    .collect::<Vec<_>>()
+            })
+            .sorted_unstable()
+            .collect());
+    }
+
+    fn storage_size(&self, path: impl AsRef<Path>) -> Result<u64> {
+        return self
+            .store
+            .shard(path.as_ref())
+            .read()
+            .get(path.as_ref())
+            .map(|entry| entry.bytes.len() as u64)
             .ok_or_else(|| Error::DBNotFound {
-                file_path: source.as_ref().to_path_buf(),
+                file_path: path.as_ref().to_path_buf(),
+            });
+    }
+
+    fn try_remove_storage(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut guard = self.store.shard(path.as_ref()).write();
+        guard
+            .remove(path.as_ref())
+            .ok_or_else(|| Error::DBNotFound {
+                file_path: path.as_ref().to_path_buf(),
             })?;
+        self.store.forget(path.as_ref());
 
-        let _ = guard.insert(destination.as_ref().to_path_buf(), content);
+        return Ok(());
+    }
 
-        Ok(())
+    fn storage_exists(&self, path: impl AsRef<Path>) -> Result<bool> {
+        return Ok(self
+            .store
+            .shard(path.as_ref())
+            .read()
+            .contains_key(path.as_ref()));
     }
 
     fn try_write_storage(&self, data: impl Serialize, path: impl AsRef<Path>) -> Result<()> {
-        let serialized = S::try_serialize_as_bytes(data)?;
+        let serialized =
+            S::try_serialize_as_bytes(data).map_err(|e| with_path_context(e, &path))?;
+
+        let size = serialized.len();
+        let mut guard = self.store.shard(path.as_ref()).write();
+        let _ = guard.insert(path.as_ref().to_path_buf(), serialized.into());
+        self.store.bump_version(path.as_ref());
+        self.store.record_write(guard, path.as_ref(), size);
+
+        return Ok(());
+    }
+
+    fn try_write_storage_bytes(&self, bytes: &[u8], path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+
+        let mut guard = self.store.shard(path).write();
+        let _ = guard.insert(path.to_path_buf(), bytes.to_vec().into());
+        self.store.bump_version(path);
+        self.store.record_write(guard, path, bytes.len());
+
+        return Ok(());
+    }
+
+    fn version(&self, path: impl AsRef<Path>) -> Result<u64> {
+        return Ok(self.store.version(path.as_ref()));
+    }
+
+    fn open_append(&self, path: impl AsRef<Path>) -> Result<Box<dyn Write>> {
+        let path = path.as_ref().to_path_buf();
+        let _ = self
+            .store
+            .shard(&path)
+            .write()
+            .entry(path.clone())
+            .or_default();
+
+        return Ok(Box::new(MemoryAppender {
+            store: self.store.clone(),
+            path,
+        }));
+    }
+
+    fn write_storage_to<W: Write>(&self, data: impl Serialize, writer: &mut W) -> Result<()> {
+        return try_write_to::<S, _>(data, writer);
+    }
+
+    fn read_storage_from<R: Read, O: for<'a> Deserialize<'a>>(&self, reader: &mut R) -> Result<O> {
+        return try_read_from::<S, _, _>(reader);
+    }
+
+    fn read_seq_from<R: Read, O: for<'a> Deserialize<'a>>(
+        &self,
+        reader: &mut R,
+        on_item: &mut dyn FnMut(O) -> Result<()>,
+    ) -> Result<()> {
+        return S::try_deserialize_seq_from_reader(reader, on_item);
+    }
+
+    fn try_read_storage_bytes(&self, path: impl AsRef<Path>) -> Result<Vec<u8>> {
+        let path = path.as_ref();
+
+        let bytes = self
+            .store
+            .shard(path)
+            .read()
+            .get(path)
+            .map(|entry| entry.bytes.clone())
+            .ok_or_else(|| Error::DBNotFound {
+                file_path: path.to_path_buf(),
+            })?;
+        self.store.touch(path);
+
+        return Ok(bytes);
+    }
+
+    fn compare_and_write(
+        &self,
+        expected_current: Option<&[u8]>,
+        data: impl Serialize,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        let serialized = S::try_serialize_as_bytes(data).map_err(|e| with_path_context(e, path))?;
+
+        let mut guard = self.store.shard(path).write();
+        if guard.get(path).map(|entry| entry.bytes.as_slice()) != expected_current {
+            return Err(Error::DBTransactionConflict {
+                file_path: path.to_path_buf(),
+            });
+        }
+
+        let size = serialized.len();
+        let _ = guard.insert(path.to_path_buf(), serialized.into());
+        self.store.bump_version(path);
+        self.store.record_write(guard, path, size);
 
-        let mut guard = self.store.write();
-        let _ = guard.insert(path.as_ref().to_path_buf(), serialized);
         return Ok(());
     }
 
     fn try_read_storage<O: for<'a> Deserialize<'a>>(&self, path: impl AsRef<Path>) -> Result<O> {
         let path = path.as_ref();
 
-        let guard = self.store.read();
-        let bytes = guard.get(path).ok_or_else(|| Error::DBNotFound {
+        let guard = self.store.shard(path).read();
+        let entry = guard.get(path).ok_or_else(|| Error::DBNotFound {
             file_path: path.to_path_buf(),
         })?;
+        self.store.touch(path);
 
-        S::try_deserialize_from_bytes(bytes).map_err(|e| {
-            warn!(
-                "Failed deserialize partition at [{}], caused by: [{e}]",
-                path.display()
-            );
+        return S::try_deserialize_from_bytes(&entry.bytes).map_err(|e| corrupt_partition(path, e));
+    }
 
-            return Error::DBCorrupt {
-                file_path: path.to_path_buf(),
-                reason: Error::DeserializationFailure(Box::new(e)).to_string(),
-            };
+    fn check_storage(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+
+        let guard = self.store.shard(path).read();
+        let entry = guard.get(path).ok_or_else(|| Error::DBNotFound {
+            file_path: path.to_path_buf(),
+        })?;
+        self.store.touch(path);
+
+        return check_storage_bytes::<S>(&entry.bytes, path);
+    }
+
+    fn try_read_storage_lenient<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(O, Vec<String>)> {
+        let path = path.as_ref();
+
+        let guard = self.store.shard(path).read();
+        let entry = guard.get(path).ok_or_else(|| Error::DBNotFound {
+            file_path: path.to_path_buf(),
+        })?;
+        self.store.touch(path);
+
+        let mut ignored = vec![];
+        let data = S::try_deserialize_from_bytes_reporting_ignored(&entry.bytes, &mut |field| {
+            ignored.push(field)
         })
+        .map_err(|e| corrupt_partition(path, e))?;
+
+        return Ok((data, ignored));
+    }
+
+    fn read_with_raw<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(O, Vec<u8>)> {
+        let path = path.as_ref();
+
+        let guard = self.store.shard(path).read();
+        let entry = guard.get(path).ok_or_else(|| Error::DBNotFound {
+            file_path: path.to_path_buf(),
+        })?;
+        self.store.touch(path);
+
+        let data =
+            S::try_deserialize_from_bytes(&entry.bytes).map_err(|e| corrupt_partition(path, e))?;
+
+        return Ok((data, entry.bytes.clone()));
     }
 }
 
+fn corrupt_partition(path: &Path, e: Error) -> Error {
+    warn!(
+        "Failed deserialize partition at [{}], caused by: [{e}]",
+        path.display()
+    );
+
+    return Error::DBCorrupt {
+        file_path: path.to_path_buf(),
+        reason: Error::DeserializationFailure(Box::new(e)).to_string(),
+    };
+}
+
 impl<S: BytesSerde> DatabaseTransaction for MemoryDB<S> {
     type TransactionDB = TransactionDB<S>;
 }
@@ -6,5 +6,8 @@ pub use memorydb::*;
 mod diskdb;
 pub use diskdb::*;
 
+mod multiformatdb;
+pub use multiformatdb::*;
+
 mod transactiondb;
 pub use transactiondb::*;
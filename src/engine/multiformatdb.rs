@@ -0,0 +1,374 @@
+use crate::{Deserialize, Error, Result, Serialize, prelude::*, transaction::*, utils::*};
+use std::{
+    borrow::Borrow,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+use tracing::warn;
+
+/// Stores every partition in two formats at once, through a preferred and a fallback [`Database`]
+///
+/// Every write goes to both databases, while reads come from the preferred database,
+/// falling back to the other one when the preferred storage can't be read.
+/// Paths are expressed with the preferred database's extension, and translated for the fallback database.
+///
+/// Both databases should use different extensions, otherwise they would write to the same files.
+#[derive(Clone, Debug)]
+pub struct MultiFormatDB<P, F> {
+    preferred: P,
+    fallback: F,
+}
+
+impl<P: Database, F: Database> MultiFormatDB<P, F> {
+    /// Returns the preferred database, used for reads
+    pub fn preferred(&self) -> &P { &self.preferred }
+
+    /// Returns the fallback database, used for reads when the preferred storage can't be read
+    pub fn fallback(&self) -> &F { &self.fallback }
+
+    /// Returns the fallback database's path corresponding to the provided path
+    ///
+    /// The preferred database's extension is replaced by the fallback database's extension,
+    /// any other path has the fallback database's extension added.
+    pub fn fallback_path(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+
+        return match path.extension() {
+            Some(extension) if extension == P::EXTENSION => path.with_extension(F::EXTENSION),
+            _ => with_added_extension(path, F::EXTENSION),
+        };
+    }
+
+    /// Returns the raw bytes of the preferred storage, to restore it with [`MultiFormatDB::restore_preferred`]
+    ///
+    /// The bytes are kept in memory rather than copied to another storage, so nothing is left behind.
+    fn preferred_snapshot(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+        return match self.preferred.try_read_storage_bytes(path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(Error::DBNotFound { .. }) => Ok(None),
+            Err(e) => Err(e),
+        };
+    }
+
+    /// Restores the preferred storage to its snapshot (removing it if it didn't exist), after failing to change
+    /// the fallback storage, so both storages stay in sync
+    fn restore_preferred(&self, path: &Path, snapshot: Option<&[u8]>) {
+        let restored = match snapshot {
+            Some(bytes) => self.preferred.try_write_storage_bytes(bytes, path),
+            None => match self.preferred.try_remove_storage(path) {
+                Err(Error::DBNotFound { .. }) => Ok(()),
+                result => result,
+            },
+        };
+
+        if let Err(e) = restored {
+            warn!(
+                "Failed restoring [{}] in {} after failing to change {}, caused by: [{e}]",
+                path.display(),
+                P::NAME,
+                F::NAME
+            );
+        }
+    }
+
+    /// Changes the preferred storage, then the fallback storage, restoring the preferred storage to the snapshot
+    /// if the fallback storage can't be changed
+    fn change_both(
+        &self,
+        path: &Path,
+        snapshot: Option<&[u8]>,
+        change_preferred: impl FnOnce() -> Result<()>,
+        change_fallback: impl FnOnce() -> Result<()>,
+    ) -> Result<()> {
+        change_preferred()?;
+
+        if let Err(e) = change_fallback() {
+            self.restore_preferred(path, snapshot);
+
+            return Err(e);
+        }
+
+        return Ok(());
+    }
+}
+
+impl<P: Database, F: Database> Database for MultiFormatDB<P, F> {
+    const NAME: &str = "MultiFormatDB";
+    const SERDE_FORMAT: &str = P::SERDE_FORMAT;
+
+    fn new(dir: impl AsRef<Path>) -> Self {
+        return Self {
+            preferred: P::new(&dir),
+            fallback: F::new(&dir),
+        };
+    }
+
+    fn with_namespace(&self, namespace: impl AsRef<str>) -> Self {
+        return Self {
+            preferred: self.preferred.with_namespace(&namespace),
+            fallback: self.fallback.with_namespace(&namespace),
+        };
+    }
+}
+
+impl<P: Database, F: Database> DatabaseOps for MultiFormatDB<P, F> {}
+
+impl<P: Database, F: Database> DatabaseOpsCustom for MultiFormatDB<P, F> {
+    fn verbose_errors(&self) -> bool { self.preferred.verbose_errors() }
+
+    fn strict_reads(&self) -> bool { self.preferred.strict_reads() }
+
+    fn try_initialize_storage_with_path<O: Serialize + for<'a> Deserialize<'a> + Borrow<O>>(
+        &self,
+        default_data: O,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        return try_populate_storage::<Self, O>(self, default_data, path);
+    }
+}
+
+impl<P: Database, F: Database> DatabaseIO for MultiFormatDB<P, F> {
+    const EXTENSION: &str = P::EXTENSION;
+
+    fn dir(&self) -> PathBuf { self.preferred.dir() }
+
+    fn dir_canonical(&self) -> Result<PathBuf> { return self.preferred.dir_canonical(); }
+
+    fn clock(&self) -> Clock { self.preferred.clock() }
+
+    /// Restores the preferred destination if the fallback storage can't be copied
+    fn try_copy_storage(
+        &self,
+        source: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<()> {
+        let (source, destination) = (source.as_ref(), destination.as_ref());
+        let snapshot = self.preferred_snapshot(destination)?;
+
+        return self.change_both(
+            destination,
+            snapshot.as_deref(),
+            || self.preferred.try_copy_storage(source, destination),
+            || {
+                self.fallback
+                    .try_copy_storage(self.fallback_path(source), self.fallback_path(destination))
+            },
+        );
+    }
+
+    /// Moves the preferred storage back, and restores the preferred destination, if the fallback storage can't be moved
+    fn try_move_storage(
+        &self,
+        source: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<()> {
+        let (source, destination) = (source.as_ref(), destination.as_ref());
+        let snapshot = self.preferred_snapshot(destination)?;
+
+        self.preferred.try_move_storage(source, destination)?;
+
+        if let Err(e) = self
+            .fallback
+            .try_move_storage(self.fallback_path(source), self.fallback_path(destination))
+        {
+            // Moving back leaves the destination missing, so only an existing destination needs restoring
+            match self.preferred.try_move_storage(destination, source) {
+                Ok(()) if snapshot.is_some() => {
+                    self.restore_preferred(destination, snapshot.as_deref())
+                }
+                Ok(()) => {}
+                Err(restore_error) => warn!(
+                    "Failed moving [{}] back in {} after failing to move {}, caused by: [{restore_error}]",
+                    destination.display(),
+                    P::NAME,
+                    F::NAME
+                ),
+            }
+
+            return Err(e);
+        }
+
+        return Ok(());
+    }
+
+    fn list_storages(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let mut storages = self.preferred.list_storages(&dir)?;
+        storages.extend(self.fallback.list_storages(&dir)?);
+        storages.sort();
+        storages.dedup();
+
+        return Ok(storages);
+    }
+
+    fn list_partitions(&self) -> Result<Vec<String>> {
+        let mut partitions = self.preferred.list_partitions()?;
+        partitions.extend(self.fallback.list_partitions()?);
+        partitions.sort();
+        partitions.dedup();
+
+        return Ok(partitions);
+    }
+
+    /// Returns the size of the fallback storage if only the fallback storage exists, like [`DatabaseIO::storage_exists`]
+    fn storage_size(&self, path: impl AsRef<Path>) -> Result<u64> {
+        return match self.preferred.storage_size(&path) {
+            Err(Error::DBNotFound { .. }) => self.fallback.storage_size(self.fallback_path(path)),
+            result => result,
+        };
+    }
+
+    /// Restores the preferred storage if the fallback storage can't be removed
+    fn try_remove_storage(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let snapshot = self.preferred_snapshot(path)?;
+
+        return self.change_both(
+            path,
+            snapshot.as_deref(),
+            || self.preferred.try_remove_storage(path),
+            || match self.fallback.try_remove_storage(self.fallback_path(path)) {
+                Ok(()) | Err(Error::DBNotFound { .. }) => Ok(()),
+                Err(e) => Err(e),
+            },
+        );
+    }
+
+    fn storage_exists(&self, path: impl AsRef<Path>) -> Result<bool> {
+        return Ok(self.preferred.storage_exists(&path)?
+            || self.fallback.storage_exists(self.fallback_path(path))?);
+    }
+
+    /// Restores the preferred storage if the fallback storage can't be written
+    fn try_write_storage(&self, data: impl Serialize, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let snapshot = self.preferred_snapshot(path)?;
+
+        return self.change_both(
+            path,
+            snapshot.as_deref(),
+            || self.preferred.try_write_storage(&data, path),
+            || {
+                self.fallback
+                    .try_write_storage(&data, self.fallback_path(path))
+            },
+        );
+    }
+
+    /// Only the preferred storage is written, as the raw bytes are in a single format
+    fn try_write_storage_bytes(&self, bytes: &[u8], path: impl AsRef<Path>) -> Result<()> {
+        return self.preferred.try_write_storage_bytes(bytes, path);
+    }
+
+    /// Compares against the preferred storage only, the fallback storage is written once the preferred one is,
+    /// and the preferred storage is restored to the expected bytes if the fallback storage can't be written
+    fn compare_and_write(
+        &self,
+        expected_current: Option<&[u8]>,
+        data: impl Serialize,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+
+        return self.change_both(
+            path,
+            expected_current,
+            || {
+                self.preferred
+                    .compare_and_write(expected_current, &data, path)
+            },
+            || {
+                self.fallback
+                    .try_write_storage(&data, self.fallback_path(path))
+            },
+        );
+    }
+
+    fn version(&self, path: impl AsRef<Path>) -> Result<u64> {
+        return self.preferred.version(path);
+    }
+
+    fn open_append(&self, path: impl AsRef<Path>) -> Result<Box<dyn Write>> {
+        // Only the preferred storage is appended to, as the raw bytes are in a single format
+        return self.preferred.open_append(path);
+    }
+
+    fn write_storage_to<W: Write>(&self, data: impl Serialize, writer: &mut W) -> Result<()> {
+        return self.preferred.write_storage_to(data, writer);
+    }
+
+    fn read_storage_from<R: Read, O: for<'a> Deserialize<'a>>(&self, reader: &mut R) -> Result<O> {
+        return self.preferred.read_storage_from(reader);
+    }
+
+    fn read_seq_from<R: Read, O: for<'a> Deserialize<'a>>(
+        &self,
+        reader: &mut R,
+        on_item: &mut dyn FnMut(O) -> Result<()>,
+    ) -> Result<()> {
+        return self.preferred.read_seq_from(reader, on_item);
+    }
+
+    fn try_read_storage_bytes(&self, path: impl AsRef<Path>) -> Result<Vec<u8>> {
+        return self.preferred.try_read_storage_bytes(path);
+    }
+
+    fn try_read_storage<O: for<'a> Deserialize<'a>>(&self, path: impl AsRef<Path>) -> Result<O> {
+        return self.preferred.try_read_storage(&path).or_else(|e| {
+            warn!(
+                "Failed reading [{}] from {}, falling back to {}, caused by: [{e}]",
+                path.as_ref().display(),
+                P::NAME,
+                F::NAME
+            );
+
+            return self.fallback.try_read_storage(self.fallback_path(&path));
+        });
+    }
+
+    /// Checks the fallback if the preferred storage can't be read, as [`DatabaseIO::try_read_storage`] would fall back to it
+    fn check_storage(&self, path: impl AsRef<Path>) -> Result<()> {
+        return self
+            .preferred
+            .check_storage(&path)
+            .or_else(|_| self.fallback.check_storage(self.fallback_path(&path)));
+    }
+
+    fn try_read_storage_lenient<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(O, Vec<String>)> {
+        return self.preferred.try_read_storage_lenient(&path).or_else(|e| {
+            warn!(
+                "Failed reading [{}] from {}, falling back to {}, caused by: [{e}]",
+                path.as_ref().display(),
+                P::NAME,
+                F::NAME
+            );
+
+            return self
+                .fallback
+                .try_read_storage_lenient(self.fallback_path(&path));
+        });
+    }
+
+    /// Reads from the fallback if the preferred storage can't be read, the raw bytes are then in the fallback's format
+    fn read_with_raw<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(O, Vec<u8>)> {
+        return self.preferred.read_with_raw(&path).or_else(|e| {
+            warn!(
+                "Failed reading [{}] from {}, falling back to {}, caused by: [{e}]",
+                path.as_ref().display(),
+                P::NAME,
+                F::NAME
+            );
+
+            return self.fallback.read_with_raw(self.fallback_path(&path));
+        });
+    }
+}
+
+impl<P: DatabaseTransaction, F: Database> DatabaseTransaction for MultiFormatDB<P, F> {
+    type TransactionDB = P::TransactionDB;
+}
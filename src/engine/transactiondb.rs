@@ -1,7 +1,10 @@
 use super::memorydb::MemoryDB;
-use crate::{Deserialize, Result, Serialize, prelude::*, utils::*};
+use crate::{Deserialize, Result, Serialize, prelude::*, transaction::*, utils::*};
+#[cfg(feature = "json")]
+use std::collections::BTreeMap;
 use std::{
     borrow::Borrow,
+    io::{Read, Write},
     path::{Path, PathBuf},
 };
 
@@ -23,6 +26,42 @@ impl<S: BytesSerde> Database for TransactionDB<S> {
             records_after: MemoryDB::new(&dir),
         };
     }
+
+    fn with_namespace(&self, namespace: impl AsRef<str>) -> Self {
+        return Self {
+            dir: self.dir.join(namespace.as_ref()),
+            records_before: self.records_before.with_namespace(&namespace),
+            records_after: self.records_after.with_namespace(&namespace),
+        };
+    }
+}
+
+impl<S: BytesSerde> TransactionDB<S> {
+    /// Returns the stored snapshot of every partition touched by the transaction, as raw values
+    ///
+    /// Storages holding a list (e.g. records) are returned as-is, any other storage is returned as a single value.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    #[cfg(feature = "json")]
+    pub fn snapshot_before(&self) -> Result<BTreeMap<String, Vec<serde_json::Value>>> {
+        return self
+            .records_before
+            .list_partitions()?
+            .into_iter()
+            .map(|partition| {
+                let value = self.records_before.try_read_storage::<serde_json::Value>(
+                    self.records_before.file_path(&partition),
+                )?;
+
+                return Ok(match value {
+                    serde_json::Value::Array(values) => (partition, values),
+                    value => (partition, vec![value]),
+                });
+            })
+            .collect();
+    }
 }
 
 impl<S: BytesSerde> DatabaseOps for TransactionDB<S> {}
@@ -43,6 +82,10 @@ impl<S: BytesSerde> DatabaseIO for TransactionDB<S> {
 
     fn dir(&self) -> PathBuf { self.dir.clone() }
 
+    fn dir_canonical(&self) -> Result<PathBuf> { return Ok(self.dir_absolute()); }
+
+    fn clock(&self) -> Clock { self.records_after.clock() }
+
     fn try_copy_storage(
         &self,
         source: impl AsRef<Path>,
@@ -51,13 +94,102 @@ impl<S: BytesSerde> DatabaseIO for TransactionDB<S> {
         return self.records_after.try_copy_storage(source, destination);
     }
 
+    fn try_move_storage(
+        &self,
+        source: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> Result<()> {
+        return self.records_after.try_move_storage(source, destination);
+    }
+
+    fn list_storages(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        return self.records_after.list_storages(dir);
+    }
+
+    fn list_partitions(&self) -> Result<Vec<String>> {
+        return self.records_after.list_partitions();
+    }
+
+    fn storage_size(&self, path: impl AsRef<Path>) -> Result<u64> {
+        return self.records_after.storage_size(path);
+    }
+
+    fn try_remove_storage(&self, path: impl AsRef<Path>) -> Result<()> {
+        return self.records_after.try_remove_storage(path);
+    }
+
+    fn storage_exists(&self, path: impl AsRef<Path>) -> Result<bool> {
+        return self.records_after.storage_exists(path);
+    }
+
     fn try_write_storage(&self, data: impl Serialize, path: impl AsRef<Path>) -> Result<()> {
         return self.records_after.try_write_storage(data, path);
     }
 
+    fn try_write_storage_bytes(&self, bytes: &[u8], path: impl AsRef<Path>) -> Result<()> {
+        return self.records_after.try_write_storage_bytes(bytes, path);
+    }
+
+    fn version(&self, path: impl AsRef<Path>) -> Result<u64> {
+        return self.records_after.version(path);
+    }
+
+    fn open_append(&self, path: impl AsRef<Path>) -> Result<Box<dyn Write>> {
+        return self.records_after.open_append(path);
+    }
+
+    fn write_storage_to<W: Write>(&self, data: impl Serialize, writer: &mut W) -> Result<()> {
+        return try_write_to::<S, _>(data, writer);
+    }
+
+    fn read_storage_from<R: Read, O: for<'a> Deserialize<'a>>(&self, reader: &mut R) -> Result<O> {
+        return try_read_from::<S, _, _>(reader);
+    }
+
+    fn read_seq_from<R: Read, O: for<'a> Deserialize<'a>>(
+        &self,
+        reader: &mut R,
+        on_item: &mut dyn FnMut(O) -> Result<()>,
+    ) -> Result<()> {
+        return S::try_deserialize_seq_from_reader(reader, on_item);
+    }
+
+    fn try_read_storage_bytes(&self, path: impl AsRef<Path>) -> Result<Vec<u8>> {
+        return self.records_after.try_read_storage_bytes(path);
+    }
+
+    fn compare_and_write(
+        &self,
+        expected_current: Option<&[u8]>,
+        data: impl Serialize,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        return self
+            .records_after
+            .compare_and_write(expected_current, data, path);
+    }
+
     fn try_read_storage<O: for<'a> Deserialize<'a>>(&self, path: impl AsRef<Path>) -> Result<O> {
         return self.records_after.try_read_storage::<O>(path);
     }
+
+    fn check_storage(&self, path: impl AsRef<Path>) -> Result<()> {
+        return self.records_after.check_storage(path);
+    }
+
+    fn try_read_storage_lenient<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(O, Vec<String>)> {
+        return self.records_after.try_read_storage_lenient::<O>(path);
+    }
+
+    fn read_with_raw<O: for<'a> Deserialize<'a>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(O, Vec<u8>)> {
+        return self.records_after.read_with_raw::<O>(path);
+    }
 }
 
 impl<S: BytesSerde> DatabaseTransactionOps for TransactionDB<S> {}
@@ -3,7 +3,12 @@ use std::path::PathBuf;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
+/// The errors of every database operation
+///
+/// New variants may be added in any release, so matching must include a wildcard arm.
+/// Prefer the classification helpers (e.g. [`Error::is_not_found`]) to branch on categories of errors.
 #[derive(Debug, Error, Display)]
+#[non_exhaustive]
 pub enum Error {
     // -- I/O
     #[display("Directory creation at [{path}] failed, caused by: [{reason}]")]
@@ -19,12 +24,40 @@ pub enum Error {
         reason: std::io::Error,
     },
 
+    #[display("Move from [{path_from}] to [{path_destination}] failed, caused by: [{reason}]")]
+    IOMoveFailure {
+        path_from: String,
+        path_destination: String,
+        reason: std::io::Error,
+    },
+
+    #[display("Removal of [{path}] failed, caused by: [{reason}]")]
+    IORemoveFailure {
+        path: String,
+        reason: std::io::Error,
+    },
+
+    #[display("Reading directory at [{path}] failed, caused by: [{reason}]")]
+    IOReadDirFailure {
+        path: String,
+        reason: std::io::Error,
+    },
+
+    #[display("Checking existence of [{path}] failed, caused by: [{reason}]")]
+    IOExistsFailure {
+        path: String,
+        reason: std::io::Error,
+    },
+
     #[display("Write to file at [{path}] failed, caused by: [{reason}]")]
     IOWriteFailure {
         path: String,
         reason: std::io::Error,
     },
 
+    #[display("Streaming storage data failed, caused by: [{reason}]")]
+    IOStreamFailure { reason: std::io::Error },
+
     // -- Serde
     #[display("Serialization failed, caused by: [{_0}]")]
     SerializationFailure(Box<dyn std::error::Error + Send + Sync>),
@@ -39,12 +72,118 @@ pub enum Error {
     #[display("Database file at [{}] is corrupt, caused by: [{reason}]", std::path::absolute(file_path).unwrap().display())]
     DBCorrupt { file_path: PathBuf, reason: String },
 
+    #[display("Database file at [{}] is too large, [{size}] bytes exceeds the limit of [{max_bytes}] bytes.", std::path::absolute(file_path).unwrap().display())]
+    DBTooLarge {
+        file_path: PathBuf,
+        size: u64,
+        max_bytes: u64,
+    },
+
+    #[display(
+        "Partition name [{partition}] is invalid, it must be a plain file name without `@`, and a route must be its record's partition or start with it and `@`."
+    )]
+    DBInvalidPartition { partition: String },
+
     #[display("Database operation failed: [{}], caused by: [{reason}]", std::path::absolute(path).unwrap().display())]
     DBOperationFailure { path: String, reason: String },
 
+    #[display("Serialization for database file at [{}] failed, caused by: [{reason}]", std::path::absolute(file_path).unwrap().display())]
+    DBSerializationFailure {
+        file_path: PathBuf,
+        reason: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[display("Deserialization for database file at [{}] failed, caused by: [{reason}]", std::path::absolute(file_path).unwrap().display())]
+    DBDeserializationFailure {
+        file_path: PathBuf,
+        reason: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[display("Database directory at [{}] is inaccessible, caused by: [{reason}]", std::path::absolute(path).unwrap().display())]
+    DBInaccessible {
+        path: PathBuf,
+        reason: std::io::Error,
+    },
+
+    #[display("Database storage at [{}] has changed since it was last read.", std::path::absolute(file_path).unwrap().display())]
+    DBTransactionConflict { file_path: PathBuf },
+
     #[display("Database transaction commit failed: [{}], caused by: [{reason}]", std::path::absolute(file_path).unwrap().display())]
     DBTransactionCommitFailure { file_path: PathBuf, reason: String },
 
-    #[display("Database transaction rollback failed: [{}], caused by: [{reason}]", std::path::absolute(file_path).unwrap().display())]
-    DBTransactionRollbackFailure { file_path: PathBuf, reason: String },
+    #[display("Database transaction rollback failed: [{}], caused by: [{reason}], after commit failed, caused by: [{commit_reason}]", std::path::absolute(file_path).unwrap().display())]
+    DBTransactionRollbackFailure {
+        file_path: PathBuf,
+        reason: String,
+        commit_reason: String,
+    },
+}
+
+impl Error {
+    /// Returns whether the storage doesn't exist
+    pub fn is_not_found(&self) -> bool { return matches!(self, Self::DBNotFound { .. }); }
+
+    /// Returns whether the data couldn't be parsed, be it a storage or data without a path (e.g. an imported stream)
+    pub fn is_corrupt(&self) -> bool {
+        return matches!(
+            self,
+            Self::DBCorrupt { .. }
+                | Self::DBDeserializationFailure { .. }
+                | Self::DeserializationFailure(_)
+        );
+    }
+
+    /// Returns whether the storage was changed concurrently, e.g. since a transaction read it
+    pub fn is_conflict(&self) -> bool { return matches!(self, Self::DBTransactionConflict { .. }); }
+
+    /// Returns whether the error was caused by the underlying I/O
+    pub fn is_io(&self) -> bool {
+        return matches!(
+            self,
+            Self::IOCreateDirFailure { .. }
+                | Self::IOCopyFailure { .. }
+                | Self::IOMoveFailure { .. }
+                | Self::IORemoveFailure { .. }
+                | Self::IOReadDirFailure { .. }
+                | Self::IOExistsFailure { .. }
+                | Self::IOWriteFailure { .. }
+                | Self::IOStreamFailure { .. }
+                | Self::DBInaccessible { .. }
+        );
+    }
+}
+
+/// Wraps an error raised while serializing, converting into [`Error::SerializationFailure`]
+///
+/// Serde errors such as `serde_json::Error` are raised both while serializing and deserializing,
+/// so the direction has to be given explicitly (e.g. `.map_err(SerializeError)?`).
+#[derive(Debug)]
+pub struct SerializeError<E>(pub E);
+
+/// Wraps an error raised while deserializing, converting into [`Error::DeserializationFailure`]
+///
+/// See [`SerializeError`] for details.
+#[derive(Debug)]
+pub struct DeserializeError<E>(pub E);
+
+impl<E: std::error::Error + Send + Sync + 'static> From<SerializeError<E>> for Error {
+    fn from(error: SerializeError<E>) -> Self { Self::SerializationFailure(Box::new(error.0)) }
+}
+
+impl<E: std::error::Error + Send + Sync + 'static> From<DeserializeError<E>> for Error {
+    fn from(error: DeserializeError<E>) -> Self { Self::DeserializationFailure(Box::new(error.0)) }
+}
+
+#[cfg(feature = "cbor")]
+impl From<minicbor_serde::error::EncodeError<std::convert::Infallible>> for Error {
+    fn from(error: minicbor_serde::error::EncodeError<std::convert::Infallible>) -> Self {
+        Self::SerializationFailure(Box::new(error))
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl From<minicbor_serde::error::DecodeError> for Error {
+    fn from(error: minicbor_serde::error::DecodeError) -> Self {
+        Self::DeserializationFailure(Box::new(error))
+    }
 }
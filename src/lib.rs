@@ -6,6 +6,8 @@ pub mod error;
 pub mod prelude;
 pub mod record;
 pub mod serdes;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod transaction;
 mod utils;
 
@@ -1 +1,9 @@
-pub use crate::{database::*, engine::*, record::*, serdes::*, transaction::*};
+pub use crate::{database::*, engine::*, record::*, serdes::*};
+
+/// Transaction support, kept apart from the base prelude
+///
+/// `use lupabase::prelude::transactions::*` brings in every transaction trait and the transaction engine,
+/// alongside the base prelude.
+pub mod transactions {
+    pub use crate::{engine::TransactionDB, transaction::*};
+}
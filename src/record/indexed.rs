@@ -0,0 +1,78 @@
+use crate::record::*;
+use std::{collections::HashMap, ops::Deref};
+
+/// Records indexed by their unique value, for constant time lookups, see [`DatabaseOps::get_all_indexed`](crate::database::DatabaseOps::get_all_indexed)
+///
+/// Dereferences to the records as read, keeping their order. If several records share a unique value,
+/// lookups return the first one, like [`DatabaseRecordsUtils::find_by_unique`](crate::record::utils::DatabaseRecordsUtils::find_by_unique).
+///
+/// # Example
+/// ```rust
+/// # use lupabase::prelude::*;
+/// # use serde::{Deserialize, Serialize};
+/// #
+/// # #[derive(Serialize, Deserialize)]
+/// # struct Record { id: u8, data: char }
+/// #
+/// # impl DatabaseRecord for Record {
+/// #    type Unique = u8;
+/// #
+/// #    fn unique_value(&self) -> Self::Unique { self.id }
+/// # }
+/// let records = Records::new(vec![
+///     Record { id: 3, data: 'a' },
+///     Record { id: 1, data: 'b' },
+/// ]);
+///
+/// assert_eq!(records.get(&1).map(|r| r.data), Some('b'));
+/// assert!(!records.contains(&2));
+/// assert_eq!(records.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Records<T: DatabaseRecord> {
+    records: Vec<T>,
+    index: HashMap<T::Unique, usize>,
+}
+
+impl<T: DatabaseRecord> Records<T> {
+    /// Indexes the records by their unique value
+    pub fn new(records: Vec<T>) -> Self {
+        let mut index = HashMap::with_capacity(records.len());
+        for (position, record) in records.iter().enumerate() {
+            // Only the first record of a unique value is indexed, borrowing skips computing it for the others
+            if let Some(unique) = record.unique_ref()
+                && index.contains_key(unique)
+            {
+                continue;
+            }
+
+            index.entry(record.unique_value()).or_insert(position);
+        }
+
+        return Self { records, index };
+    }
+
+    /// Returns the record with the unique value
+    pub fn get(&self, unique: &T::Unique) -> Option<&T> {
+        return self
+            .index
+            .get(unique)
+            .map(|&position| &self.records[position]);
+    }
+
+    /// Returns whether a record has the unique value
+    pub fn contains(&self, unique: &T::Unique) -> bool { return self.index.contains_key(unique); }
+
+    /// Returns the records, dropping the index
+    pub fn into_inner(self) -> Vec<T> { return self.records; }
+}
+
+impl<T: DatabaseRecord> From<Vec<T>> for Records<T> {
+    fn from(records: Vec<T>) -> Self { return Self::new(records); }
+}
+
+impl<T: DatabaseRecord> Deref for Records<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target { return &self.records; }
+}
@@ -1,9 +1,22 @@
 mod operatable;
 pub mod utils;
 pub use operatable::*;
+mod indexed;
+pub use indexed::*;
 
-use crate::{Deserialize, Serialize};
-use std::{fmt::Debug, hash::Hash};
+use crate::{Deserialize, Error, Result, Serialize};
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    hash::Hash,
+    marker::PhantomData,
+    path::{Component, Path},
+};
+
+/// Re-export of [`uuid::Uuid`], which implements [`IntoUnique`] and can be used as [`DatabaseRecord::Unique`]
+///
+/// Serialized as a string in human-readable formats (e.g. JSON) and as raw bytes otherwise (e.g. CBOR).
+#[cfg(feature = "uuid")]
+pub use uuid::Uuid;
 
 /// Unique identifier for [`DatabaseRecord`]
 ///
@@ -23,10 +36,125 @@ pub trait DatabaseRecord: Serialize + for<'a> Deserialize<'a> {
     /// This method should provide a value that uniquely identifies records of the same type,
     /// essential for database operations.
     fn unique_value(&self) -> Self::Unique;
+
+    /// Returns a reference to the unique identifier of the record, if the record stores it as-is
+    ///
+    /// Implementing this method lets lookups borrow the unique identifier instead of calling
+    /// [`DatabaseRecord::unique_value`] for every record, which matters for uniques that are
+    /// expensive to clone (e.g. [`String`]). Returns [`None`] by default.
+    fn unique_ref(&self) -> Option<&Self::Unique> { None }
+
+    /// Returns whether the unique identifier of the record equals the provided one
+    ///
+    /// Borrows through [`DatabaseRecord::unique_ref`] when available, falling back to [`DatabaseRecord::unique_value`].
+    fn unique_eq(&self, unique_value: &Self::Unique) -> bool {
+        return match self.unique_ref() {
+            Some(unique) => unique == unique_value,
+            None => &self.unique_value() == unique_value,
+        };
+    }
+}
+
+/// Separates the partition of [`DatabaseRecordPartitioned::ROUTED`] records from the suffix of their routes
+/// (e.g. `Logs@2024-01`), see [`DatabaseRecordPartitioned::route`]
+///
+/// Reserved for routes, so no other partition can be mistaken for a route.
+pub const ROUTE_SEPARATOR: char = '@';
+
+/// Checks that the partition name is usable as a storage's file name
+///
+/// Empty names, `.` and `..`, and names containing a path separator (`/` or `\`), would create storages
+/// outside the expected partition layout (e.g. `.json`, a sibling of the database's directory or a nested directory).
+/// Names containing [`ROUTE_SEPARATOR`] are reserved for routes.
+///
+/// # Errors
+/// - Invalid partition name ([`Error::DBInvalidPartition`])
+pub fn validate_partition_name(partition: &str) -> Result<()> {
+    let mut components = Path::new(partition).components();
+    let is_file_name = matches!(components.next(), Some(Component::Normal(name)) if name == partition)
+        && components.next().is_none();
+
+    if !is_file_name || partition.contains(['/', '\\', ROUTE_SEPARATOR]) {
+        return Err(Error::DBInvalidPartition {
+            partition: partition.to_string(),
+        });
+    }
+
+    return Ok(());
 }
 
 /// Represents a Record that has a built-in partition
 pub trait DatabaseRecordPartitioned: DatabaseRecord {
     /// Specifies the partition where records of this type are stored
     const PARTITION: &str;
+
+    /// Whether records of this type are split across partitions by [`DatabaseRecordPartitioned::route`]
+    ///
+    /// Defaults to `false`, so [`DatabaseOps::get_all`](crate::database::DatabaseOps::get_all) only reads
+    /// [`DatabaseRecordPartitioned::PARTITION`] without listing the storage's partitions.
+    ///
+    /// Routed records are read by [`DatabaseOps::get_all`](crate::database::DatabaseOps::get_all) and inserted by
+    /// [`DatabaseOps::insert_all`](crate::database::DatabaseOps::insert_all) and its variants. Operations which
+    /// rewrite existing records of a single partition (e.g. [`DatabaseOps::update`](crate::database::DatabaseOps::update),
+    /// [`DatabaseOps::upsert`](crate::database::DatabaseOps::upsert) or [`DatabaseOps::touch`](crate::database::DatabaseOps::touch))
+    /// fail for routed records instead, use their `*_with_path` variant on the routed partition.
+    const ROUTED: bool = false;
+
+    /// Returns the partition where the record is inserted, only used when [`DatabaseRecordPartitioned::ROUTED`] is set
+    ///
+    /// Routed partitions must be named `{PARTITION}@{suffix}` (e.g. `Logs@2024-01` for logs split by month,
+    /// see [`DatabaseRecordPartitioned::routed_partition`]), or exactly [`DatabaseRecordPartitioned::PARTITION`],
+    /// so they can all be found when reading. Other routes are refused with [`Error::DBInvalidPartition`].
+    fn route(&self) -> String { return Self::PARTITION.to_string(); }
+
+    /// Returns the name of the routed partition with the suffix, `{PARTITION}@{suffix}`
+    fn routed_partition(suffix: impl Display) -> String
+    where
+        Self: Sized, {
+        return format!("{}{ROUTE_SEPARATOR}{suffix}", Self::PARTITION);
+    }
+}
+
+/// The partition of a [`DatabaseRecordPartitioned`], tying its name to the record type at compile time
+///
+/// Displays as, and dereferences to a path of, [`DatabaseRecordPartitioned::PARTITION`], so it can be passed
+/// to the by-name APIs (e.g. [`DatabaseOpsCustom::get_all_by_name`](crate::database::DatabaseOpsCustom::get_all_by_name))
+/// instead of a string that may name another record's partition.
+pub struct PartitionId<T: DatabaseRecordPartitioned>(PhantomData<T>);
+
+impl<T: DatabaseRecordPartitioned> PartitionId<T> {
+    /// Returns the partition of `T`
+    pub const fn new() -> Self { return Self(PhantomData); }
+}
+
+impl<T: DatabaseRecordPartitioned> Default for PartitionId<T> {
+    fn default() -> Self { return Self::new(); }
+}
+
+impl<T: DatabaseRecordPartitioned> Clone for PartitionId<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T: DatabaseRecordPartitioned> Copy for PartitionId<T> {}
+
+impl<T: DatabaseRecordPartitioned> Debug for PartitionId<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return f.debug_tuple("PartitionId").field(&T::PARTITION).finish();
+    }
+}
+
+impl<T: DatabaseRecordPartitioned> Display for PartitionId<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { return f.write_str(T::PARTITION); }
+}
+
+impl<T: DatabaseRecordPartitioned> AsRef<Path> for PartitionId<T> {
+    fn as_ref(&self) -> &Path { return Path::new(T::PARTITION); }
+}
+
+/// Represents a partitioned Record whose unique identifiers can be queried by range
+///
+/// Useful for sortable unique identifiers (e.g. ULIDs or timestamps), see [`DatabaseOps::get_range`](crate::database::DatabaseOps::get_range).
+pub trait RangeQueryable: DatabaseRecordPartitioned {
+    /// Returns whether the unique identifier is within the inclusive range from `lo` to `hi`
+    fn unique_in_range(unique: &Self::Unique, lo: &Self::Unique, hi: &Self::Unique) -> bool;
 }
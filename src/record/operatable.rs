@@ -1,4 +1,11 @@
-use crate::{error::Result, prelude::DatabaseOps, record::DatabaseRecordPartitioned};
+use crate::{
+    Deserialize, Error, Serialize,
+    error::Result,
+    prelude::DatabaseOps,
+    record::{DatabaseRecord, DatabaseRecordPartitioned},
+    transaction::DatabaseTransaction,
+};
+use std::marker::PhantomData;
 
 /// Provides a composable variadic database operation for [`DatabaseRecordPartitioned`] with [`DatabaseOps`].
 ///
@@ -33,6 +40,18 @@ pub trait DatabaseRecordOperatablePartitioned {
 
     /// See [`DatabaseOps::try_initialize_storage`] for details and the list of possible errors.
     fn try_initialize_storage(db: &impl DatabaseOps, default_data: Self::Collection) -> Result<()>;
+
+    /// See [`DatabaseTransaction::try_commit`] for details and the list of possible errors.
+    fn try_commit<DB: DatabaseTransaction + ?Sized>(
+        db: &DB,
+        transaction: &DB::TransactionDB,
+    ) -> Result<()>;
+
+    /// See [`DatabaseTransaction::try_rollback`] for details and the list of possible errors.
+    fn try_rollback<DB: DatabaseTransaction + ?Sized>(
+        db: &DB,
+        transaction: &DB::TransactionDB,
+    ) -> Result<()>;
 }
 
 impl<R1> DatabaseRecordOperatablePartitioned for R1
@@ -74,6 +93,20 @@ where
     fn try_initialize_storage(db: &impl DatabaseOps, default_data: Self::Collection) -> Result<()> {
         return db.try_initialize_storage::<R1, Vec<R1>>(default_data);
     }
+
+    fn try_commit<DB: DatabaseTransaction + ?Sized>(
+        db: &DB,
+        transaction: &DB::TransactionDB,
+    ) -> Result<()> {
+        return db.try_commit::<R1>(transaction);
+    }
+
+    fn try_rollback<DB: DatabaseTransaction + ?Sized>(
+        db: &DB,
+        transaction: &DB::TransactionDB,
+    ) -> Result<()> {
+        return db.try_rollback::<R1>(transaction);
+    }
 }
 
 impl<R1, RN> DatabaseRecordOperatablePartitioned for (R1, RN)
@@ -126,4 +159,148 @@ where
         db.try_initialize_storage::<R1, Vec<R1>>(default_data.0)?;
         return RN::try_initialize_storage(db, default_data.1);
     }
+
+    /// Partitions are committed in order, and if any fails, the partitions committed before it are rolled back
+    fn try_commit<DB: DatabaseTransaction + ?Sized>(
+        db: &DB,
+        transaction: &DB::TransactionDB,
+    ) -> Result<()> {
+        db.try_commit::<R1>(transaction)?;
+
+        if let Err(commit_error) = RN::try_commit(db, transaction) {
+            db.try_rollback::<R1>(transaction)
+                .map_err(|rollback_error| Error::DBTransactionRollbackFailure {
+                    file_path: db.file_path(R1::PARTITION),
+                    reason: rollback_error.to_string(),
+                    commit_reason: commit_error.to_string(),
+                })?;
+
+            return Err(commit_error);
+        }
+
+        return Ok(());
+    }
+
+    fn try_rollback<DB: DatabaseTransaction + ?Sized>(
+        db: &DB,
+        transaction: &DB::TransactionDB,
+    ) -> Result<()> {
+        db.try_rollback::<R1>(transaction)?;
+        return RN::try_rollback(db, transaction);
+    }
+}
+
+/// Names a storage at a fixed path inside [`DatabaseIO::dir`](crate::database::DatabaseIO::dir),
+/// for the [`Single`] and [`Collection`] storage shapes
+pub trait StorageName {
+    /// The name of the storage, resolved through [`DatabaseIO::file_path`](crate::database::DatabaseIO::file_path)
+    const NAME: &str;
+}
+
+/// A single value of `T`, stored at the storage named by `N`
+///
+/// Only used as a type, to compose [`DatabaseStorageOperatable`] tuples.
+pub struct Single<T, N>(PhantomData<(T, N)>);
+
+/// A collection of [`DatabaseRecord`], stored at the storage named by `N`
+///
+/// Only used as a type, to compose [`DatabaseStorageOperatable`] tuples.
+pub struct Collection<T, N>(PhantomData<(T, N)>);
+
+/// Provides a composable variadic database operation for storages of any shape with [`DatabaseOps`].
+///
+/// Unlike [`DatabaseRecordOperatablePartitioned`], tuple elements may mix [`DatabaseRecordPartitioned`] with
+/// [`Single`] and [`Collection`] storages at fixed paths. As a single value has no records to insert or update,
+/// only whole storages are read and written.
+pub trait DatabaseStorageOperatable {
+    /// The data stored for this storage, [`Vec`] of records for partitioned records and collections
+    type Collection;
+
+    /// See [`DatabaseIO::try_read_storage`](crate::database::DatabaseIO::try_read_storage) for details and the list of possible errors.
+    fn get(db: &impl DatabaseOps) -> Result<Self::Collection>;
+
+    /// See [`DatabaseIO::try_write_storage`](crate::database::DatabaseIO::try_write_storage) for details and the list of possible errors.
+    fn write(db: &impl DatabaseOps, data: Self::Collection) -> Result<()>;
+
+    /// See [`DatabaseOps::try_initialize_storage`] for details and the list of possible errors.
+    fn try_initialize_storage(db: &impl DatabaseOps, default_data: Self::Collection) -> Result<()>;
+}
+
+impl<R1> DatabaseStorageOperatable for R1
+where
+    R1: DatabaseRecordPartitioned,
+{
+    type Collection = Vec<R1>;
+
+    fn get(db: &impl DatabaseOps) -> Result<Self::Collection> { return db.get_all(); }
+
+    fn write(db: &impl DatabaseOps, data: Self::Collection) -> Result<()> {
+        return db.replace_all(data);
+    }
+
+    fn try_initialize_storage(db: &impl DatabaseOps, default_data: Self::Collection) -> Result<()> {
+        return db.try_initialize_storage::<R1, Vec<R1>>(default_data);
+    }
+}
+
+impl<T, N> DatabaseStorageOperatable for Single<T, N>
+where
+    T: Serialize + for<'a> Deserialize<'a>,
+    N: StorageName,
+{
+    type Collection = T;
+
+    fn get(db: &impl DatabaseOps) -> Result<Self::Collection> {
+        return db.try_read_storage(db.file_path(N::NAME));
+    }
+
+    fn write(db: &impl DatabaseOps, data: Self::Collection) -> Result<()> {
+        return db.try_write_storage(data, db.file_path(N::NAME));
+    }
+
+    fn try_initialize_storage(db: &impl DatabaseOps, default_data: Self::Collection) -> Result<()> {
+        return db.try_initialize_storage_with_path(default_data, db.file_path(N::NAME));
+    }
+}
+
+impl<T, N> DatabaseStorageOperatable for Collection<T, N>
+where
+    T: DatabaseRecord,
+    N: StorageName,
+{
+    type Collection = Vec<T>;
+
+    fn get(db: &impl DatabaseOps) -> Result<Self::Collection> {
+        return db.get_all_by_name(N::NAME);
+    }
+
+    fn write(db: &impl DatabaseOps, data: Self::Collection) -> Result<()> {
+        return db.replace_all_with_path(data, db.file_path(N::NAME));
+    }
+
+    fn try_initialize_storage(db: &impl DatabaseOps, default_data: Self::Collection) -> Result<()> {
+        return db.try_initialize_storage_with_path(default_data, db.file_path(N::NAME));
+    }
+}
+
+impl<R1, RN> DatabaseStorageOperatable for (R1, RN)
+where
+    R1: DatabaseStorageOperatable,
+    RN: DatabaseStorageOperatable,
+{
+    type Collection = (R1::Collection, RN::Collection);
+
+    fn get(db: &impl DatabaseOps) -> Result<Self::Collection> {
+        return Ok((R1::get(db)?, RN::get(db)?));
+    }
+
+    fn write(db: &impl DatabaseOps, data: Self::Collection) -> Result<()> {
+        R1::write(db, data.0)?;
+        return RN::write(db, data.1);
+    }
+
+    fn try_initialize_storage(db: &impl DatabaseOps, default_data: Self::Collection) -> Result<()> {
+        R1::try_initialize_storage(db, default_data.0)?;
+        return RN::try_initialize_storage(db, default_data.1);
+    }
 }
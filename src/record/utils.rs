@@ -1,5 +1,17 @@
 use crate::record::*;
 use itertools::Itertools;
+use std::collections::HashMap;
+
+/// The changes turning one slice of records into another, see [`DatabaseRecordsUtils::diff_against`]
+#[derive(Debug)]
+pub struct SliceDiff<'a, T: DatabaseRecord> {
+    /// Desired records whose unique value is missing from the current records
+    pub to_insert: Vec<&'a T>,
+    /// Desired records whose unique value exists in the current records, but with different content
+    pub to_update: Vec<&'a T>,
+    /// Unique values of current records missing from the desired records
+    pub to_delete: Vec<T::Unique>,
+}
 
 /// Provide utility methods for DatabaseRecord.
 pub trait DatabaseRecordsUtils<T: DatabaseRecord> {
@@ -14,6 +26,13 @@ pub trait DatabaseRecordsUtils<T: DatabaseRecord> {
     ) -> Vec<<T as DatabaseRecord>::Unique>;
     fn find_by_unique(&self, unique: &T::Unique) -> Option<&T>;
     fn find_by_unique_mut(&mut self, unique_value: &T::Unique) -> Option<&mut T>;
+    fn chunk_by_size(&self, n: usize) -> Vec<&[T]>;
+    fn grouped_duplicates(&self) -> HashMap<T::Unique, Vec<&T>>;
+    fn index_positions(&self) -> HashMap<T::Unique, usize>;
+    fn as_map(&self) -> HashMap<T::Unique, &T>;
+    fn diff_against<'a>(&self, desired: &'a [T]) -> SliceDiff<'a, T>
+    where
+        T: PartialEq;
 }
 
 impl<T: DatabaseRecord> DatabaseRecordsUtils<T> for [T] {
@@ -125,7 +144,7 @@ impl<T: DatabaseRecord> DatabaseRecordsUtils<T> for [T] {
     /// assert_eq!(record.id, 2);
     /// ```
     fn find_by_unique(&self, unique_value: &T::Unique) -> Option<&T> {
-        self.iter().find(|r| &r.unique_value() == unique_value)
+        self.iter().find(|r| r.unique_eq(unique_value))
     }
     /// Returns a mutable reference to the record with the specified unique value, if it exists.
     ///
@@ -148,6 +167,313 @@ impl<T: DatabaseRecord> DatabaseRecordsUtils<T> for [T] {
     /// assert_eq!(record.id, 2);
     /// ```
     fn find_by_unique_mut(&mut self, unique_value: &T::Unique) -> Option<&mut T> {
-        self.iter_mut().find(|r| &r.unique_value() == unique_value)
+        self.iter_mut().find(|r| r.unique_eq(unique_value))
+    }
+    /// Returns the records in order, split into chunks of at most `n` records.
+    ///
+    /// A size of `0` returns every record in a single chunk, and no records return no chunks.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use lupabase::prelude::*;
+    /// # use lupabase::record::utils::*;
+    /// # use serde::{Deserialize, Serialize};
+    /// #
+    /// # #[derive(Serialize, Deserialize)]
+    /// # struct Record { id: u8 }
+    /// #
+    /// # impl DatabaseRecord for Record {
+    /// #    type Unique = u8;
+    /// #
+    /// #    fn unique_value(&self) -> Self::Unique { self.id }
+    /// # }
+    /// let records = [Record { id: 1 }, Record { id: 2 }, Record { id: 3 }];
+    /// let chunks = records.chunk_by_size(2);
+    /// assert_eq!(chunks.len(), 2);
+    /// assert_eq!(chunks[0].as_uniques(), vec![1, 2]);
+    /// assert_eq!(chunks[1].as_uniques(), vec![3]);
+    /// assert_eq!(records.chunk_by_size(0).len(), 1);
+    /// ```
+    fn chunk_by_size(&self, n: usize) -> Vec<&[T]> {
+        if self.is_empty() {
+            return vec![];
+        }
+
+        return self.chunks(if n == 0 { self.len() } else { n }).collect();
+    }
+    /// Returns every unique value shared by more than one record, mapped to all records sharing it, in order.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use lupabase::prelude::*;
+    /// # use lupabase::record::utils::*;
+    /// # use serde::{Deserialize, Serialize};
+    /// #
+    /// # #[derive(Serialize, Deserialize)]
+    /// # struct Record { id: u8, data: String }
+    /// #
+    /// # impl DatabaseRecord for Record {
+    /// #    type Unique = u8;
+    /// #
+    /// #    fn unique_value(&self) -> Self::Unique { self.id }
+    /// # }
+    /// let records = [
+    ///     Record {
+    ///         id: 1,
+    ///         data: "first".into(),
+    ///     },
+    ///     Record {
+    ///         id: 2,
+    ///         data: "only".into(),
+    ///     },
+    ///     Record {
+    ///         id: 1,
+    ///         data: "second".into(),
+    ///     },
+    /// ];
+    /// let duplicates = records.grouped_duplicates();
+    /// assert_eq!(duplicates.len(), 1);
+    /// assert_eq!(
+    ///     duplicates[&1]
+    ///         .iter()
+    ///         .map(|r| r.data.as_str())
+    ///         .collect::<Vec<_>>(),
+    ///     vec!["first", "second"]
+    /// );
+    /// ```
+    fn grouped_duplicates(&self) -> HashMap<T::Unique, Vec<&T>> {
+        let mut groups = self.iter().into_group_map_by(|r| r.unique_value());
+        groups.retain(|_, records| records.len() > 1);
+
+        return groups;
+    }
+    /// Returns every unique value mapped to the position of its record, for lookups in constant time.
+    ///
+    /// If several records share a unique value, the position of the last one wins,
+    /// see [`DatabaseRecordsUtils::grouped_duplicates`] to detect them first.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use lupabase::prelude::*;
+    /// # use lupabase::record::utils::*;
+    /// # use serde::{Deserialize, Serialize};
+    /// #
+    /// # #[derive(Serialize, Deserialize)]
+    /// # struct Record { id: u8 }
+    /// #
+    /// # impl DatabaseRecord for Record {
+    /// #    type Unique = u8;
+    /// #
+    /// #    fn unique_value(&self) -> Self::Unique { self.id }
+    /// # }
+    /// let records = [Record { id: 3 }, Record { id: 1 }, Record { id: 3 }];
+    /// let positions = records.index_positions();
+    /// assert_eq!(positions.len(), 2);
+    /// assert_eq!(positions[&1], 1);
+    /// assert_eq!(positions[&3], 2);
+    /// ```
+    fn index_positions(&self) -> HashMap<T::Unique, usize> {
+        let mut positions = HashMap::with_capacity(self.len());
+        for (position, r) in self.iter().enumerate() {
+            // Borrowing skips computing the unique value of records already indexed
+            if let Some(unique) = r.unique_ref()
+                && let Some(indexed) = positions.get_mut(unique)
+            {
+                *indexed = position;
+                continue;
+            }
+
+            let _ = positions.insert(r.unique_value(), position);
+        }
+
+        return positions;
+    }
+
+    /// Returns every record keyed by its unique value.
+    ///
+    /// If several records share a unique value, the last one wins,
+    /// see [`DatabaseRecordsUtils::grouped_duplicates`] to detect them first.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use lupabase::prelude::*;
+    /// # use lupabase::record::utils::*;
+    /// # use serde::{Deserialize, Serialize};
+    /// #
+    /// # #[derive(Serialize, Deserialize)]
+    /// # struct Record { id: u8, data: char }
+    /// #
+    /// # impl DatabaseRecord for Record {
+    /// #    type Unique = u8;
+    /// #
+    /// #    fn unique_value(&self) -> Self::Unique { self.id }
+    /// # }
+    /// let records = [
+    ///     Record { id: 3, data: 'a' },
+    ///     Record { id: 1, data: 'b' },
+    ///     Record { id: 3, data: 'c' },
+    /// ];
+    /// let map = records.as_map();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map[&1].data, 'b');
+    /// assert_eq!(map[&3].data, 'c');
+    /// ```
+    fn as_map(&self) -> HashMap<T::Unique, &T> {
+        let mut map = HashMap::with_capacity(self.len());
+        for r in self {
+            // Borrowing skips computing the unique value of records already mapped
+            if let Some(unique) = r.unique_ref()
+                && let Some(mapped) = map.get_mut(unique)
+            {
+                *mapped = r;
+                continue;
+            }
+
+            let _ = map.insert(r.unique_value(), r);
+        }
+
+        return map;
+    }
+    /// Returns the changes turning these records into the desired records, comparing records by unique value,
+    /// and then by content to find updates. Unchanged records are left out.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use lupabase::prelude::*;
+    /// # use lupabase::record::utils::*;
+    /// # use serde::{Deserialize, Serialize};
+    /// #
+    /// # #[derive(Serialize, Deserialize, PartialEq)]
+    /// # struct Record { id: u8, data: u8 }
+    /// #
+    /// # impl DatabaseRecord for Record {
+    /// #    type Unique = u8;
+    /// #
+    /// #    fn unique_value(&self) -> Self::Unique { self.id }
+    /// # }
+    /// let current = [
+    ///     Record { id: 1, data: 1 },
+    ///     Record { id: 2, data: 2 },
+    ///     Record { id: 3, data: 3 },
+    /// ];
+    /// let desired = [
+    ///     Record { id: 1, data: 1 },
+    ///     Record { id: 2, data: 20 },
+    ///     Record { id: 4, data: 4 },
+    /// ];
+    /// let diff = current.diff_against(&desired);
+    /// assert!(diff.to_insert == [&desired[2]]);
+    /// assert!(diff.to_update == [&desired[1]]);
+    /// assert_eq!(diff.to_delete, vec![3]);
+    /// ```
+    fn diff_against<'a>(&self, desired: &'a [T]) -> SliceDiff<'a, T>
+    where
+        T: PartialEq, {
+        let mut diff = SliceDiff {
+            to_insert: vec![],
+            to_update: vec![],
+            to_delete: vec![],
+        };
+
+        for desired_record in desired {
+            match self.find_by_unique(&desired_record.unique_value()) {
+                None => diff.to_insert.push(desired_record),
+                Some(record) if record != desired_record => diff.to_update.push(desired_record),
+                Some(_) => {}
+            }
+        }
+
+        diff.to_delete = self
+            .iter()
+            .filter(|r| desired.find_by_unique(&r.unique_value()).is_none())
+            .map(|r| r.unique_value())
+            .collect();
+
+        return diff;
+    }
+}
+
+/// Provide utility methods for owned DatabaseRecord collections.
+pub trait DatabaseRecordsOwnedUtils<T: DatabaseRecord> {
+    fn to_map(self) -> HashMap<T::Unique, T>;
+    fn into_sorted_by_unique(self) -> Vec<T>
+    where
+        T::Unique: Ord;
+}
+
+impl<T: DatabaseRecord> DatabaseRecordsOwnedUtils<T> for Vec<T> {
+    /// Consumes the records, returning them keyed by their unique value.
+    ///
+    /// If several records share a unique value, the last one wins,
+    /// see [`DatabaseRecordsUtils::as_map`] for the borrowing variant.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use lupabase::prelude::*;
+    /// # use lupabase::record::utils::*;
+    /// # use serde::{Deserialize, Serialize};
+    /// #
+    /// # #[derive(Serialize, Deserialize)]
+    /// # struct Record { id: u8, data: char }
+    /// #
+    /// # impl DatabaseRecord for Record {
+    /// #    type Unique = u8;
+    /// #
+    /// #    fn unique_value(&self) -> Self::Unique { self.id }
+    /// # }
+    /// let records = vec![
+    ///     Record { id: 3, data: 'a' },
+    ///     Record { id: 1, data: 'b' },
+    ///     Record { id: 3, data: 'c' },
+    /// ];
+    /// let map = records.to_map();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map[&1].data, 'b');
+    /// assert_eq!(map[&3].data, 'c');
+    /// ```
+    fn to_map(self) -> HashMap<T::Unique, T> {
+        return self.into_iter().map(|r| (r.unique_value(), r)).collect();
+    }
+
+    /// Consumes the records, returning them sorted by their unique value.
+    ///
+    /// The sort is stable, so records sharing a unique value keep their order.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use lupabase::prelude::*;
+    /// # use lupabase::record::utils::*;
+    /// # use serde::{Deserialize, Serialize};
+    /// #
+    /// # #[derive(Serialize, Deserialize)]
+    /// # struct Record { id: u8, data: char }
+    /// #
+    /// # impl DatabaseRecord for Record {
+    /// #    type Unique = u8;
+    /// #
+    /// #    fn unique_value(&self) -> Self::Unique { self.id }
+    /// # }
+    /// #
+    /// # fn main() -> lupabase::Result<()> {
+    /// let db = MemoryDB::<JsonSerde>::new("Sorted");
+    /// let records = vec![
+    ///     Record { id: 3, data: 'a' },
+    ///     Record { id: 1, data: 'b' },
+    ///     Record { id: 2, data: 'c' },
+    /// ];
+    ///
+    /// db.try_write_storage(records.into_sorted_by_unique(), "records")?;
+    ///
+    /// let stored = db.try_read_storage::<Vec<Record>>("records")?;
+    /// assert_eq!(stored.as_uniques(), vec![1, 2, 3]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn into_sorted_by_unique(mut self) -> Vec<T>
+    where
+        T::Unique: Ord, {
+        self.sort_by_key(|r| r.unique_value());
+
+        return self;
     }
 }
@@ -1,4 +1,5 @@
-use crate::{Deserialize, Result, Serialize};
+use crate::{Deserialize, Error, Result, Serialize};
+use std::io::Read;
 
 /// Provides bytes Serialization / Deserialization
 pub trait BytesSerde {
@@ -13,9 +14,107 @@ pub trait BytesSerde {
 
     /// Attempts to deserialize the provided data from bytes
     ///
+    /// Failures are attributed to the offending field when possible (e.g. `[0].id: invalid value: integer 0`).
+    ///
     /// # Errors
     /// - Parsing failure
     fn try_deserialize_from_bytes<'de, O: Deserialize<'de>>(bytes: &'de [u8]) -> Result<O>;
+
+    /// Attempts to deserialize the provided data from bytes, reporting the path of every ignored (unknown) field
+    ///
+    /// Reports nothing by default.
+    ///
+    /// # Errors
+    /// - Parsing failure
+    fn try_deserialize_from_bytes_reporting_ignored<'de, O: Deserialize<'de>>(
+        bytes: &'de [u8],
+        on_ignored: &mut dyn FnMut(String),
+    ) -> Result<O> {
+        let _ = on_ignored;
+
+        return Self::try_deserialize_from_bytes(bytes);
+    }
+
+    /// Attempts to deserialize a sequence from the reader, passing every item to `on_item` as soon as it's deserialized
+    ///
+    /// The items are never collected, nor is the whole input held in memory by `JsonSerde` and `CborSerde`.
+    /// By default the whole input is read and deserialized before passing the items.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    /// - Any error returned by `on_item`, which stops the deserialization
+    fn try_deserialize_seq_from_reader<R: Read, O: for<'a> Deserialize<'a>>(
+        reader: &mut R,
+        on_item: &mut dyn FnMut(O) -> Result<()>,
+    ) -> Result<()> {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|e| Error::IOStreamFailure { reason: e })?;
+
+        for item in Self::try_deserialize_from_bytes::<Vec<O>>(&bytes)? {
+            on_item(item)?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(feature = "json")]
+use seq::*;
+
+#[cfg(feature = "json")]
+mod seq {
+    use super::*;
+    use crate::error::DeserializeError;
+    use serde_core::de::{self, Deserializer, SeqAccess, Visitor};
+    use std::fmt;
+
+    /// Deserializes a sequence, passing every item to `on_item` instead of collecting them
+    ///
+    /// An error returned by `on_item` stops the deserialization, and is returned instead of the resulting parsing failure.
+    pub fn deserialize_seq_with<'de, D: Deserializer<'de>, O: Deserialize<'de>>(
+        deserializer: D,
+        on_item: &mut dyn FnMut(O) -> Result<()>,
+    ) -> Result<()>
+    where
+        D::Error: std::error::Error + Send + Sync + 'static, {
+        let mut visitor = SeqVisitor {
+            on_item,
+            error: None,
+        };
+
+        return match deserializer.deserialize_seq(&mut visitor) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(visitor.error.unwrap_or_else(|| DeserializeError(e).into())),
+        };
+    }
+
+    struct SeqVisitor<'f, O> {
+        on_item: &'f mut dyn FnMut(O) -> Result<()>,
+        error: Option<Error>,
+    }
+
+    impl<'de, O: Deserialize<'de>> Visitor<'de> for &mut SeqVisitor<'_, O> {
+        type Value = ();
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            return formatter.write_str("a sequence");
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+            while let Some(item) = seq.next_element::<O>()? {
+                if let Err(e) = (self.on_item)(item) {
+                    self.error = Some(e);
+
+                    return Err(de::Error::custom("Stopped by the item callback"));
+                }
+            }
+
+            return Ok(());
+        }
+    }
 }
 
 #[cfg(feature = "cbor")]
@@ -24,7 +123,8 @@ pub use cbor::*;
 #[cfg(feature = "cbor")]
 mod cbor {
     use super::*;
-    use crate::Error;
+    use crate::error::DeserializeError;
+    use std::io::{self, BufReader};
 
     #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
     pub struct CborSerde;
@@ -33,13 +133,148 @@ mod cbor {
         const FORMAT: &str = "cbor";
 
         fn try_serialize_as_bytes<S: Serialize>(data: S) -> Result<Vec<u8>> {
-            minicbor_serde::to_vec(&data).map_err(|e| Error::SerializationFailure(Box::new(e)))
+            return Ok(minicbor_serde::to_vec(&data)?);
         }
 
         fn try_deserialize_from_bytes<'de, O: Deserialize<'de>>(bytes: &'de [u8]) -> Result<O> {
-            minicbor_serde::from_slice(bytes)
-                .map_err(|e| Error::DeserializationFailure(Box::new(e)))
+            let mut deserializer = minicbor_serde::Deserializer::new(bytes);
+
+            return Ok(
+                serde_path_to_error::deserialize(&mut deserializer).map_err(DeserializeError)?
+            );
         }
+
+        fn try_deserialize_from_bytes_reporting_ignored<'de, O: Deserialize<'de>>(
+            bytes: &'de [u8],
+            on_ignored: &mut dyn FnMut(String),
+        ) -> Result<O> {
+            let mut deserializer = minicbor_serde::Deserializer::new(bytes);
+            let mut callback = |path: serde_ignored::Path<'_>| on_ignored(path.to_string());
+            let deserializer = serde_ignored::Deserializer::new(&mut deserializer, &mut callback);
+
+            return Ok(serde_path_to_error::deserialize(deserializer).map_err(DeserializeError)?);
+        }
+
+        /// CBOR is only parsed from bytes, so the bytes of each item are read (and held) one at a time
+        fn try_deserialize_seq_from_reader<R: Read, O: for<'a> Deserialize<'a>>(
+            reader: &mut R,
+            on_item: &mut dyn FnMut(O) -> Result<()>,
+        ) -> Result<()> {
+            let mut reader = BufReader::new(reader);
+            let mut item = vec![];
+
+            let length = match read_head(&mut reader, &mut item).map_err(read_failure)? {
+                Head::Item(4, length) => length,
+                _ => return Err(read_failure(invalid_data("Expected an array of items"))),
+            };
+
+            let mut index = 0;
+            while length.is_none_or(|length| index < length) {
+                item.clear();
+                if !read_item(&mut reader, &mut item, 0).map_err(read_failure)? {
+                    if length.is_some() {
+                        return Err(read_failure(invalid_data("Unexpected break")));
+                    }
+
+                    break;
+                }
+
+                on_item(Self::try_deserialize_from_bytes(&item)?)?;
+                index += 1;
+            }
+
+            return Ok(());
+        }
+    }
+
+    /// The head of a CBOR data item, its major type and argument (`None` for an indefinite length), or a break
+    enum Head {
+        Item(u8, Option<u64>),
+        Break,
+    }
+
+    /// Reads the head of the next CBOR data item, appending its bytes to the buffer
+    fn read_head<R: Read>(reader: &mut R, buffer: &mut Vec<u8>) -> io::Result<Head> {
+        let mut initial = [0; 1];
+        reader.read_exact(&mut initial)?;
+        buffer.push(initial[0]);
+
+        let (major, info) = (initial[0] >> 5, initial[0] & 0x1F);
+        let argument = match info {
+            0..=23 => Some(u64::from(info)),
+            24..=27 => {
+                let size = 1 << (info - 24);
+                let mut argument = [0; 8];
+                reader.read_exact(&mut argument[8 - size..])?;
+                buffer.extend_from_slice(&argument[8 - size..]);
+
+                Some(u64::from_be_bytes(argument))
+            }
+            31 if major == 7 => return Ok(Head::Break),
+            31 if (2..=5).contains(&major) => None,
+            _ => return Err(invalid_data("Invalid additional information")),
+        };
+
+        return Ok(Head::Item(major, argument));
+    }
+
+    /// The deepest nesting of CBOR data items read, so malformed input can't overflow the stack
+    const MAX_DEPTH: usize = 256;
+
+    /// Reads the next CBOR data item whole, appending its bytes to the buffer
+    ///
+    /// Returns `false` on a break, which ends an item of indefinite length.
+    fn read_item<R: Read>(reader: &mut R, buffer: &mut Vec<u8>, depth: usize) -> io::Result<bool> {
+        if depth > MAX_DEPTH {
+            return Err(invalid_data("Items nested too deeply"));
+        }
+
+        let read_items = |reader: &mut R, buffer: &mut Vec<u8>, count: u64| {
+            for _ in 0..count {
+                if !read_item(reader, buffer, depth + 1)? {
+                    return Err(invalid_data("Unexpected break"));
+                }
+            }
+
+            return Ok(());
+        };
+
+        match read_head(reader, buffer)? {
+            Head::Break => return Ok(false),
+            Head::Item(2 | 3, Some(length)) => {
+                let read = reader.take(length).read_to_end(buffer)?;
+                if read as u64 != length {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+            }
+            Head::Item(4, Some(length)) => read_items(reader, buffer, length)?,
+            Head::Item(5, Some(length)) => read_items(
+                reader,
+                buffer,
+                length
+                    .checked_mul(2)
+                    .ok_or_else(|| invalid_data("Map too large"))?,
+            )?,
+            Head::Item(6, Some(_)) => read_items(reader, buffer, 1)?,
+            Head::Item(_, None) => while read_item(reader, buffer, depth + 1)? {},
+            Head::Item(_, Some(_)) => {}
+        }
+
+        return Ok(true);
+    }
+
+    fn invalid_data(reason: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, reason)
+    }
+
+    /// Malformed input is a parsing failure, other I/O errors are stream failures
+    fn read_failure(e: io::Error) -> Error {
+        return match e.kind() {
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+                Error::DeserializationFailure(Box::new(e))
+            }
+            _ => Error::IOStreamFailure { reason: e },
+        };
     }
 }
 
@@ -49,7 +284,8 @@ pub use json::*;
 #[cfg(feature = "json")]
 mod json {
     use super::*;
-    use crate::Error;
+    use crate::error::{DeserializeError, SerializeError};
+    use std::io::BufReader;
 
     #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
     pub struct JsonSerde;
@@ -58,11 +294,46 @@ mod json {
         const FORMAT: &str = "json";
 
         fn try_serialize_as_bytes<S: Serialize>(data: S) -> Result<Vec<u8>> {
-            serde_json::to_vec(&data).map_err(|e| Error::SerializationFailure(Box::new(e)))
+            return Ok(serde_json::to_vec(&data).map_err(SerializeError)?);
         }
 
         fn try_deserialize_from_bytes<'de, O: Deserialize<'de>>(bytes: &'de [u8]) -> Result<O> {
-            serde_json::from_slice(bytes).map_err(|e| Error::DeserializationFailure(Box::new(e)))
+            let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+
+            let data =
+                serde_path_to_error::deserialize(&mut deserializer).map_err(DeserializeError)?;
+            deserializer.end().map_err(DeserializeError)?;
+
+            return Ok(data);
+        }
+
+        fn try_deserialize_from_bytes_reporting_ignored<'de, O: Deserialize<'de>>(
+            bytes: &'de [u8],
+            on_ignored: &mut dyn FnMut(String),
+        ) -> Result<O> {
+            let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+
+            let mut callback = |path: serde_ignored::Path<'_>| on_ignored(path.to_string());
+            let data = serde_path_to_error::deserialize(serde_ignored::Deserializer::new(
+                &mut deserializer,
+                &mut callback,
+            ))
+            .map_err(DeserializeError)?;
+            deserializer.end().map_err(DeserializeError)?;
+
+            return Ok(data);
+        }
+
+        fn try_deserialize_seq_from_reader<R: Read, O: for<'a> Deserialize<'a>>(
+            reader: &mut R,
+            on_item: &mut dyn FnMut(O) -> Result<()>,
+        ) -> Result<()> {
+            let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+
+            deserialize_seq_with(&mut deserializer, on_item)?;
+            deserializer.end().map_err(DeserializeError)?;
+
+            return Ok(());
         }
     }
 }
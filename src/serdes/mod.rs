@@ -1,2 +1,4 @@
 mod bytes;
 pub use bytes::*;
+mod sniff;
+pub use sniff::*;
@@ -0,0 +1,83 @@
+/// The format of stored bytes, as identified by [`sniff_format`]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum FormatKind {
+    /// JSON, as written by [`JsonSerde`](crate::serdes::JsonSerde)
+    Json,
+    /// CBOR, as written by [`CborSerde`](crate::serdes::CborSerde)
+    Cbor,
+    /// Gzip compressed data, of any format
+    Gzip,
+    /// Zstandard compressed data, of any format
+    Zstd,
+}
+
+impl FormatKind {
+    /// Returns the name of the format, matching [`BytesSerde::FORMAT`](crate::serdes::BytesSerde::FORMAT) for the serde formats
+    pub const fn name(&self) -> &'static str {
+        return match self {
+            Self::Json => "json",
+            Self::Cbor => "cbor",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        };
+    }
+}
+
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+
+/// Identifies the format of stored bytes (e.g. from [`DatabaseIO::try_read_storage_bytes`](crate::database::DatabaseIO::try_read_storage_bytes)),
+/// returning [`None`] if it's empty or unrecognized
+///
+/// This is a best guess from the leading bytes, the bytes aren't parsed. Compression is recognized by its magic bytes,
+/// JSON by its first non-whitespace character (the whole input must be UTF-8), and CBOR by a well-formed initial byte.
+///
+/// # Example
+/// ```rust
+/// # use lupabase::prelude::*;
+/// assert_eq!(sniff_format(b"[{\"id\":1}]"), Some(FormatKind::Json));
+/// assert_eq!(
+///     sniff_format(&[0x81, 0xA1, 0x62, 0x69, 0x64, 0x01]),
+///     Some(FormatKind::Cbor)
+/// );
+/// assert_eq!(sniff_format(&[0x1F, 0x8B, 0x08]), Some(FormatKind::Gzip));
+/// assert_eq!(sniff_format(b""), None);
+/// ```
+pub fn sniff_format(bytes: &[u8]) -> Option<FormatKind> {
+    if bytes.starts_with(GZIP_MAGIC) {
+        return Some(FormatKind::Gzip);
+    }
+    if bytes.starts_with(ZSTD_MAGIC) {
+        return Some(FormatKind::Zstd);
+    }
+
+    if is_json(bytes) {
+        return Some(FormatKind::Json);
+    }
+
+    let initial_byte = *bytes.first()?;
+    let (major_type, additional_info) = (initial_byte >> 5, initial_byte & 0x1F);
+    // Additional info 28 to 30 is reserved, and 31 (indefinite length) is only valid for strings, arrays and maps
+    // (or as the "break" stop code, which can't start an item)
+    let is_cbor = match additional_info {
+        0..=27 => true,
+        31 => (2..=5).contains(&major_type),
+        _ => false,
+    };
+
+    return is_cbor.then_some(FormatKind::Cbor);
+}
+
+fn is_json(bytes: &[u8]) -> bool {
+    let trimmed = bytes.trim_ascii_start();
+
+    let starts_json_value = match trimmed.first() {
+        Some(b'[' | b'{' | b'"' | b'-' | b'0'..=b'9') => true,
+        Some(b't') => trimmed.starts_with(b"true"),
+        Some(b'f') => trimmed.starts_with(b"false"),
+        Some(b'n') => trimmed.starts_with(b"null"),
+        _ => false,
+    };
+
+    return starts_json_value && str::from_utf8(bytes).is_ok();
+}
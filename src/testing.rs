@@ -0,0 +1,152 @@
+//! A reusable test battery for downstream [`DatabaseRecordPartitioned`] implementations
+//!
+//! Runs the same operations lupabase tests itself with, against any record type and engine:
+//! ```ignore
+//! #[test]
+//! fn my_record_basics() -> lupabase::Result<()> {
+//!     lupabase::testing::run_basics::<MyRecord, DiskDB<CborSerde>>()
+//! }
+//! ```
+//!
+//! Failed expectations panic, as with any other assertion.
+
+use crate::{Result, prelude::*, transaction::*};
+use std::fmt::Debug;
+use tempfile::TempDir;
+use tracing::info;
+
+/// A [`DatabaseRecord`] which can be generated from a seed, e.g. for benchmarks with [`gen_records`]
+pub trait TestGen: DatabaseRecord {
+    /// Generates a new record, different seeds must produce different unique values
+    fn generate(seed: u64) -> Self;
+}
+
+/// A [`DatabaseRecordPartitioned`] which can be generated and modified by the test battery
+pub trait TestableRecord: TestGen + DatabaseRecordPartitioned + Clone + Debug + PartialEq {
+    /// Returns a copy of the record with the same unique value but different data
+    fn modified(&self) -> Self;
+}
+
+/// Generates `n` records from the sequential seeds `1..=n`, so every record has a different unique value
+pub fn gen_records<T: TestGen>(n: u64) -> Vec<T> { return (1..=n).map(T::generate).collect(); }
+
+fn create_temp_dir<DB: Database>(tester: &str) -> TempDir {
+    let temp_dir = TempDir::with_prefix(format!("{tester}-{}-{}-", DB::SERDE_FORMAT, DB::NAME))
+        .expect("Temporary directory creation failed");
+
+    info!("Created temporary TempDir: [{}]", temp_dir.path().display());
+
+    return temp_dir;
+}
+
+/// Runs the basic operations (insert, update, upsert, replace) against a fresh database
+///
+/// # Errors
+/// - Any error returned by the database
+///
+/// # Panics
+/// - The database doesn't hold the expected records after an operation
+pub fn run_basics<R: TestableRecord, DB: Database>() -> Result<()> {
+    let temp_dir = create_temp_dir::<DB>("basics");
+    let db = DB::new(temp_dir.path());
+
+    let mut seed = 0;
+    let mut generate = || {
+        seed += 1;
+        return R::generate(seed);
+    };
+
+    db.try_initialize_storage::<R, Vec<R>>(vec![])?;
+    assert!(
+        db.get_all::<R>()?.is_empty(),
+        "Initialized storage should be empty"
+    );
+
+    let mut expected = vec![generate()];
+    db.insert(expected[0].clone())?;
+    assert_eq!(db.get_all::<R>()?, expected, "Inserted");
+    assert!(
+        db.insert(expected[0].clone()).is_err(),
+        "Inserting a duplicate should fail"
+    );
+
+    let records = [generate(), generate()];
+    db.insert_all(records.clone())?;
+    expected.extend(records);
+    assert_eq!(db.get_all::<R>()?, expected, "Inserted all");
+
+    expected[0] = expected[0].modified();
+    db.update(expected[0].clone())?;
+    assert_eq!(db.get_all::<R>()?, expected, "Updated");
+    assert!(
+        db.update(generate()).is_err(),
+        "Updating a missing record should fail"
+    );
+
+    expected[1] = expected[1].modified();
+    expected[2] = expected[2].modified();
+    // Updating out of order should be fine!
+    db.update_all([expected[2].clone(), expected[1].clone()])?;
+    assert_eq!(db.get_all::<R>()?, expected, "Updated all");
+
+    expected[0] = expected[0].modified();
+    expected.push(generate());
+    db.upsert_all([expected[0].clone(), expected[3].clone()])?;
+    assert_eq!(db.get_all::<R>()?, expected, "Upserted all");
+
+    db.replace_all::<R>([])?;
+    assert!(db.get_all::<R>()?.is_empty(), "Replaced all empty");
+    db.replace_all(expected.clone())?;
+    assert_eq!(db.get_all::<R>()?, expected, "Replaced all restored");
+
+    return Ok(());
+}
+
+/// Runs a transaction against a fresh database, committing and then rolling it back
+///
+/// # Errors
+/// - Any error returned by the database or the transaction
+///
+/// # Panics
+/// - The database or the transaction doesn't hold the expected records after an operation
+pub fn run_transactions<R: TestableRecord, DB: DatabaseTransaction>() -> Result<()> {
+    let temp_dir = create_temp_dir::<DB>("transactions");
+    let db = DB::new(temp_dir.path());
+
+    let mut seed = 0;
+    let mut generate = || {
+        seed += 1;
+        return R::generate(seed);
+    };
+
+    let before = vec![generate()];
+    db.try_initialize_storage::<R, Vec<R>>(before.clone())?;
+
+    let tx = db.transact()?;
+    tx.try_initialize_storage::<R, Vec<R>>(db.get_all::<R>()?)?;
+
+    let mut after = before.clone();
+    after[0] = after[0].modified();
+    after.push(generate());
+    tx.update(after[0].clone())?;
+    tx.insert(after[1].clone())?;
+    assert_eq!(tx.get_all::<R>()?, after, "Transaction operated");
+    assert_eq!(
+        db.get_all::<R>()?,
+        before,
+        "Database should be untouched before commit"
+    );
+    assert_eq!(
+        tx.get_all_before::<R>()?,
+        before,
+        "Transaction should keep the records before"
+    );
+
+    db.try_commit::<R>(&tx)?;
+    assert_eq!(db.get_all::<R>()?, after, "Committed");
+
+    db.try_rollback::<R>(&tx)?;
+    assert_eq!(db.get_all::<R>()?, before, "Rolled back");
+
+    return Ok(());
+}
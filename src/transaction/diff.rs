@@ -0,0 +1,99 @@
+use crate::record::{utils::*, *};
+use std::fmt::Debug;
+
+/// The changes made to a partition's records in a transaction, see [`DatabaseTransactionOps::diff`](crate::transaction::DatabaseTransactionOps::diff)
+///
+/// Records are matched by unique value, and then compared by content to find the changed ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionDiff<T: DatabaseRecord> {
+    /// Records whose unique value is missing from the records before, in their order after
+    pub added: Vec<T>,
+    /// Records whose unique value is missing from the records after, in their order before
+    pub removed: Vec<T>,
+    /// Records whose content changed, as the record before and after, in their order after
+    pub changed: Vec<(T, T)>,
+}
+
+impl<T: DatabaseRecord> PartitionDiff<T> {
+    /// Computes the changes turning the records before into the records after
+    pub fn between(before: Vec<T>, after: Vec<T>) -> Self
+    where
+        T: PartialEq, {
+        let before_uniques = before.as_uniques();
+        let mut before = before.to_map();
+
+        let mut diff = Self {
+            added: vec![],
+            removed: vec![],
+            changed: vec![],
+        };
+
+        for record_after in after {
+            match before.remove(&record_after.unique_value()) {
+                None => diff.added.push(record_after),
+                Some(record_before) if record_before != record_after => {
+                    diff.changed.push((record_before, record_after))
+                }
+                Some(_) => {}
+            }
+        }
+
+        diff.removed = before_uniques
+            .into_iter()
+            .filter_map(|unique| before.remove(&unique))
+            .collect();
+
+        return diff;
+    }
+
+    /// Returns whether there are no changes
+    pub fn is_empty(&self) -> bool {
+        return self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty();
+    }
+}
+
+/// Renders the changes as human-readable lines, e.g. for logging or a CLI
+///
+/// Each record is rendered with its [`Debug`] representation, on a line starting with a marker:
+/// `+` for added, `-` for removed, and `~` for changed records, shown as `before -> after`.
+/// An empty diff renders as an empty string.
+///
+/// # Example
+/// ```rust
+/// # use lupabase::prelude::{transactions::*, *};
+/// # use serde::{Deserialize, Serialize};
+/// #
+/// # #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// # struct Record { id: u8, data: char }
+/// #
+/// # impl DatabaseRecord for Record {
+/// #    type Unique = u8;
+/// #
+/// #    fn unique_value(&self) -> Self::Unique { self.id }
+/// # }
+/// let before = vec![Record { id: 1, data: 'a' }, Record { id: 2, data: 'b' }];
+/// let after = vec![Record { id: 2, data: 'c' }, Record { id: 3, data: 'd' }];
+///
+/// assert_eq!(
+///     render_diff(&PartitionDiff::between(before, after)),
+///     "\
+/// + Record { id: 3, data: 'd' }
+/// - Record { id: 1, data: 'a' }
+/// ~ Record { id: 2, data: 'b' } -> Record { id: 2, data: 'c' }"
+/// );
+/// ```
+pub fn render_diff<T: DatabaseRecord + Debug>(diff: &PartitionDiff<T>) -> String {
+    let mut lines = vec![];
+
+    for record in &diff.added {
+        lines.push(format!("+ {record:?}"));
+    }
+    for record in &diff.removed {
+        lines.push(format!("- {record:?}"));
+    }
+    for (record_before, record_after) in &diff.changed {
+        lines.push(format!("~ {record_before:?} -> {record_after:?}"));
+    }
+
+    return lines.join("\n");
+}
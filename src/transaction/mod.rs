@@ -2,9 +2,20 @@ mod operation;
 pub use operation::*;
 mod io;
 pub use io::*;
+mod readonly;
+pub use readonly::*;
+mod diff;
+pub use diff::*;
 
-use crate::{Deserialize, Error, Result, Serialize, database::*, record::*};
-use std::path::Path;
+use crate::{
+    Deserialize, Error, Result, Serialize,
+    database::*,
+    record::{utils::DatabaseRecordsUtils, *},
+    utils,
+};
+use serde_core::ser::{self, Impossible, SerializeSeq, Serializer};
+use std::{collections::HashSet, fmt, path::Path};
+use tracing::info;
 
 // TODO: Lock file when transaction started
 /// Provides atomic transactional support for databases.
@@ -16,9 +27,27 @@ pub trait DatabaseTransaction: Database {
 
     /// Begins a new transaction (usually is Infallible)
     ///
+    /// The transaction shares the database's [`DatabaseIO::dir`], so [`DatabaseIO::file_path`] resolves to the
+    /// same path in both, and the partition-based operations (e.g. [`DatabaseOps::insert`]) can be used on the transaction.
+    ///
+    /// # Errors
+    /// - I/O
+    fn transact(&self) -> Result<Self::TransactionDB> {
+        return Ok(Self::TransactionDB::new(self.dir()));
+    }
+
+    /// Begins a new read-only transaction, giving repeatable reads per storage read through it
+    ///
+    /// Each storage is snapshotted on its first read (like [`DatabaseTransactionIO::try_read_storage_before`]),
+    /// so reading a storage again sees the same data even if it was written to in the meantime.
+    /// Storages are snapshotted independently, so this isn't a point-in-time view across storages:
+    /// a storage first read after a write sees that write, even if another storage was snapshotted before it.
+    ///
     /// # Errors
     /// - I/O
-    fn transact(&self) -> Result<Self::TransactionDB> { return Ok(Self::TransactionDB::new("")); }
+    fn transact_readonly(&self) -> Result<ReadonlyTransaction<'_, Self>> {
+        return Ok(ReadonlyTransaction::new(self));
+    }
 
     /// Commits the current transaction in the given path
     ///
@@ -30,20 +59,30 @@ pub trait DatabaseTransaction: Database {
         database_path: impl AsRef<Path>,
     ) -> Result<()> {
         let records = transaction.try_read_storage::<O>(&transaction_path)?;
+        let count = record_count(&records);
         match self.try_write_storage(records, &database_path) {
-            Ok(()) => Ok(()),
-            Err(e) => {
+            Ok(()) => {
+                info!(
+                    records = count,
+                    "Committed transaction into [{}].",
+                    database_path.as_ref().display()
+                );
+
+                Ok(())
+            }
+            Err(commit_error) => {
                 let database_path = database_path.as_ref().to_path_buf();
 
                 self.try_rollback_with_path::<O>(transaction, transaction_path, &database_path)
-                    .map_err(|e| Error::DBTransactionRollbackFailure {
+                    .map_err(|rollback_error| Error::DBTransactionRollbackFailure {
                         file_path: database_path.clone(),
-                        reason: e.to_string(),
+                        reason: rollback_error.to_string(),
+                        commit_reason: commit_error.to_string(),
                     })?;
 
                 return Err(Error::DBTransactionCommitFailure {
                     file_path: database_path,
-                    reason: e.to_string(),
+                    reason: commit_error.to_string(),
                 });
             }
         }
@@ -51,6 +90,10 @@ pub trait DatabaseTransaction: Database {
 
     /// Commits the current transaction
     ///
+    /// Successful commits are logged at info level, with the number of committed records.
+    /// If the commit fails, the transaction is rolled back. Should the rollback fail as well,
+    /// [`Error::DBTransactionRollbackFailure`] is returned carrying both the commit and rollback causes.
+    ///
     /// # Errors
     /// - I/O
     fn try_commit<T: DatabaseRecordPartitioned>(
@@ -59,8 +102,126 @@ pub trait DatabaseTransaction: Database {
     ) -> Result<()> {
         return self.try_commit_with_path::<Vec<T>>(
             transaction,
-            transaction.file_path(T::PARTITION),
-            self.file_path(T::PARTITION),
+            transaction.partition_path::<T>()?,
+            self.partition_path::<T>()?,
+        );
+    }
+
+    /// Commits the current transaction in the given path, only if the check accepts the transaction's records
+    ///
+    /// See [`DatabaseTransaction::try_commit_with_check`] for details and the list of possible errors.
+    fn try_commit_with_check_with_path<T: DatabaseRecord, C: FnOnce(&[T]) -> Result<()>>(
+        &self,
+        transaction: &Self::TransactionDB,
+        check: C,
+        transaction_path: impl AsRef<Path>,
+        database_path: impl AsRef<Path>,
+    ) -> Result<()> {
+        check(&transaction.get_all_with_path::<T>(&transaction_path)?)?;
+
+        return self.try_commit_with_path::<Vec<T>>(transaction, transaction_path, database_path);
+    }
+
+    /// Commits the current transaction, only if the check accepts the transaction's records
+    ///
+    /// The check runs over every record the commit would write (e.g. to enforce an application-level invariant).
+    /// If it fails, its error is returned as-is and nothing is written.
+    ///
+    /// See [`DatabaseTransaction::try_commit`] for details and the list of possible errors.
+    fn try_commit_with_check<T: DatabaseRecordPartitioned, C: FnOnce(&[T]) -> Result<()>>(
+        &self,
+        transaction: &Self::TransactionDB,
+        check: C,
+    ) -> Result<()> {
+        return self.try_commit_with_check_with_path(
+            transaction,
+            check,
+            transaction.partition_path::<T>()?,
+            self.partition_path::<T>()?,
+        );
+    }
+
+    /// Commits the current transaction for every partition of the [`DatabaseRecordOperatablePartitioned`] tuple
+    ///
+    /// The tuple's partitions are manipulated on the transaction with [`DatabaseOpsOperatable`] (e.g.
+    /// [`DatabaseOpsOperatable::insert_all_with_operatable`]), then committed together.
+    /// Partitions are committed in order, and if any commit fails, every partition committed before it is rolled back.
+    ///
+    /// See [`DatabaseTransaction::try_commit`] for details and the list of possible errors.
+    fn try_commit_operatable<R: DatabaseRecordOperatablePartitioned>(
+        &self,
+        transaction: &Self::TransactionDB,
+    ) -> Result<()> {
+        return R::try_commit(self, transaction);
+    }
+
+    /// Merges the changes of the current transaction in the given path into the database's current records
+    ///
+    /// See [`DatabaseTransaction::merge_commit`] for details and the list of possible errors.
+    fn merge_commit_with_path<T: DatabaseRecord + PartialEq>(
+        &self,
+        transaction: &Self::TransactionDB,
+        transaction_path: impl AsRef<Path>,
+        database_path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let records_before = transaction.get_all_before_with_path::<T>(&transaction_path)?;
+        let records_after = transaction.get_all_with_path::<T>(&transaction_path)?;
+
+        let diff = records_before.diff_against(&records_after);
+        let changed_uniques = diff
+            .to_insert
+            .iter()
+            .chain(&diff.to_update)
+            .map(|record| record.unique_value())
+            .collect::<HashSet<_>>();
+        let deleted_uniques = diff.to_delete.into_iter().collect::<HashSet<_>>();
+
+        let changed_records = records_after
+            .into_iter()
+            .filter(|record| changed_uniques.contains(&record.unique_value()))
+            .collect::<Vec<_>>();
+        let (changed, deleted) = (changed_records.len(), deleted_uniques.len());
+
+        self.read_modify_write_records::<T, _>(&database_path, |records| {
+            records.retain(|record| !deleted_uniques.contains(&record.unique_value()));
+            utils::upsert_records(records, changed_records);
+
+            return Ok(());
+        })
+        .map_err(|commit_error| Error::DBTransactionCommitFailure {
+            file_path: database_path.as_ref().to_path_buf(),
+            reason: commit_error.to_string(),
+        })?;
+
+        info!(
+            changed,
+            deleted,
+            "Merge-committed transaction into [{}].",
+            database_path.as_ref().display()
+        );
+
+        return Ok(());
+    }
+
+    /// Merges the changes of the current transaction into the database's current records
+    ///
+    /// Unlike [`DatabaseTransaction::try_commit`], which overwrites the partition with the transaction's records,
+    /// only the records added, changed or removed relative to [`DatabaseTransactionOps::get_all_before`] are applied.
+    /// Concurrent transactions changing disjoint records therefore keep each other's changes,
+    /// while for records changed by both, the last merge-commit wins.
+    /// Successful merge-commits are logged at info level, with the number of changed and deleted records.
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    fn merge_commit<T: DatabaseRecordPartitioned + PartialEq>(
+        &self,
+        transaction: &Self::TransactionDB,
+    ) -> Result<()> {
+        return self.merge_commit_with_path::<T>(
+            transaction,
+            transaction.partition_path::<T>()?,
+            self.partition_path::<T>()?,
         );
     }
 
@@ -74,12 +235,22 @@ pub trait DatabaseTransaction: Database {
         database_path: impl AsRef<Path>,
     ) -> Result<()> {
         let records_before = transaction.try_read_storage_before::<O>(&transaction_path)?;
+        let count = record_count(&records_before);
+        self.try_write_storage(records_before, &database_path)?;
+
+        info!(
+            records = count,
+            "Rolled back transaction in [{}].",
+            database_path.as_ref().display()
+        );
 
-        return self.try_write_storage(records_before, database_path);
+        return Ok(());
     }
 
     /// Rolls back the current transaction
     ///
+    /// Successful rollbacks are logged at info level, with the number of reverted records.
+    ///
     /// # Errors
     /// - I/O
     fn try_rollback<T: DatabaseRecordPartitioned>(
@@ -88,8 +259,175 @@ pub trait DatabaseTransaction: Database {
     ) -> Result<()> {
         self.try_rollback_with_path::<Vec<T>>(
             transaction,
-            transaction.file_path(T::PARTITION),
-            self.file_path(T::PARTITION),
+            transaction.partition_path::<T>()?,
+            self.partition_path::<T>()?,
         )
     }
+
+    /// Rolls back the current transaction for every partition of the [`DatabaseRecordOperatablePartitioned`] tuple
+    ///
+    /// See [`DatabaseTransaction::try_rollback`] for details and the list of possible errors.
+    fn try_rollback_operatable<R: DatabaseRecordOperatablePartitioned>(
+        &self,
+        transaction: &Self::TransactionDB,
+    ) -> Result<()> {
+        return R::try_rollback(self, transaction);
+    }
+}
+
+/// Returns the number of records in a storage serialized as a list, [`None`] for any other storage (e.g. a single value)
+///
+/// Only the list's length is taken, its records aren't serialized.
+fn record_count(records: &impl Serialize) -> Option<usize> {
+    return records.serialize(RecordCounter).ok();
+}
+
+/// Serializes a list as its length, failing for anything else, see [`record_count`]
+struct RecordCounter;
+
+#[derive(Debug)]
+struct NotAList;
+
+impl fmt::Display for NotAList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { return f.write_str("Not a list"); }
+}
+
+impl std::error::Error for NotAList {}
+
+impl ser::Error for NotAList {
+    fn custom<T: fmt::Display>(_msg: T) -> Self { return Self; }
+}
+
+macro_rules! not_a_list {
+    ($($method:ident($($ty:ty),*)),* $(,)?) => {
+        $(fn $method(self, $(_: $ty),*) -> std::result::Result<usize, NotAList> { return Err(NotAList); })*
+    };
+}
+
+impl Serializer for RecordCounter {
+    type Ok = usize;
+    type Error = NotAList;
+    type SerializeSeq = RecordCount;
+    type SerializeTuple = Impossible<usize, NotAList>;
+    type SerializeTupleStruct = Impossible<usize, NotAList>;
+    type SerializeTupleVariant = Impossible<usize, NotAList>;
+    type SerializeMap = Impossible<usize, NotAList>;
+    type SerializeStruct = Impossible<usize, NotAList>;
+    type SerializeStructVariant = Impossible<usize, NotAList>;
+
+    not_a_list!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    );
+
+    fn serialize_some<T: ?Sized + Serialize>(
+        self,
+        _value: &T,
+    ) -> std::result::Result<usize, NotAList> {
+        return Err(NotAList);
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> std::result::Result<usize, NotAList> {
+        return value.serialize(self);
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> std::result::Result<usize, NotAList> {
+        return Err(NotAList);
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> std::result::Result<RecordCount, NotAList> {
+        return Ok(RecordCount(0));
+    }
+
+    fn serialize_tuple(self, _len: usize) -> std::result::Result<Self::SerializeTuple, NotAList> {
+        return Err(NotAList);
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> std::result::Result<Self::SerializeTupleStruct, NotAList> {
+        return Err(NotAList);
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> std::result::Result<Self::SerializeTupleVariant, NotAList> {
+        return Err(NotAList);
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> std::result::Result<Self::SerializeMap, NotAList> {
+        return Err(NotAList);
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> std::result::Result<Self::SerializeStruct, NotAList> {
+        return Err(NotAList);
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> std::result::Result<Self::SerializeStructVariant, NotAList> {
+        return Err(NotAList);
+    }
+}
+
+/// Counts the elements of a list without serializing them
+struct RecordCount(usize);
+
+impl SerializeSeq for RecordCount {
+    type Ok = usize;
+    type Error = NotAList;
+
+    fn serialize_element<T: ?Sized + Serialize>(
+        &mut self,
+        _value: &T,
+    ) -> std::result::Result<(), NotAList> {
+        self.0 += 1;
+
+        return Ok(());
+    }
+
+    fn end(self) -> std::result::Result<usize, NotAList> { return Ok(self.0); }
 }
@@ -17,6 +17,27 @@ pub trait DatabaseTransactionOps: Database + DatabaseTransactionIO {
     ///
     /// See [`DatabaseTransactionOps::get_all_before_with_path`] for details and the list of possible errors.
     fn get_all_before<T: DatabaseRecordPartitioned>(&self) -> Result<Vec<T>> {
-        self.get_all_before_with_path::<T>(self.file_path(T::PARTITION))
+        self.get_all_before_with_path::<T>(self.partition_path::<T>()?)
+    }
+
+    /// Computes the changes made in the transaction to the [`DatabaseRecord`] in the given path
+    ///
+    /// See [`DatabaseTransactionOps::diff`] for details and the list of possible errors.
+    fn diff_with_path<T: DatabaseRecord + PartialEq>(
+        &self,
+        transaction_path: impl AsRef<Path>,
+    ) -> Result<PartitionDiff<T>> {
+        return Ok(PartitionDiff::between(
+            self.get_all_before_with_path(&transaction_path)?,
+            self.get_all_with_path(&transaction_path)?,
+        ));
+    }
+
+    /// Computes the changes made in the transaction to the [`DatabaseRecordPartitioned`],
+    /// relative to the stored snapshot, e.g. to review them with [`render_diff`] before committing
+    ///
+    /// See [`DatabaseTransactionIO::try_read_storage_before`] for details and the list of possible errors.
+    fn diff<T: DatabaseRecordPartitioned + PartialEq>(&self) -> Result<PartitionDiff<T>> {
+        return self.diff_with_path(self.partition_path::<T>()?);
     }
 }
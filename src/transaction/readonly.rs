@@ -0,0 +1,67 @@
+use crate::{Deserialize, Result, Serialize, database::*, record::*, transaction::*};
+use std::path::Path;
+
+/// A read-only transaction, see [`DatabaseTransaction::transact_readonly`]
+///
+/// Every storage is snapshotted from the database on its first read, and all later reads of it
+/// are served from the snapshot, regardless of concurrent writes to the database.
+/// Storages aren't snapshotted together, so reads are repeatable per storage rather than across storages.
+/// Writes aren't possible, so there is nothing to commit or roll back.
+pub struct ReadonlyTransaction<'a, DB: DatabaseTransaction + ?Sized> {
+    database: &'a DB,
+    snapshot: DB::TransactionDB,
+}
+
+impl<'a, DB: DatabaseTransaction + ?Sized> ReadonlyTransaction<'a, DB> {
+    pub(crate) fn new(database: &'a DB) -> Self {
+        return Self {
+            database,
+            snapshot: DB::TransactionDB::new(database.dir()),
+        };
+    }
+
+    /// Attempts to read data from the snapshot of the storage, snapshotting it first if it wasn't read before
+    ///
+    /// # Errors
+    /// - I/O
+    /// - Parsing failure
+    pub fn try_read_storage<O: Serialize + for<'de> Deserialize<'de>>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<O> {
+        if self.snapshot.storage_exists(&path)? {
+            return self.snapshot.try_read_storage(path);
+        }
+
+        let data = self.database.try_read_storage::<O>(&path)?;
+        self.snapshot.try_write_storage(&data, path)?;
+
+        return Ok(data);
+    }
+
+    /// Reads all [`DatabaseRecord`] from the snapshot of the given path
+    ///
+    /// See [`ReadonlyTransaction::try_read_storage`] for details and the list of possible errors.
+    pub fn get_all_with_path<T: DatabaseRecord>(&self, path: impl AsRef<Path>) -> Result<Vec<T>> {
+        return self.try_read_storage::<Vec<T>>(path);
+    }
+
+    /// Reads all [`DatabaseRecordPartitioned`] from the snapshot of their partition
+    ///
+    /// See [`ReadonlyTransaction::try_read_storage`] for details and the list of possible errors.
+    pub fn get_all<T: DatabaseRecordPartitioned>(&self) -> Result<Vec<T>> {
+        return self.get_all_with_path(self.database.partition_path::<T>()?);
+    }
+
+    /// Ends the transaction, a no-op as nothing can be written
+    ///
+    /// # Errors
+    /// - Infallible, kept for parity with [`DatabaseTransaction::try_commit`]
+    pub fn try_commit(self) -> Result<()> { return Ok(()); }
+
+    /// Ends the transaction, a no-op as nothing can be written
+    ///
+    /// # Errors
+    /// - Infallible, kept for parity with [`DatabaseTransaction::try_rollback`]
+    pub fn try_rollback(self) -> Result<()> { return Ok(()); }
+}
@@ -1,26 +1,239 @@
 use crate::{
     Deserialize, Error, Result, Serialize,
-    database::Database,
+    database::{Database, DatabaseIO, DuplicatePolicy, IMPORT_BATCH_SIZE, RepairReport},
     record::{utils::DatabaseRecordsUtils, *},
+    serdes::BytesSerde,
 };
+use itertools::Itertools;
+use serde_core::de::IgnoredAny;
 use std::{
     borrow::Borrow,
-    fs::{self, create_dir_all},
-    path::Path,
+    collections::HashSet,
+    ffi::OsStr,
+    fmt::{self, Debug},
+    fs::{self, File, OpenOptions, create_dir_all},
+    hash::{Hash, Hasher},
+    io::{self, Read, Write},
+    ops::Deref,
+    path::{Path, PathBuf},
 };
 use tracing::{debug, info, warn};
 
+const MAX_REPORTED_UNIQUES: usize = 10;
+
+/// A unique value borrowed from its record through [`DatabaseRecord::unique_ref`], or computed when it can't be
+pub enum UniqueRef<'a, U> {
+    Borrowed(&'a U),
+    Owned(U),
+}
+
+impl<'a, U> UniqueRef<'a, U> {
+    pub fn of<R: DatabaseRecord<Unique = U>>(record: &'a R) -> Self {
+        return match record.unique_ref() {
+            Some(unique) => Self::Borrowed(unique),
+            None => Self::Owned(record.unique_value()),
+        };
+    }
+}
+
+impl<U> Deref for UniqueRef<'_, U> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        return match self {
+            Self::Borrowed(unique) => unique,
+            Self::Owned(unique) => unique,
+        };
+    }
+}
+
+impl<U: PartialEq> PartialEq for UniqueRef<'_, U> {
+    fn eq(&self, other: &Self) -> bool { return **self == **other; }
+}
+
+impl<U: Eq> Eq for UniqueRef<'_, U> {}
+
+impl<U: Hash> Hash for UniqueRef<'_, U> {
+    fn hash<H: Hasher>(&self, state: &mut H) { (**self).hash(state); }
+}
+
+impl<U: Debug> Debug for UniqueRef<'_, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { return (**self).fmt(f); }
+}
+
+pub fn format_uniques<U: Debug>(uniques: &[U], verbose: bool) -> String {
+    if verbose {
+        return format!("{uniques:#?}");
+    }
+
+    if uniques.len() <= MAX_REPORTED_UNIQUES {
+        return format!("{uniques:?}");
+    }
+
+    return format!(
+        "{:?} and {} more",
+        &uniques[..MAX_REPORTED_UNIQUES],
+        uniques.len() - MAX_REPORTED_UNIQUES
+    );
+}
+
 pub fn check_is_all_new_records<R: DatabaseRecord>(
     current_records: &[R],
     new_records: &[R],
     path: impl AsRef<Path>,
+    verbose: bool,
+) -> Result<()> {
+    let duplicates = current_records
+        .iter()
+        .chain(new_records)
+        .map(UniqueRef::of)
+        .duplicates()
+        .collect::<Vec<_>>();
+    if !duplicates.is_empty() {
+        return Err(Error::DBOperationFailure {
+            path: path.as_ref().display().to_string(),
+            reason: format!(
+                "Found duplicate Unique value(s) in record(s) when inserting: [{}].",
+                format_uniques(&duplicates, verbose)
+            ),
+        });
+    };
+
+    Ok(())
+}
+
+pub fn check_is_all_unique_records<R: DatabaseRecord>(
+    records: &[R],
+    path: impl AsRef<Path>,
+    verbose: bool,
+) -> Result<()> {
+    let duplicates = records
+        .iter()
+        .map(UniqueRef::of)
+        .duplicates()
+        .collect::<Vec<_>>();
+    if !duplicates.is_empty() {
+        return Err(Error::DBCorrupt {
+            file_path: path.as_ref().to_path_buf(),
+            reason: format!(
+                "Found duplicate Unique value(s) in stored record(s): [{}].",
+                format_uniques(&duplicates, verbose)
+            ),
+        });
+    };
+
+    Ok(())
+}
+
+pub fn check_is_all_unique_new_records<R: DatabaseRecord>(
+    records: &[R],
+    path: impl AsRef<Path>,
+    verbose: bool,
+) -> Result<()> {
+    let duplicates = records
+        .iter()
+        .map(UniqueRef::of)
+        .duplicates()
+        .collect::<Vec<_>>();
+    if !duplicates.is_empty() {
+        return Err(Error::DBOperationFailure {
+            path: path.as_ref().display().to_string(),
+            reason: format!(
+                "Found duplicate Unique value(s) in record(s) when writing: [{}].",
+                format_uniques(&duplicates, verbose)
+            ),
+        });
+    };
+
+    Ok(())
+}
+
+pub fn touch_record<R: DatabaseRecord>(
+    records: &mut [R],
+    unique: &R::Unique,
+    f: impl FnOnce(&mut R),
+    path: impl AsRef<Path>,
+    verbose: bool,
+) -> Result<()> {
+    let failure = |reason: &str| Error::DBOperationFailure {
+        path: path.as_ref().display().to_string(),
+        reason: format!(
+            "{reason} when touching: [{}].",
+            format_uniques(std::slice::from_ref(unique), verbose)
+        ),
+    };
+
+    let record = records
+        .find_by_unique_mut(unique)
+        .ok_or_else(|| failure("Found non-matching Unique value in record"))?;
+
+    f(record);
+
+    if !record.unique_eq(unique) {
+        return Err(failure("Found changed Unique value in record"));
+    }
+
+    Ok(())
+}
+
+pub fn upsert_record_with<R: DatabaseRecord>(
+    records: &mut Vec<R>,
+    unique: &R::Unique,
+    update: impl FnOnce(&mut R),
+    default: impl FnOnce() -> R,
+    path: impl AsRef<Path>,
+    verbose: bool,
 ) -> Result<()> {
-    let duplicates = &current_records.find_intersecting_uniques_from(new_records);
+    let failure = |reason: &str| Error::DBOperationFailure {
+        path: path.as_ref().display().to_string(),
+        reason: format!(
+            "{reason} when upserting: [{}].",
+            format_uniques(std::slice::from_ref(unique), verbose)
+        ),
+    };
+
+    let record = match records.iter().position(|r| r.unique_eq(unique)) {
+        Some(position) => &mut records[position],
+        None => {
+            let record = default();
+            if !record.unique_eq(unique) {
+                return Err(failure("Found non-matching Unique value in default record"));
+            }
+
+            records.push(record);
+            records
+                .last_mut()
+                .expect("Record should exist as it was just pushed.")
+        }
+    };
+
+    update(record);
+
+    if !record.unique_eq(unique) {
+        return Err(failure("Found changed Unique value in record"));
+    }
+
+    Ok(())
+}
+
+pub fn check_is_all_new_uniques<R: DatabaseRecord>(
+    current_records: &[R],
+    uniques: &[R::Unique],
+    path: impl AsRef<Path>,
+    verbose: bool,
+) -> Result<()> {
+    let duplicates = current_records
+        .iter()
+        .map(UniqueRef::of)
+        .chain(uniques.iter().map(UniqueRef::Borrowed))
+        .duplicates()
+        .collect::<Vec<_>>();
     if !duplicates.is_empty() {
         return Err(Error::DBOperationFailure {
             path: path.as_ref().display().to_string(),
             reason: format!(
-                "Found duplicate Unique value(s) in record(s) when inserting: [{duplicates:#?}].",
+                "Found duplicate Unique value(s) in record(s) when inserting: [{}].",
+                format_uniques(&duplicates, verbose)
             ),
         });
     };
@@ -32,13 +245,25 @@ pub fn check_is_all_existing_records<R: DatabaseRecord>(
     current_records: &[R],
     new_records: &[R],
     path: impl AsRef<Path>,
+    verbose: bool,
 ) -> Result<()> {
-    let non_matching = &current_records.find_non_intersecting_uniques_from(new_records);
+    let intersecting = current_records
+        .iter()
+        .chain(new_records)
+        .map(UniqueRef::of)
+        .duplicates()
+        .collect::<HashSet<_>>();
+    let non_matching = new_records
+        .iter()
+        .map(UniqueRef::of)
+        .filter(|u| !intersecting.contains(u))
+        .collect::<Vec<_>>();
     if !non_matching.is_empty() {
         return Err(Error::DBOperationFailure {
             path: path.as_ref().display().to_string(),
             reason: format!(
-                "Found non-matching Unique value(s) in record(s) when updating: [{non_matching:?}].",
+                "Found non-matching Unique value(s) in record(s) when updating: [{}].",
+                format_uniques(&non_matching, verbose)
             ),
         });
     };
@@ -46,6 +271,53 @@ pub fn check_is_all_existing_records<R: DatabaseRecord>(
     Ok(())
 }
 
+pub fn check_is_all_existing_uniques<R: DatabaseRecord>(
+    current_records: &[R],
+    uniques: &[R::Unique],
+    path: impl AsRef<Path>,
+    verbose: bool,
+) -> Result<()> {
+    let current_uniques = current_records
+        .iter()
+        .map(UniqueRef::of)
+        .collect::<HashSet<_>>();
+    let non_matching = uniques
+        .iter()
+        .filter(|u| !current_uniques.contains(&UniqueRef::Borrowed(*u)))
+        .collect::<Vec<_>>();
+    if !non_matching.is_empty() {
+        return Err(Error::DBOperationFailure {
+            path: path.as_ref().display().to_string(),
+            reason: format!(
+                "Found non-matching Unique value(s) in record(s) when deleting: [{}].",
+                format_uniques(&non_matching, verbose)
+            ),
+        });
+    };
+
+    Ok(())
+}
+
+/// Updates each record in place, or appends it if its unique value isn't stored yet, returning the number of upserted records
+pub fn upsert_records<R: DatabaseRecord>(
+    records: &mut Vec<R>,
+    upserted_records: impl IntoIterator<Item = R>,
+) -> usize {
+    let mut upserted_count = 0;
+
+    for upserted_record in upserted_records.into_iter() {
+        if let Some(record) = records.find_by_unique_mut(&upserted_record.unique_value()) {
+            *record = upserted_record;
+        } else {
+            records.push(upserted_record);
+        }
+
+        upserted_count += 1;
+    }
+
+    return upserted_count;
+}
+
 pub fn try_populate_storage<D: Database, O: Serialize + for<'a> Deserialize<'a>>(
     database: &D,
     default_data: impl Borrow<O>,
@@ -54,7 +326,9 @@ pub fn try_populate_storage<D: Database, O: Serialize + for<'a> Deserialize<'a>>
     debug!("Populating storage with: {}", std::any::type_name::<O>());
 
     match database.try_read_storage::<O>(&path) {
-        Ok(_) => {}
+        Ok(_) => {
+            info!("Found [{}].", path.as_ref().display());
+        }
         Err(Error::DBNotFound { file_path }) => {
             warn!(
                 "Couldn't find [{}]. Trying to populate {}.",
@@ -63,14 +337,25 @@ pub fn try_populate_storage<D: Database, O: Serialize + for<'a> Deserialize<'a>>
             );
 
             database.try_write_storage(default_data.borrow(), &path)?;
+
+            info!("Created [{}].", path.as_ref().display());
         }
         Err(e) => return Err(e),
     };
 
-    info!("Found [{}].", path.as_ref().display());
     return Ok(());
 }
 
+/// Parses the bytes in the format of the [`BytesSerde`], skipping their contents, see [`DatabaseIO::check_storage`]
+pub fn check_storage_bytes<S: BytesSerde>(bytes: &[u8], path: &Path) -> Result<()> {
+    return S::try_deserialize_from_bytes::<IgnoredAny>(bytes)
+        .map(|_| ())
+        .map_err(|e| Error::DBCorrupt {
+            file_path: path.to_path_buf(),
+            reason: Error::DeserializationFailure(Box::new(e)).to_string(),
+        });
+}
+
 pub fn backup_failed_parse<D: Database>(
     database: &D,
     path: impl AsRef<Path>,
@@ -96,7 +381,77 @@ pub fn backup_failed_parse<D: Database>(
     };
 }
 
+pub fn repair_partition<D: Database + ?Sized, T: DatabaseRecordPartitioned>(
+    database: &D,
+    policy: DuplicatePolicy,
+    reorder: impl FnOnce(&mut Vec<T>),
+) -> Result<RepairReport> {
+    let path = database.partition_path::<T>()?;
+
+    let records = database.get_all_lenient::<T>()?;
+    let total = records.len();
+    let original_uniques = records
+        .iter()
+        .map(|record| record.unique_value())
+        .collect::<Vec<_>>();
+
+    let mut repaired = policy.deduplicate(records);
+    reorder(&mut repaired);
+
+    let kept = repaired.len();
+    let removed = total - kept;
+    if removed == 0
+        && repaired
+            .iter()
+            .map(|record| record.unique_value())
+            .eq(original_uniques)
+    {
+        return Ok(RepairReport {
+            kept,
+            removed,
+            backup_path: None,
+        });
+    }
+
+    let backup_path = database.try_backup_storage(&path, "REPAIR")?;
+    database.try_write_storage(repaired, &path)?;
+
+    info!(
+        "Repaired partition at [{}], removing [{removed}] duplicate records",
+        path.display()
+    );
+
+    return Ok(RepairReport {
+        kept,
+        removed,
+        backup_path: Some(backup_path),
+    });
+}
+
+pub fn with_path_context(error: Error, path: impl AsRef<Path>) -> Error {
+    return match error {
+        Error::SerializationFailure(reason) => Error::DBSerializationFailure {
+            file_path: path.as_ref().to_path_buf(),
+            reason,
+        },
+        Error::DeserializationFailure(reason) => Error::DBDeserializationFailure {
+            file_path: path.as_ref().to_path_buf(),
+            reason,
+        },
+        e => e,
+    };
+}
+
 pub fn try_copy_file(source: impl AsRef<Path>, destination: impl AsRef<Path>) -> Result<()> {
+    // Copying a file onto itself would truncate it
+    let is_same_file = match (fs::canonicalize(&source), fs::canonicalize(&destination)) {
+        (Ok(source), Ok(destination)) => source == destination,
+        _ => source.as_ref() == destination.as_ref(),
+    };
+    if is_same_file {
+        return Err(identical_copy_failure(source));
+    }
+
     return std::fs::copy(&source, &destination)
         .map(|_| {})
         .map_err(|e| Error::IOCopyFailure {
@@ -106,7 +461,42 @@ pub fn try_copy_file(source: impl AsRef<Path>, destination: impl AsRef<Path>) ->
         });
 }
 
-pub fn try_write_file(serialized_bytes: &[u8], path: impl AsRef<Path>) -> Result<()> {
+pub fn identical_copy_failure(source: impl AsRef<Path>) -> Error {
+    return Error::DBOperationFailure {
+        path: source.as_ref().display().to_string(),
+        reason: String::from("Source and destination of the copy are identical."),
+    };
+}
+
+pub fn try_move_file(source: impl AsRef<Path>, destination: impl AsRef<Path>) -> Result<()> {
+    let (source, destination) = (source.as_ref(), destination.as_ref());
+    let map_err = |e| Error::IOMoveFailure {
+        path_from: source.display().to_string(),
+        path_destination: destination.display().to_string(),
+        reason: e,
+    };
+
+    return match fs::rename(source, destination) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            debug!(
+                "Moving [{}] across filesystems, falling back to copy and delete.",
+                source.display()
+            );
+
+            try_copy_file(source, destination)?;
+            return fs::remove_file(source).map_err(map_err);
+        }
+        Err(e) => Err(map_err(e)),
+    };
+}
+
+/// Writes the file, with the given permissions mode on Unix (ignored on other platforms)
+pub fn try_write_file(
+    serialized_bytes: &[u8],
+    path: impl AsRef<Path>,
+    file_mode: Option<u32>,
+) -> Result<()> {
     let path = path.as_ref();
 
     if let Some(parent) = path.parent() {
@@ -123,22 +513,374 @@ pub fn try_write_file(serialized_bytes: &[u8], path: impl AsRef<Path>) -> Result
         });
     }
 
-    return fs::write(path, serialized_bytes).map_err(|e| Error::IOWriteFailure {
+    let map_err = |e| Error::IOWriteFailure {
         path: path.display().to_string(),
         reason: e,
+    };
+
+    let mut file = open_with_mode(
+        OpenOptions::new().write(true).create(true).truncate(true),
+        path,
+        file_mode,
+    )
+    .map_err(map_err)?;
+
+    return file.write_all(serialized_bytes).map_err(map_err);
+}
+
+/// Opens the file, creating it with the given permissions mode on Unix (ignored on other platforms)
+///
+/// Existing files get the mode as well, before anything is written to them.
+fn open_with_mode(
+    options: &mut OpenOptions,
+    path: &Path,
+    file_mode: Option<u32>,
+) -> io::Result<File> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        if let Some(mode) = file_mode {
+            let file = options.mode(mode).open(path)?;
+            file.set_permissions(fs::Permissions::from_mode(mode))?;
+
+            return Ok(file);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = file_mode;
+
+    return options.open(path);
+}
+
+/// Returns the path of the sidecar file holding the version of the file
+pub fn version_path(path: impl AsRef<Path>) -> PathBuf {
+    return with_added_extension(path, "version");
+}
+
+pub fn try_read_version_file(path: impl AsRef<Path>) -> Result<u64> {
+    let version_path = version_path(path);
+
+    let version = match fs::read_to_string(&version_path) {
+        Ok(version) => version,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => {
+            return Err(Error::DBCorrupt {
+                file_path: version_path,
+                reason: e.to_string(),
+            });
+        }
+    };
+
+    return version
+        .trim()
+        .parse()
+        .map_err(|e: std::num::ParseIntError| Error::DBCorrupt {
+            file_path: version_path,
+            reason: e.to_string(),
+        });
+}
+
+pub fn try_bump_version_file(path: impl AsRef<Path>, file_mode: Option<u32>) -> Result<()> {
+    return try_update_version_file(path, file_mode, |version| Ok(version + 1));
+}
+
+/// Sets the version of the destination past the versions of both files, after copying or moving the source onto it
+pub fn try_carry_version_file(
+    source: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+    file_mode: Option<u32>,
+) -> Result<()> {
+    return try_update_version_file(destination, file_mode, |version| {
+        Ok(try_read_version_file(source)?.max(version) + 1)
     });
 }
 
-pub fn try_read_file(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+/// Updates the version of the file, holding an exclusive lock on its version lock file throughout
+///
+/// The lock serializes updates across processes, so that no update is lost. The new version is written to
+/// a temporary file which then replaces the version file, so that readers never see a partially written version.
+fn try_update_version_file(
+    path: impl AsRef<Path>,
+    file_mode: Option<u32>,
+    update: impl FnOnce(u64) -> Result<u64>,
+) -> Result<()> {
+    let version_path = version_path(path.as_ref());
+    let lock_path = with_added_extension(&version_path, "lock");
+    let temp_path = with_added_extension(&version_path, "tmp");
+
+    if let Some(parent) = version_path.parent() {
+        create_dir_all(parent).map_err(|e| Error::IOCreateDirFailure {
+            path: parent.display().to_string(),
+            reason: e,
+        })?;
+    }
+
+    let map_err = |e| Error::IOWriteFailure {
+        path: version_path.display().to_string(),
+        reason: e,
+    };
+
+    let lock = open_with_mode(
+        OpenOptions::new().write(true).create(true).truncate(false),
+        &lock_path,
+        file_mode,
+    )
+    .map_err(map_err)?;
+    lock.lock().map_err(map_err)?;
+
+    let version = update(try_read_version_file(path)?)?;
+    try_write_file(version.to_string().as_bytes(), &temp_path, file_mode)?;
+
+    return fs::rename(&temp_path, &version_path).map_err(map_err);
+}
+
+pub fn try_open_append(path: impl AsRef<Path>, file_mode: Option<u32>) -> Result<File> {
     let path = path.as_ref();
 
-    return fs::read(path).map_err(|e| match e.kind() {
-        std::io::ErrorKind::NotFound => Error::DBNotFound {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).map_err(|e| Error::IOCreateDirFailure {
+            path: parent.display().to_string(),
+            reason: e,
+        })?;
+    }
+
+    return open_with_mode(
+        OpenOptions::new().create(true).append(true),
+        path,
+        file_mode,
+    )
+    .map_err(|e| Error::IOWriteFailure {
+        path: path.display().to_string(),
+        reason: e,
+    });
+}
+
+/// Reads the file, refusing files larger than `max_bytes` before reading them into memory
+pub fn try_read_file(path: impl AsRef<Path>, max_bytes: Option<u64>) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    let map_err = |e: io::Error| match e.kind() {
+        io::ErrorKind::NotFound => Error::DBNotFound {
             file_path: path.to_path_buf(),
         },
         _ => Error::DBCorrupt {
             file_path: path.to_path_buf(),
             reason: e.to_string(),
         },
-    });
+    };
+
+    let Some(max_bytes) = max_bytes else {
+        return fs::read(path).map_err(map_err);
+    };
+    let too_large = |size| Error::DBTooLarge {
+        file_path: path.to_path_buf(),
+        size,
+        max_bytes,
+    };
+
+    let file = File::open(path).map_err(map_err)?;
+    let size = file.metadata().map_err(map_err)?.len();
+    if size > max_bytes {
+        return Err(too_large(size));
+    }
+
+    // The file may grow after its size was checked, never read more than one byte past the limit
+    let mut bytes = Vec::with_capacity(size as usize);
+    file.take(max_bytes + 1)
+        .read_to_end(&mut bytes)
+        .map_err(map_err)?;
+    if bytes.len() as u64 > max_bytes {
+        return Err(too_large(bytes.len() as u64));
+    }
+
+    return Ok(bytes);
+}
+
+pub fn try_write_to<S: BytesSerde, W: Write>(data: impl Serialize, writer: &mut W) -> Result<()> {
+    let serialized = S::try_serialize_as_bytes(data)?;
+
+    return writer
+        .write_all(&serialized)
+        .and_then(|()| writer.flush())
+        .map_err(|e| Error::IOStreamFailure { reason: e });
+}
+
+pub fn try_read_from<S: BytesSerde, R: Read, O: for<'a> Deserialize<'a>>(
+    reader: &mut R,
+) -> Result<O> {
+    let mut bytes = vec![];
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| Error::IOStreamFailure { reason: e })?;
+
+    return S::try_deserialize_from_bytes(&bytes);
+}
+
+/// Reads the records streamed from the reader, passing them to `on_batch` in batches of [`IMPORT_BATCH_SIZE`] records
+///
+/// Returns the count of records passed to `on_batch`.
+pub fn try_read_record_batches_from<DB: DatabaseIO + ?Sized, T: DatabaseRecord, R: Read>(
+    database: &DB,
+    reader: &mut R,
+    on_batch: &mut dyn FnMut(Vec<T>) -> Result<()>,
+) -> Result<usize> {
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+    let mut count = 0;
+
+    database.read_seq_from(reader, &mut |record: T| {
+        batch.push(record);
+        if batch.len() == IMPORT_BATCH_SIZE {
+            on_batch(std::mem::replace(
+                &mut batch,
+                Vec::with_capacity(IMPORT_BATCH_SIZE),
+            ))?;
+            count += IMPORT_BATCH_SIZE;
+        }
+
+        return Ok(());
+    })?;
+
+    if !batch.is_empty() {
+        let batch_size = batch.len();
+        on_batch(batch)?;
+        count += batch_size;
+    }
+
+    return Ok(count);
+}
+
+/// Counts the records stored in the path, counting a missing storage as empty
+///
+/// A storage holding the engine's own encoding of no records is compared byte for byte rather than deserialized,
+/// so checking an empty storage never reads records.
+pub fn try_count_stored_records<DB: DatabaseIO + ?Sized>(
+    database: &DB,
+    path: &Path,
+) -> Result<usize> {
+    let mut empty = vec![];
+    database.write_storage_to(Vec::<()>::new(), &mut empty)?;
+
+    match database.storage_size(path) {
+        Ok(size) if size == empty.len() as u64 => {
+            if database.try_read_storage_bytes(path)? == empty {
+                return Ok(0);
+            }
+        }
+        Ok(_) => {}
+        Err(Error::DBNotFound { .. }) => return Ok(0),
+        Err(e) => return Err(e),
+    }
+
+    return Ok(database.try_read_storage::<Vec<IgnoredAny>>(path)?.len());
+}
+
+pub fn try_list_dir(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    let map_err = |e| Error::IOReadDirFailure {
+        path: dir.display().to_string(),
+        reason: e,
+    };
+
+    // `read_dir`'s order is platform and filesystem dependent, sorting keeps listings stable everywhere
+    let mut paths = fs::read_dir(dir)
+        .map_err(map_err)?
+        .map(|entry| entry.map(|e| e.path()).map_err(map_err))
+        .collect::<Result<Vec<_>>>()?;
+    paths.sort_unstable();
+
+    return Ok(paths);
+}
+
+pub fn partition_name(path: impl AsRef<Path>, extension: &str) -> Option<String> {
+    let path = path.as_ref();
+
+    if path.extension()? != extension {
+        return None;
+    }
+
+    return path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned());
+}
+
+/// Toolchain-independent alternative to `Path::with_added_extension`, keeping any existing extension.
+pub fn with_added_extension(path: impl AsRef<Path>, extension: impl AsRef<OsStr>) -> PathBuf {
+    let path = path.as_ref();
+    let extension = extension.as_ref();
+
+    let Some(file_name) = path.file_name() else {
+        return path.to_path_buf();
+    };
+
+    if extension.is_empty() {
+        return path.to_path_buf();
+    }
+
+    let mut file_name = file_name.to_os_string();
+    file_name.push(".");
+    file_name.push(extension);
+
+    return path.with_file_name(file_name);
+}
+
+/// Applies an RFC 7386 JSON Merge Patch onto the target.
+#[cfg(feature = "json")]
+pub fn apply_merge_patch(target: &mut serde_json::Value, patch: serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+
+    let target = target
+        .as_object_mut()
+        .expect("Target should be an object as it was replaced before.");
+
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(&key);
+        } else {
+            apply_merge_patch(target.entry(key).or_insert(serde_json::Value::Null), value);
+        }
+    }
+}
+
+/// Records the count and latency of a database operation once dropped
+#[cfg(feature = "metrics")]
+pub struct OperationMetrics {
+    operation: &'static str,
+    partition: String,
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "metrics")]
+impl OperationMetrics {
+    pub fn start(operation: &'static str, path: impl AsRef<Path>) -> Self {
+        return Self {
+            operation,
+            partition: path
+                .as_ref()
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            start: std::time::Instant::now(),
+        };
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Drop for OperationMetrics {
+    fn drop(&mut self) {
+        let labels = [
+            ("operation", self.operation.to_string()),
+            ("partition", std::mem::take(&mut self.partition)),
+        ];
+
+        metrics::counter!("lupabase_operations_total", &labels).increment(1);
+        metrics::histogram!("lupabase_operation_duration_seconds", &labels)
+            .record(self.start.elapsed().as_secs_f64());
+    }
 }
@@ -0,0 +1,222 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use std::error::Error;
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn backups_cbor() -> Result<(), Box<dyn Error>> {
+    backups_tester::<DiskDB<CborSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn backups_json() -> Result<(), Box<dyn Error>> {
+    backups_tester::<DiskDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn backups_memory() -> Result<(), Box<dyn Error>> {
+    backups_tester::<MemoryDB<CborSerde>>()?;
+    backups_tester::<MemoryDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn backups_fixed_clock() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("backups", "fixed-clock");
+
+    let disk_db = DiskDB::<JsonSerde>::new(&working_dir).with_clock(Clock::Fixed(1_700_000_000));
+    let memory_db =
+        MemoryDB::<CborSerde>::new(&working_dir).with_clock(Clock::Fixed(1_700_000_000));
+
+    disk_db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![])?;
+    memory_db
+        .try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![])?;
+
+    assert_eq!(
+        disk_db.try_backup_storage(
+            disk_db.file_path(TestRecordPartitioned::PARTITION),
+            "Manual"
+        )?,
+        working_dir.join("TestRecordPartitioned.json.1700000000-Manual.bak")
+    );
+    assert_eq!(
+        memory_db.try_backup_storage(
+            memory_db.file_path(TestRecordPartitioned::PARTITION),
+            "Manual"
+        )?,
+        working_dir.join("TestRecordPartitioned.cbor.1700000000-Manual.bak")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn backups_prune_older_than() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("backups", "prune-older-than");
+
+    let db_at =
+        |timestamp| DiskDB::<JsonSerde>::new(&working_dir).with_clock(Clock::Fixed(timestamp));
+    let db = db_at(1_700_000_000);
+    let db_file_path = db.file_path(TestRecordPartitioned::PARTITION);
+
+    db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![])?;
+
+    let oldest_path = db_at(1_600_000_000).try_backup_storage(&db_file_path, "Manual")?;
+    let older_path = db_at(1_650_000_000).try_backup_storage(&db_file_path, "Manual")?;
+    let cutoff_path = db_at(1_700_000_000).try_backup_storage(&db_file_path, "Manual")?;
+    let newer_path = db_at(1_750_000_000).try_backup_storage(&db_file_path, "Manual")?;
+
+    assert_eq!(
+        db.prune_backups_older_than(&db_file_path, 1_700_000_000)?,
+        vec![oldest_path, older_path]
+    );
+    assert_eq!(
+        db.list_backups(&db_file_path)?
+            .into_iter()
+            .map(|backup| backup.path)
+            .collect::<Vec<_>>(),
+        vec![cutoff_path, newer_path]
+    );
+
+    assert!(
+        db.prune_backups_older_than(&db_file_path, 1_700_000_000)?
+            .is_empty()
+    );
+
+    Ok(())
+}
+
+fn backups_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let db_name = &format!("{}-{}", DB::SERDE_FORMAT, DB::NAME);
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("backups", db_name);
+
+    let db = DB::new(&working_dir);
+
+    {
+        span_and_info!("List backups");
+
+        let db_file_path = db.file_path(TestRecordPartitioned::PARTITION);
+        db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![])?;
+
+        assert!(db.list_backups(&db_file_path)?.is_empty());
+
+        let manual_path = db.try_backup_storage(&db_file_path, "Manual")?;
+        let failed_parsing_path = db.try_backup_storage(&db_file_path, "FAILED_PARSING")?;
+
+        // Backups of other partitions are not listed
+        db.try_initialize_storage::<TestRecordPartitioned2, Vec<TestRecordPartitioned2>>(vec![])?;
+        db.try_backup_storage(db.file_path(TestRecordPartitioned2::PARTITION), "Manual")?;
+
+        let mut backups = db.list_backups(&db_file_path)?;
+        backups.sort_by(|a, b| a.reason.cmp(&b.reason));
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].path, failed_parsing_path);
+        assert_eq!(backups[0].reason, "FAILED_PARSING");
+        assert_eq!(backups[1].path, manual_path);
+        assert_eq!(backups[1].reason, "Manual");
+
+        let failed_parsing_backups =
+            db.list_backups_with_reason(&db_file_path, "FAILED_PARSING")?;
+        assert_eq!(failed_parsing_backups.len(), 1);
+        assert_eq!(failed_parsing_backups[0].path, failed_parsing_path);
+
+        let manual_backups = db.list_backups_with_reason(&db_file_path, "Manual")?;
+        assert_eq!(manual_backups.len(), 1);
+        assert_eq!(manual_backups[0].path, manual_path);
+
+        assert!(
+            db.list_backups_with_reason(&db_file_path, "Unknown")?
+                .is_empty()
+        );
+    }
+
+    {
+        span_and_info!("Prune all backups");
+
+        // Along the 3 (2 + 1) backups created above, each with a distinct name within the same second
+        for i in 0..3 {
+            db.try_backup_storage(
+                db.file_path(TestRecordPartitioned::PARTITION),
+                format!("Manual{i}"),
+            )?;
+        }
+        for i in 0..2 {
+            db.try_backup_storage(
+                db.file_path(TestRecordPartitioned2::PARTITION),
+                format!("Manual{i}"),
+            )?;
+        }
+
+        let backup_size = db.storage_size(db.file_path(TestRecordPartitioned::PARTITION))?;
+        assert_eq!(db.backups_total_size()?, backup_size * 8);
+
+        assert_eq!(db.prune_all_backups(2)?, 4);
+        assert_eq!(
+            db.list_backups(db.file_path(TestRecordPartitioned::PARTITION))?
+                .len(),
+            2
+        );
+        assert_eq!(
+            db.list_backups(db.file_path(TestRecordPartitioned2::PARTITION))?
+                .len(),
+            2
+        );
+        assert_eq!(db.backups_total_size()?, backup_size * 4);
+
+        assert_eq!(db.prune_all_backups(2)?, 0);
+    }
+
+    {
+        span_and_info!("Read backup");
+
+        let db_file_path = db.file_path(TestRecordPartitioned::PARTITION);
+
+        let id = &mut 0;
+        let records = vec![
+            TestRecordPartitioned::new(id),
+            TestRecordPartitioned::new(id),
+        ];
+        db.replace_all(records.clone())?;
+        let backup_path = db.try_backup_storage(&db_file_path, "Preview")?;
+
+        db.insert(TestRecordPartitioned::new(id))?;
+        db.replace_all(db.get_all::<TestRecordPartitioned>()?.split_off(1))?;
+
+        // The backup still holds the older records
+        assert_eq!(
+            db.read_backup::<TestRecordPartitioned>(&backup_path)?,
+            records
+        );
+        assert_ne!(db.get_all::<TestRecordPartitioned>()?, records);
+
+        // Backups of other partitions, and the partition itself, are rejected
+        let other_backup_path =
+            db.try_backup_storage(db.file_path(TestRecordPartitioned2::PARTITION), "Preview")?;
+        assert!(matches!(
+            db.read_backup::<TestRecordPartitioned>(&other_backup_path),
+            Err(lupabase::Error::DBOperationFailure { .. })
+        ));
+        assert!(matches!(
+            db.read_backup::<TestRecordPartitioned>(&db_file_path),
+            Err(lupabase::Error::DBOperationFailure { .. })
+        ));
+    }
+
+    Ok(())
+}
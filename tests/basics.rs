@@ -2,8 +2,9 @@ pub mod tests_records;
 pub mod tests_utils;
 
 use insta::assert_debug_snapshot;
-use lupabase::prelude::*;
-use std::{error::Error, fs};
+use lupabase::{prelude::*, record::utils::DatabaseRecordsUtils};
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fs, num::NonZero};
 use tests_records::*;
 use tests_utils::*;
 
@@ -158,6 +159,19 @@ fn basics_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
             );
         }
 
+        {
+            span_and_info!("Operation", "Getting all as alternate type");
+
+            let records = db.get_all::<TestRecordPartitioned>()?;
+            let alternate_records =
+                db.get_all_as::<TestRecordAlternate>(TestRecordPartitioned::PARTITION)?;
+            assert_eq!(
+                records.as_uniques(),
+                alternate_records.as_uniques(),
+                "Alternate records should have been read from the same partition"
+            );
+        }
+
         {
             span_and_info!("Re-Initialize");
 
@@ -167,6 +181,462 @@ fn basics_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
                 db.get_all::<TestRecordPartitioned>()?
             );
         }
+
+        {
+            span_and_info!("Operation", "Applying changes");
+
+            let records = db.get_all::<TestRecordPartitioned>()?;
+            let mut updated_record = records[1].clone();
+            updated_record.data = String::from("Data has been updated by a changeset!");
+
+            db.apply_changes(
+                vec![
+                    TestRecordPartitioned::new(id),
+                    TestRecordPartitioned::new(id),
+                ],
+                vec![updated_record],
+                vec![records[0].id, records[2].id],
+            )?;
+            assert_debug_snapshot!(
+                format!("{db_name} applied changes"),
+                db.get_all::<TestRecordPartitioned>()?
+            );
+
+            let records = db.get_all::<TestRecordPartitioned>()?;
+            let missing_id = TestRecordPartitioned::new(id).id;
+            assert!(
+                db.apply_changes(
+                    vec![TestRecordPartitioned::new(id)],
+                    vec![],
+                    vec![records[0].id, missing_id],
+                )
+                .is_err(),
+                "Deleting a missing record should abort the whole changeset"
+            );
+            assert!(db.get_all::<TestRecordPartitioned>()? == records);
+        }
+
+        {
+            span_and_info!("Operation", "Trying to insert");
+
+            let records = db.get_all::<TestRecordPartitioned>()?;
+
+            let mut record = TestRecordPartitioned::new(id);
+            assert_eq!(db.try_insert(record.clone())?, Ok(()));
+
+            let existing = record.clone();
+            record.data = String::from("Data should not be inserted!");
+            assert_eq!(db.try_insert(record)?, Err(existing.clone()));
+
+            assert!(db.get_all::<TestRecordPartitioned>()? == [records, vec![existing]].concat());
+        }
+    }
+
+    {
+        span_and_info!("Replace all if changed");
+
+        let db_file_path = db.file_path(TestRecordPartitioned::PARTITION);
+        let modified = || fs::metadata(&db_file_path).and_then(|m| m.modified()).ok();
+
+        let mut records = db.get_all::<TestRecordPartitioned>()?;
+        records.reverse();
+
+        let modified_before = modified();
+        // Reordered records should be considered unchanged
+        assert!(!db.replace_all_if_changed(records.clone())?);
+        assert_eq!(modified(), modified_before);
+
+        records[0].data = String::from("Data has been replaced!");
+        assert!(db.replace_all_if_changed(records.clone())?);
+        assert!(!db.replace_all_if_changed(records.clone())?);
+        assert!(db.get_all::<TestRecordPartitioned>()? == records);
+    }
+
+    {
+        span_and_info!("Replace all if");
+
+        let records = db.get_all::<TestRecordPartitioned>()?;
+        let mut replaced_records = records.clone();
+        replaced_records[0].data = String::from("Data has been conditionally replaced!");
+
+        // The stored records no longer match the expected ones
+        let expected_len = records.len() + 1;
+        assert!(!db.replace_all_if(
+            replaced_records.clone(),
+            |current: &[TestRecordPartitioned]| { current.len() == expected_len }
+        )?);
+        assert!(db.get_all::<TestRecordPartitioned>()? == records);
+
+        assert!(db.replace_all_if(replaced_records.clone(), |current| {
+            current == records.as_slice()
+        })?);
+        assert!(db.get_all::<TestRecordPartitioned>()? == replaced_records);
+
+        db.replace_all(records)?;
+    }
+
+    {
+        span_and_info!("Range");
+
+        let records = db.get_all::<TestRecordPartitioned>()?;
+        let mut ids = records.as_uniques();
+        ids.sort();
+        let (lo, hi) = (ids[1], ids[3]);
+
+        let range = db.get_range::<TestRecordPartitioned>(&lo, &hi)?;
+        assert_eq!(range.len(), 3);
+        assert!(range.iter().all(|r| (lo..=hi).contains(&r.id)));
+    }
+
+    {
+        span_and_info!("Fold");
+
+        let sum = db.fold_all::<TestRecordPartitioned, _, _>(0, |sum, r| sum + r.id.get())?;
+        assert_eq!(
+            sum,
+            db.get_all::<TestRecordPartitioned>()?
+                .iter()
+                .map(|r| r.id.get())
+                .sum::<u64>()
+        );
+    }
+
+    {
+        span_and_info!("Count where");
+
+        let records = db.get_all::<TestRecordPartitioned>()?;
+        let prefix = "My data of 1";
+
+        let count = db.count_where::<TestRecordPartitioned, _>(|r| r.data.starts_with(prefix))?;
+        assert!(count > 0);
+        assert_eq!(
+            count,
+            records
+                .iter()
+                .filter(|r| r.data.starts_with(prefix))
+                .count()
+        );
+        assert_eq!(
+            db.count_where::<TestRecordPartitioned, _>(|_| true)?,
+            records.len()
+        );
+        assert_eq!(db.count_where::<TestRecordPartitioned, _>(|_| false)?, 0);
+    }
+
+    {
+        span_and_info!("Find first");
+
+        let records = db.get_all::<TestRecordPartitioned>()?;
+        let prefix = "My data of 1";
+
+        let first = db.find_first::<TestRecordPartitioned, _>(|r| r.data.starts_with(prefix))?;
+        assert!(first.is_some());
+        // The first match in storage order, on every read
+        assert!(first.as_ref() == records.iter().find(|r| r.data.starts_with(prefix)));
+        for _ in 0..10 {
+            assert!(
+                db.find_first::<TestRecordPartitioned, _>(|r| r.data.starts_with(prefix))? == first
+            );
+        }
+
+        assert!(
+            db.find_first::<TestRecordPartitioned, _>(|_| true)?
+                .as_ref()
+                == records.first()
+        );
+        assert!(
+            db.find_first::<TestRecordPartitioned, _>(|_| false)?
+                .is_none()
+        );
+    }
+
+    {
+        span_and_info!("Bulk load");
+
+        let id = &mut 0_u64;
+        let records = (0..10_000)
+            .map(|_| TestRecordPartitioned2::new(id))
+            .collect::<Vec<_>>();
+
+        db.bulk_load(&records)?;
+        assert!(db.get_all::<TestRecordPartitioned2>()? == records);
+
+        let db_file_path = db.file_path(TestRecordPartitioned2::PARTITION);
+        let version = db.version(&db_file_path)?;
+        assert!(
+            matches!(
+                db.bulk_load([TestRecordPartitioned2::new(id)]),
+                Err(lupabase::Error::DBOperationFailure { reason, .. }) if reason.contains(&format!("[{}]", records.len()))
+            ),
+            "Bulk loading into a non-empty partition should fail, counting its records"
+        );
+        assert_eq!(db.version(&db_file_path)?, version);
+
+        db.try_initialize_storage::<TestRecordPartitioned3, Vec<TestRecordPartitioned3>>(vec![])?;
+        let record = TestRecordPartitioned3::new(id);
+        assert!(
+            db.bulk_load([record.clone(), record.clone()]).is_err(),
+            "Bulk loading duplicates should fail"
+        );
+        db.bulk_load([record.clone()])?;
+        assert!(db.get_all::<TestRecordPartitioned3>()? == vec![record]);
+    }
+
+    {
+        span_and_info!("Import stream");
+
+        let existing = db.get_all::<TestRecordPartitioned2>()?;
+        let id = &mut 15_000_u64;
+        let imported_records = (0..2_500)
+            .map(|_| TestRecordPartitioned2::new(id))
+            .collect::<Vec<_>>();
+
+        let mut dump = vec![];
+        db.write_storage_to(&imported_records, &mut dump)?;
+
+        // Appended after the existing records
+        assert_eq!(
+            db.import_stream::<TestRecordPartitioned2, _>(dump.as_slice())?,
+            imported_records.len()
+        );
+        let records = db.get_all::<TestRecordPartitioned2>()?;
+        assert!(records[..existing.len()] == existing);
+        assert!(records[existing.len()..] == imported_records);
+
+        assert!(
+            db.import_stream::<TestRecordPartitioned2, _>(dump.as_slice())
+                .is_err(),
+            "Importing existing records should fail"
+        );
+        assert_eq!(db.get_all::<TestRecordPartitioned2>()?.len(), records.len());
+
+        // Nothing is imported when any record conflicts
+        let id = &mut 20_000_u64;
+        let mut conflicting_records = (0..10)
+            .map(|_| TestRecordPartitioned2::new(id))
+            .collect::<Vec<_>>();
+        conflicting_records.push(imported_records[0].clone());
+        let mut conflicting_dump = vec![];
+        db.write_storage_to(&conflicting_records, &mut conflicting_dump)?;
+        assert!(
+            db.import_stream::<TestRecordPartitioned2, _>(conflicting_dump.as_slice())
+                .is_err()
+        );
+        assert!(db.get_all::<TestRecordPartitioned2>()? == records);
+
+        let mut empty_dump = vec![];
+        db.write_storage_to(Vec::<TestRecordPartitioned2>::new(), &mut empty_dump)?;
+        assert_eq!(
+            db.import_stream::<TestRecordPartitioned2, _>(empty_dump.as_slice())?,
+            0
+        );
+
+        // Batches are inserted as they're read, before the rest of the input is parsed
+        let id = &mut 30_000_u64;
+        let streamed_records = (0..IMPORT_BATCH_SIZE + 10)
+            .map(|_| TestRecordPartitioned2::new(id))
+            .collect::<Vec<_>>();
+        let mut streamed_dump = vec![];
+        db.write_storage_to(&streamed_records, &mut streamed_dump)?;
+        let truncated_dump = &streamed_dump[..streamed_dump.len() - 1];
+        assert!(
+            db.import_stream::<TestRecordPartitioned2, _>(truncated_dump)
+                .is_err()
+        );
+        let records = db.get_all::<TestRecordPartitioned2>()?;
+        assert!(
+            records[records.len() - IMPORT_BATCH_SIZE..] == streamed_records[..IMPORT_BATCH_SIZE]
+        );
+    }
+
+    {
+        span_and_info!("Batch");
+
+        let records = db.get_all::<TestRecordPartitioned2>()?;
+        let id = &mut 40_000_u64;
+        let (new_record_1, new_record_2) = (
+            TestRecordPartitioned2::new(id),
+            TestRecordPartitioned2::new(id),
+        );
+        let mut updated_record = records[1].clone();
+        updated_record.data = String::from("Data has been updated by a batch!");
+
+        db.batch::<TestRecordPartitioned2>()
+            .insert(new_record_1.clone())
+            .update(updated_record.clone())
+            .delete(records[0].id)
+            .insert(new_record_2.clone())
+            .delete(records[2].id)
+            .apply()?;
+
+        let mut expected = records.clone();
+        expected[1] = updated_record;
+        expected.retain(|r| r.id != records[0].id && r.id != records[2].id);
+        expected.extend([new_record_1.clone(), new_record_2]);
+        assert!(db.get_all::<TestRecordPartitioned2>()? == expected);
+
+        // The valid operations aren't applied either
+        assert!(
+            db.batch::<TestRecordPartitioned2>()
+                .insert(TestRecordPartitioned2::new(id))
+                .delete(expected[0].id)
+                .insert(new_record_1)
+                .apply()
+                .is_err(),
+            "Inserting an existing record should abort the whole batch"
+        );
+        assert!(db.get_all::<TestRecordPartitioned2>()? == expected);
+    }
+
+    {
+        span_and_info!("Upsert ordering");
+
+        let id = &mut 20_000_u64;
+        let record_1 = TestRecordPartitioned3::new(id);
+        let mut record_2 = TestRecordPartitioned3::new(id);
+        let record_3 = TestRecordPartitioned3::new(id);
+
+        // Inserted records are appended, updated records keep their position
+        db.upsert_all([record_2.clone(), record_1.clone()])?;
+        record_2.data = String::from("Data 2 has been upserted!");
+        db.upsert_all([record_3.clone(), record_2.clone()])?;
+        assert_eq!(
+            db.get_all::<TestRecordPartitioned3>()?.as_uniques()[1..],
+            [record_2.id, record_1.id, record_3.id]
+        );
+
+        db.upsert_all_sorted([record_1])?;
+        assert_debug_snapshot!(
+            format!("{db_name} upserted all sorted"),
+            db.get_all::<TestRecordPartitioned3>()?
+        );
+    }
+
+    {
+        span_and_info!("Rekey");
+
+        let records = db.get_all::<TestRecordPartitioned3>()?;
+        let old_record = records[1].clone();
+        let mut new_record = old_record.clone();
+        new_record.id = NonZero::new(30_000).expect("ID should not be Zero");
+
+        db.rekey(&old_record.id, new_record.clone())?;
+        let rekeyed_records = db.get_all::<TestRecordPartitioned3>()?;
+        assert!(rekeyed_records.find_by_unique(&old_record.id).is_none());
+        assert!(rekeyed_records[1] == new_record);
+        assert_eq!(rekeyed_records.len(), records.len());
+
+        assert!(
+            db.rekey(&old_record.id, old_record.clone()).is_err(),
+            "Rekeying an absent record should fail"
+        );
+        assert!(
+            db.rekey(&new_record.id, records[0].clone()).is_err(),
+            "Rekeying onto an existing unique should fail"
+        );
+        // Rekeying onto the same unique is a plain replacement
+        db.rekey(&new_record.id, new_record.clone())?;
+    }
+
+    {
+        span_and_info!("Touch");
+
+        let records = db.get_all::<TestRecordPartitioned3>()?;
+        let touched_id = records[0].id;
+
+        db.touch::<TestRecordPartitioned3, _>(&touched_id, |record| {
+            record.data.push_str(" has been touched!");
+        })?;
+
+        // Only the touched record changed
+        let touched_records = db.get_all::<TestRecordPartitioned3>()?;
+        assert_eq!(touched_records.len(), records.len());
+        for (touched, record) in touched_records.iter().zip(&records) {
+            match touched.id == touched_id {
+                true => assert_eq!(touched.data, format!("{} has been touched!", record.data)),
+                false => assert!(touched == record),
+            }
+        }
+
+        assert!(
+            db.touch::<TestRecordPartitioned3, _>(
+                &NonZero::new(40_000).expect("ID should not be Zero"),
+                |_| {}
+            )
+            .is_err(),
+            "Touching an absent record should fail"
+        );
+        assert!(
+            db.touch::<TestRecordPartitioned3, _>(&touched_id, |record| {
+                record.id = NonZero::new(40_000).expect("ID should not be Zero");
+            })
+            .is_err(),
+            "Touching the unique should fail"
+        );
+        assert!(db.get_all::<TestRecordPartitioned3>()? == touched_records);
+    }
+
+    {
+        span_and_info!("Upsert with");
+
+        let increment = |id: u64| {
+            return db.upsert_with::<TestRecordCounter, _, _>(
+                &id,
+                |counter| counter.count += 1,
+                || TestRecordCounter { id, count: 0 },
+            );
+        };
+
+        // Created on the first call, then incremented
+        increment(1)?;
+        assert_eq!(
+            db.get_all::<TestRecordCounter>()?,
+            vec![TestRecordCounter { id: 1, count: 1 }]
+        );
+
+        increment(1)?;
+        increment(2)?;
+        increment(1)?;
+        assert_eq!(
+            db.get_all::<TestRecordCounter>()?,
+            vec![
+                TestRecordCounter { id: 1, count: 3 },
+                TestRecordCounter { id: 2, count: 1 }
+            ]
+        );
+
+        assert!(
+            db.upsert_with::<TestRecordCounter, _, _>(
+                &3,
+                |_| {},
+                || TestRecordCounter { id: 4, count: 0 }
+            )
+            .is_err(),
+            "A default with another unique should fail"
+        );
+        assert!(
+            db.upsert_with::<TestRecordCounter, _, _>(
+                &1,
+                |counter| counter.id = 4,
+                || TestRecordCounter { id: 1, count: 0 }
+            )
+            .is_err(),
+            "Changing the unique should fail"
+        );
+        assert_eq!(db.get_all::<TestRecordCounter>()?.len(), 2);
+    }
+
+    {
+        span_and_info!("Projection");
+
+        let records = db.get_all::<TestRecordPartitioned>()?;
+        assert!(!records.is_empty());
+
+        let projected = db.project_all::<TestRecordPartitioned, TestRecordAlternate>()?;
+        assert_eq!(
+            projected.iter().map(|p| p.id).collect::<Vec<_>>(),
+            records.as_uniques()
+        );
     }
 
     {
@@ -199,6 +669,32 @@ fn basics_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
             );
         }
 
+        {
+            span_and_info!("Operation", "Getting all by name");
+
+            assert!(
+                db.get_all_by_name::<TestRecord>("TestRecords")?
+                    == db.get_all_with_path::<TestRecord>(&db_file_path)?
+            );
+        }
+
+        {
+            span_and_info!("Operation", "Write records");
+
+            let records = db.try_read_storage::<Vec<TestRecord>>(&db_file_path)?;
+            let duplicated_records = [records.clone(), vec![records[0].clone()]].concat();
+
+            assert!(matches!(
+                db.try_write_records(&duplicated_records, &db_file_path),
+                Err(lupabase::Error::DBOperationFailure { .. })
+            ));
+            // Nothing should have been written
+            assert!(db.try_read_storage::<Vec<TestRecord>>(&db_file_path)? == records);
+
+            db.try_write_records(&records, &db_file_path)?;
+            assert!(db.try_read_storage::<Vec<TestRecord>>(&db_file_path)? == records);
+        }
+
         {
             span_and_info!("Operation", "Backup");
 
@@ -266,10 +762,49 @@ fn basics_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
             db.try_initialize_storage_with_path(default_record, &db_file_path)?;
             assert_debug_snapshot!(
                 format!("{db_name} Single Storage reinitialized"),
-                db.try_read_storage::<TestRecord>(db_file_path)?
+                db.try_read_storage::<TestRecord>(&db_file_path)?
+            );
+        }
+
+        {
+            span_and_info!("Operation", "Merge patch");
+
+            let record = db.try_read_storage::<TestRecord>(&db_file_path)?;
+            db.merge_patch::<TestRecord>(
+                &db_file_path,
+                serde_json::json!({ "data": "Data has been patched!" }),
+            )?;
+            assert_eq!(
+                db.try_read_storage::<TestRecord>(&db_file_path)?,
+                TestRecord {
+                    data: String::from("Data has been patched!"),
+                    ..record
+                }
+            );
+
+            assert!(
+                db.merge_patch::<TestRecord>(&db_file_path, serde_json::json!({ "id": null }))
+                    .is_err(),
+                "Patching away a required field should fail"
             );
         }
     }
 
     Ok(())
 }
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestRecordCounter {
+    pub id: u64,
+    pub count: u64,
+}
+
+impl DatabaseRecord for TestRecordCounter {
+    type Unique = u64;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
+impl DatabaseRecordPartitioned for TestRecordCounter {
+    const PARTITION: &str = "TestRecordCounter";
+}
@@ -0,0 +1,511 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    error::Error,
+    fs,
+    num::NonZero,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+};
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn diskdb_file_cache_cbor() -> Result<(), Box<dyn Error>> {
+    diskdb_file_cache_tester::<CborSerde>()?;
+
+    Ok(())
+}
+
+#[test]
+fn diskdb_file_cache_json() -> Result<(), Box<dyn Error>> {
+    diskdb_file_cache_tester::<JsonSerde>()?;
+
+    Ok(())
+}
+
+fn diskdb_file_cache_tester<S: BytesSerde>() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("diskdb-file-cache", S::FORMAT);
+
+    let db = DiskDB::<S>::new(&working_dir).with_file_cache(1024 * 1024);
+    let db_clone = db.clone();
+    let db_file_path = db.file_path("TestRecord");
+
+    let mut record = TestRecord::new(&mut 0);
+    record.data = String::from("AAAA");
+    db.try_write_storage(&record, &db_file_path)?;
+    assert_eq!(
+        db_clone.try_read_storage::<TestRecord>(&db_file_path)?,
+        record
+    );
+
+    // Outlast the granularity of the file timestamps, so the rewrite changes the status change time
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    // Overwrite the file behind the engine's back, keeping its length and modification time
+    let modified = fs::metadata(&db_file_path)?.modified()?;
+    let mut external_record = record.clone();
+    external_record.data = String::from("BBBB");
+    fs::write(&db_file_path, S::try_serialize_as_bytes(&external_record)?)?;
+    fs::File::options()
+        .write(true)
+        .open(&db_file_path)?
+        .set_modified(modified)?;
+
+    if cfg!(unix) {
+        assert_eq!(
+            db_clone.try_read_storage::<TestRecord>(&db_file_path)?,
+            external_record,
+            "The cache should detect the rewrite through the status change time"
+        );
+    }
+    assert_eq!(
+        DiskDB::<S>::new(&working_dir).try_read_storage::<TestRecord>(&db_file_path)?,
+        external_record,
+        "Without the file cache, the file should always be read"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn diskdb_storage_exists_inaccessible() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("diskdb-storage-exists", "inaccessible");
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir);
+
+    // A regular file in place of a directory makes the existence of its children undeterminable
+    let not_a_dir = working_dir.join("NotADirectory");
+    fs::write(&not_a_dir, [])?;
+
+    assert!(matches!(
+        db.storage_exists(not_a_dir.join("TestRecord.json")),
+        Err(lupabase::Error::IOExistsFailure { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn diskdb_try_new_probe_writable() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("diskdb-try-new", "probe");
+
+    DiskDB::<JsonSerde>::try_new(&working_dir, true)?;
+    assert!(
+        fs::read_dir(&working_dir)?.next().is_none(),
+        "Probing should not leave files behind"
+    );
+
+    let mut permissions = fs::metadata(&working_dir)?.permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&working_dir, permissions.clone())?;
+
+    // Privileged users (e.g. root) can write regardless of permissions
+    let writable = fs::write(working_dir.join("Writable"), []).is_ok();
+
+    let unprobed = DiskDB::<JsonSerde>::try_new(&working_dir, false);
+    let probed = DiskDB::<JsonSerde>::try_new(&working_dir, true);
+
+    #[expect(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(&working_dir, permissions)?;
+
+    assert!(unprobed.is_ok(), "Not probing should not fail");
+    if !writable {
+        assert!(matches!(
+            probed,
+            Err(lupabase::Error::DBInaccessible { .. })
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn diskdb_max_read_bytes() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("diskdb-max-read-bytes", "limit");
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir).with_file_cache(1024 * 1024);
+
+    let id = &mut 0;
+    db.try_initialize_storage::<TestRecordPartitioned, _>(vec![TestRecordPartitioned::new(id)])?;
+    let size = db.storage_size(db.file_path(TestRecordPartitioned::PARTITION))?;
+
+    // Even files cached by an unlimited clone are refused
+    let limited_db = db.clone().with_max_read_bytes(size);
+    assert_eq!(limited_db.get_all::<TestRecordPartitioned>()?.len(), 1);
+
+    db.insert(TestRecordPartitioned::new(id))?;
+    db.get_all::<TestRecordPartitioned>()?;
+
+    let error = limited_db
+        .get_all::<TestRecordPartitioned>()
+        .expect_err("Files over the limit should be refused");
+    assert!(
+        matches!(error, lupabase::Error::DBTooLarge { max_bytes, .. } if max_bytes == size),
+        "Expected DBTooLarge, got: [{error}]"
+    );
+
+    // Refusing a file doesn't treat it as corrupt
+    assert!(
+        limited_db
+            .list_backups(db.file_path(TestRecordPartitioned::PARTITION))?
+            .is_empty()
+    );
+
+    Ok(())
+}
+
+static PARSED_RECORDS: AtomicUsize = AtomicUsize::new(0);
+
+fn counted_parse<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NonZero<u64>, D::Error> {
+    PARSED_RECORDS.fetch_add(1, Ordering::Relaxed);
+    NonZero::deserialize(deserializer)
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestRecordCounted {
+    #[serde(deserialize_with = "counted_parse")]
+    pub id: NonZero<u64>,
+}
+
+impl DatabaseRecord for TestRecordCounted {
+    type Unique = NonZero<u64>;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
+impl DatabaseRecordPartitioned for TestRecordCounted {
+    const PARTITION: &str = "TestRecordCounted";
+}
+
+#[test]
+fn diskdb_parsed_cache() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("diskdb-parsed-cache", "cbor");
+
+    let db = DiskDB::<CborSerde>::new(&working_dir).with_file_cache(1024 * 1024);
+
+    let records = (1..=3)
+        .map(|id| TestRecordCounted {
+            id: NonZero::new(id).expect("ID should not be Zero"),
+        })
+        .collect::<Vec<_>>();
+    db.try_initialize_storage::<TestRecordCounted, _>(records.clone())?;
+
+    let parsed_before = PARSED_RECORDS.load(Ordering::Relaxed);
+    assert_eq!(db.get_all_cached::<TestRecordCounted>()?, records);
+    assert_eq!(PARSED_RECORDS.load(Ordering::Relaxed), parsed_before + 3);
+
+    // The second read reuses the parsed records, also through clones
+    assert_eq!(db.get_all_cached::<TestRecordCounted>()?, records);
+    assert_eq!(db.clone().get_all_cached::<TestRecordCounted>()?, records);
+    assert_eq!(PARSED_RECORDS.load(Ordering::Relaxed), parsed_before + 3);
+
+    // Reading as another type reparses, without evicting the cached records
+    let alternate_records = db.get_all_cached_with_path::<TestRecordAlternate>(
+        db.file_path(TestRecordCounted::PARTITION),
+    )?;
+    assert_eq!(alternate_records.len(), 3);
+    assert_eq!(db.get_all_cached::<TestRecordCounted>()?, records);
+    assert_eq!(PARSED_RECORDS.load(Ordering::Relaxed), parsed_before + 3);
+
+    // Writing discards the parsed records
+    db.insert(TestRecordCounted {
+        id: NonZero::new(4).expect("ID should not be Zero"),
+    })?;
+    let parsed_before = PARSED_RECORDS.load(Ordering::Relaxed);
+    assert_eq!(db.get_all_cached::<TestRecordCounted>()?.len(), 4);
+    assert!(PARSED_RECORDS.load(Ordering::Relaxed) > parsed_before);
+
+    // Files too large for the cache, or read without it, are parsed on every read
+    for uncached_db in [
+        DiskDB::<CborSerde>::new(&working_dir).with_file_cache(1),
+        DiskDB::<CborSerde>::new(&working_dir),
+    ] {
+        let parsed_before = PARSED_RECORDS.load(Ordering::Relaxed);
+        assert_eq!(uncached_db.get_all_cached::<TestRecordCounted>()?.len(), 4);
+        assert_eq!(uncached_db.get_all_cached::<TestRecordCounted>()?.len(), 4);
+        assert_eq!(PARSED_RECORDS.load(Ordering::Relaxed), parsed_before + 8);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn diskdb_version() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("diskdb-version", "json");
+
+    // Separate handles share nothing but the directory, as separate processes would
+    let writer = DiskDB::<JsonSerde>::new(&working_dir);
+    let poller = DiskDB::<JsonSerde>::new(&working_dir);
+    let path = poller.file_path(TestRecordPartitioned::PARTITION);
+
+    assert_eq!(poller.version(&path)?, 0);
+
+    let id = &mut 0;
+    writer
+        .try_initialize_storage::<TestRecordPartitioned, _>(vec![TestRecordPartitioned::new(id)])?;
+    let initialized_version = poller.version(&path)?;
+    assert!(initialized_version > 0);
+
+    writer.insert(TestRecordPartitioned::new(id))?;
+    let inserted_version = poller.version(&path)?;
+    assert!(inserted_version > initialized_version);
+
+    // Reads don't change the version
+    let _ = writer.get_all::<TestRecordPartitioned>()?;
+    assert_eq!(poller.version(&path)?, inserted_version);
+
+    // The version sidecar is not a partition
+    assert_eq!(
+        poller.list_partitions()?,
+        vec![TestRecordPartitioned::PARTITION.to_string()]
+    );
+
+    // Versions never go backwards, even if the storage is removed
+    writer.try_remove_storage(&path)?;
+    writer
+        .try_initialize_storage::<TestRecordPartitioned, _>(vec![TestRecordPartitioned::new(id)])?;
+    let reinitialized_version = poller.version(&path)?;
+    assert!(reinitialized_version > inserted_version);
+
+    // Moving or copying onto the partition bumps its version, past the source's
+    let other_path = writer.file_path("Other");
+    writer.try_write_storage(Vec::<TestRecordPartitioned>::new(), &other_path)?;
+    writer.try_move_storage(&other_path, &path)?;
+    let moved_version = poller.version(&path)?;
+    assert!(moved_version > reinitialized_version);
+
+    writer.clone_partition(TestRecordPartitioned::PARTITION, "Clone")?;
+    assert!(poller.version(writer.file_path("Clone"))? > moved_version);
+    writer.try_copy_storage(writer.file_path("Clone"), &path)?;
+    assert!(poller.version(&path)? > moved_version);
+
+    // Backups are not versioned
+    let backup_path = writer.try_backup_storage(&path, "TEST")?;
+    assert!(!fs::exists(format!("{}.version", backup_path.display()))?);
+
+    Ok(())
+}
+
+#[test]
+fn diskdb_version_concurrent() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    const WRITES: u64 = 100;
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("diskdb-version-concurrent", "json");
+
+    let writers = [
+        DiskDB::<JsonSerde>::new(&working_dir),
+        DiskDB::<JsonSerde>::new(&working_dir),
+    ];
+    let poller = DiskDB::<JsonSerde>::new(&working_dir);
+    let path = poller.file_path(TestRecordPartitioned::PARTITION);
+    let writing = AtomicBool::new(true);
+
+    span_and_info!("Concurrent bumps");
+
+    thread::scope(|scope| {
+        // Polling throughout the writes never observes a partial or decreasing version
+        let polling = scope.spawn(|| {
+            let mut last_version = 0;
+            while writing.load(Ordering::Relaxed) {
+                let version = poller.version(&path)?;
+                assert!(version >= last_version);
+                last_version = version;
+            }
+
+            lupabase::Result::Ok(())
+        });
+
+        let handles = writers
+            .iter()
+            .map(|writer| {
+                scope.spawn(|| {
+                    for _ in 0..WRITES {
+                        writer.try_write_storage(Vec::<TestRecordPartitioned>::new(), &path)?;
+                    }
+
+                    lupabase::Result::Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let written = handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().expect("Writer thread should not panic"));
+        writing.store(false, Ordering::Relaxed);
+
+        written.and(polling.join().expect("Poller thread should not panic"))
+    })?;
+
+    // Every write of both handles bumped the version exactly once
+    assert_eq!(poller.version(&path)?, WRITES * 2);
+
+    Ok(())
+}
+
+#[test]
+fn diskdb_sorted_listing() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("diskdb-sorted", "json");
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir);
+
+    for partition in ["Logs", "Users", "Accounts", "Logs.2024-01", "Sessions"] {
+        db.try_initialize_storage_with_path(vec![] as Vec<TestRecord>, db.file_path(partition))?;
+    }
+    db.try_backup_storage(db.file_path("Users"), "Manual")?;
+
+    // Sorted by name, not by file name (where `Logs.2024-01.json` would precede `Logs.json`)
+    let partitions = db.list_partitions()?;
+    assert_eq!(
+        partitions,
+        vec!["Accounts", "Logs", "Logs.2024-01", "Sessions", "Users"]
+    );
+    for _ in 0..10 {
+        assert_eq!(db.list_partitions()?, partitions);
+    }
+
+    let storages = db.list_storages(&working_dir)?;
+    assert!(storages.is_sorted());
+    assert_eq!(db.list_storages(&working_dir)?, storages);
+
+    assert_eq!(
+        db.partition_stats()?.into_keys().collect::<Vec<_>>(),
+        partitions
+    );
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn diskdb_file_mode() -> Result<(), Box<dyn Error>> {
+    use std::{io::Write, os::unix::fs::PermissionsExt, path::Path};
+
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("diskdb-file-mode", "json");
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir).with_file_mode(0o600);
+    let mode = |path: &Path| -> Result<u32, Box<dyn Error>> {
+        return Ok(fs::metadata(path)?.permissions().mode() & 0o777);
+    };
+
+    let id = &mut 0;
+    let path = db.file_path(TestRecordPartitioned::PARTITION);
+    db.try_initialize_storage::<TestRecordPartitioned, _>(vec![TestRecordPartitioned::new(id)])?;
+    assert_eq!(mode(&path)?, 0o600);
+    assert_eq!(mode(&path.with_added_extension("version"))?, 0o600);
+
+    // Existing files are restricted before being written to
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644))?;
+    db.insert(TestRecordPartitioned::new(id))?;
+    assert_eq!(mode(&path)?, 0o600);
+
+    let backup_path = db.try_backup_storage(&path, "Manual")?;
+    assert_eq!(mode(&backup_path)?, 0o600);
+
+    let append_path = db.file_path("Audit.log");
+    db.open_append(&append_path)?.write_all(b"First line\n")?;
+    assert_eq!(mode(&append_path)?, 0o600);
+
+    // Without a mode, files are created with the default permissions
+    let default_db = DiskDB::<JsonSerde>::new(working_dir.join("default"));
+    default_db
+        .try_initialize_storage::<TestRecordPartitioned, _>(vec![TestRecordPartitioned::new(id)])?;
+    assert_ne!(
+        mode(&default_db.file_path(TestRecordPartitioned::PARTITION))?,
+        0o600
+    );
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn diskdb_dir_canonical() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("diskdb-dir-canonical", "json");
+
+    let real_dir = working_dir.join("real");
+    let linked_dir = working_dir.join("linked");
+    fs::create_dir_all(&real_dir)?;
+    std::os::unix::fs::symlink(&real_dir, &linked_dir)?;
+
+    let real_db = DiskDB::<JsonSerde>::new(&real_dir);
+    let linked_db = DiskDB::<JsonSerde>::new(&linked_dir);
+
+    // Only the canonical directory resolves the link
+    assert_ne!(linked_db.dir_absolute(), real_db.dir_absolute());
+    assert_eq!(linked_db.dir_canonical()?, fs::canonicalize(&real_dir)?);
+    assert_eq!(linked_db.dir_canonical()?, real_db.dir_canonical()?);
+
+    // A missing directory falls back to the absolute directory
+    let missing_db = DiskDB::<JsonSerde>::new(working_dir.join("missing"));
+    assert_eq!(missing_db.dir_canonical()?, missing_db.dir_absolute());
+
+    Ok(())
+}
+
+#[test]
+fn diskdb_compare_and_write_concurrent() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("diskdb-compare-and-write", "json");
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir);
+    let path = db.file_path("Counter");
+    db.try_write_storage(0_u64, &path)?;
+
+    for round in 0..1_000_u64 {
+        let expected = db.try_read_storage_bytes(&path)?;
+
+        // Every thread expects the same bytes, so only one of them may swap
+        let successes = std::thread::scope(|scope| {
+            let handles = (0..8)
+                .map(|_| {
+                    let (db, path, expected) = (db.clone(), &path, &expected);
+                    scope.spawn(move || db.compare_and_write(Some(expected), round + 1, path))
+                })
+                .collect::<Vec<_>>();
+
+            return handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Thread should not panic"))
+                .filter(|result| result.is_ok())
+                .count();
+        });
+
+        assert_eq!(successes, 1, "Exactly one compare and write should succeed");
+        assert_eq!(db.try_read_storage::<u64>(&path)?, round + 1);
+    }
+
+    Ok(())
+}
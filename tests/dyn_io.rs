@@ -0,0 +1,49 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use std::{error::Error, path::Path, sync::Arc};
+use tests_records::*;
+use tests_utils::*;
+
+fn open_engine(engine: &str, dir: &Path) -> Arc<dyn DynDatabaseIO> {
+    match engine {
+        "cbor" => Arc::new(DiskDB::<CborSerde>::new(dir)),
+        "json" => Arc::new(DiskDB::<JsonSerde>::new(dir)),
+        "memory" => Arc::new(MemoryDB::<CborSerde>::new(dir)),
+        _ => panic!("Unknown engine: [{engine}]"),
+    }
+}
+
+#[test]
+fn dyn_io_runtime_engine() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    for engine in ["cbor", "json", "memory"] {
+        span_and_info!("Engine", "{engine}");
+
+        let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("dyn-io", engine);
+
+        let db = open_engine(engine, &working_dir);
+        let db_file_path = db.dyn_file_path(Path::new(TestRecordPartitioned::PARTITION));
+
+        let records = vec![
+            TestRecordPartitioned::new(&mut 0),
+            TestRecordPartitioned::new(&mut 1),
+        ];
+        let value = serde_json::to_value(&records)?;
+
+        assert!(!db.dyn_storage_exists(&db_file_path)?);
+        db.dyn_write_value(&value, &db_file_path)?;
+        assert!(db.dyn_storage_exists(&db_file_path)?);
+
+        assert_eq!(db.dyn_read_value(&db_file_path)?, value);
+        assert!(!db.dyn_read_bytes(&db_file_path)?.is_empty());
+        assert_eq!(
+            db.dyn_list_partitions()?,
+            vec![TestRecordPartitioned::PARTITION.to_string()]
+        );
+    }
+
+    Ok(())
+}
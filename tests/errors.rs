@@ -0,0 +1,152 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::{
+    Error,
+    error::{DeserializeError, SerializeError},
+};
+use std::{collections::BTreeMap, path::PathBuf};
+use tests_utils::*;
+
+#[test]
+fn errors_from_serde() {
+    init_tracing_for_tests();
+
+    span_and_info!("From serde", "Json");
+
+    let json_error = serde_json::from_str::<u64>("\"Not a number\"")
+        .expect_err("A string should not parse as a number");
+    assert!(matches!(
+        Error::from(DeserializeError(json_error)),
+        Error::DeserializationFailure(_)
+    ));
+
+    let json_error = serde_json::to_vec(&BTreeMap::from([(vec![1_u8], 1_u8)]))
+        .expect_err("A non-string key should not serialize");
+    assert!(matches!(
+        Error::from(SerializeError(json_error)),
+        Error::SerializationFailure(_)
+    ));
+
+    span_and_info!("From serde", "Cbor");
+
+    let cbor_error = minicbor_serde::from_slice::<u64>(&[0x1C])
+        .expect_err("A reserved value should not parse as a number");
+    assert!(matches!(
+        Error::from(cbor_error),
+        Error::DeserializationFailure(_)
+    ));
+}
+
+#[test]
+fn errors_classification() {
+    init_tracing_for_tests();
+
+    span_and_info!("Classification");
+
+    let io_error = || std::io::Error::other("Test failure");
+    let path = || String::from("Test");
+    let file_path = || PathBuf::from("Test");
+
+    let classify = |error: &Error| {
+        return [
+            error.is_not_found(),
+            error.is_corrupt(),
+            error.is_conflict(),
+            error.is_io(),
+        ];
+    };
+
+    let not_found = [Error::DBNotFound {
+        file_path: file_path(),
+    }];
+    let corrupt = [
+        Error::DBCorrupt {
+            file_path: file_path(),
+            reason: String::from("Test corruption"),
+        },
+        Error::DBDeserializationFailure {
+            file_path: file_path(),
+            reason: Box::new(io_error()),
+        },
+        Error::DeserializationFailure(Box::new(io_error())),
+    ];
+    let conflict = [Error::DBTransactionConflict {
+        file_path: file_path(),
+    }];
+    let io = [
+        Error::IOCreateDirFailure {
+            path: path(),
+            reason: io_error(),
+        },
+        Error::IOCopyFailure {
+            path_from: path(),
+            path_destination: path(),
+            reason: io_error(),
+        },
+        Error::IOMoveFailure {
+            path_from: path(),
+            path_destination: path(),
+            reason: io_error(),
+        },
+        Error::IORemoveFailure {
+            path: path(),
+            reason: io_error(),
+        },
+        Error::IOReadDirFailure {
+            path: path(),
+            reason: io_error(),
+        },
+        Error::IOExistsFailure {
+            path: path(),
+            reason: io_error(),
+        },
+        Error::IOWriteFailure {
+            path: path(),
+            reason: io_error(),
+        },
+        Error::IOStreamFailure { reason: io_error() },
+        Error::DBInaccessible {
+            path: file_path(),
+            reason: io_error(),
+        },
+    ];
+    let unclassified = [
+        Error::SerializationFailure(Box::new(io_error())),
+        Error::DBSerializationFailure {
+            file_path: file_path(),
+            reason: Box::new(io_error()),
+        },
+        Error::DBTooLarge {
+            file_path: file_path(),
+            size: 2,
+            max_bytes: 1,
+        },
+        Error::DBInvalidPartition { partition: path() },
+        Error::DBOperationFailure {
+            path: path(),
+            reason: String::from("Test failure"),
+        },
+        Error::DBTransactionCommitFailure {
+            file_path: file_path(),
+            reason: String::from("Test failure"),
+        },
+        Error::DBTransactionRollbackFailure {
+            file_path: file_path(),
+            reason: String::from("Test failure"),
+            commit_reason: String::from("Test failure"),
+        },
+    ];
+
+    for (errors, expected) in [
+        (not_found.as_slice(), [true, false, false, false]),
+        (corrupt.as_slice(), [false, true, false, false]),
+        (conflict.as_slice(), [false, false, true, false]),
+        (io.as_slice(), [false, false, false, true]),
+        (unclassified.as_slice(), [false, false, false, false]),
+    ] {
+        for error in errors {
+            assert_eq!(classify(error), expected, "Classifying [{error:?}]");
+        }
+    }
+}
@@ -0,0 +1,238 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::{transactions::*, *};
+use std::{error::Error, fs, num::NonZero};
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn integrity_cbor() -> Result<(), Box<dyn Error>> {
+    integrity_tester::<DiskDB<CborSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn integrity_json() -> Result<(), Box<dyn Error>> {
+    integrity_tester::<DiskDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn integrity_strict_reads() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    span_and_info!("Strict reads");
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("integrity", "strict-reads");
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir);
+    let strict_db = db.clone().with_strict_reads(true);
+
+    // A hand-edited partition with a duplicate unique
+    fs::write(
+        db.file_path(TestRecordPartitioned::PARTITION),
+        r#"[{"id":1,"data":"First"},{"id":2,"data":"Only"},{"id":1,"data":"Second"}]"#,
+    )?;
+
+    assert_eq!(db.get_all::<TestRecordPartitioned>()?.len(), 3);
+    assert_eq!(
+        strict_db.get_all_lenient::<TestRecordPartitioned>()?.len(),
+        3
+    );
+
+    let error = strict_db
+        .get_all::<TestRecordPartitioned>()
+        .expect_err("Strict read should detect the duplicate");
+    assert!(matches!(error, lupabase::Error::DBCorrupt { .. }));
+    assert!(
+        error.to_string().contains("[1]"),
+        "Unclear message: [{error}]"
+    );
+
+    // Writes reading through the strict read are refused as well
+    assert!(matches!(
+        strict_db.insert(TestRecordPartitioned::new(&mut 2)),
+        Err(lupabase::Error::DBCorrupt { .. })
+    ));
+
+    // As are the writes reading, mutating and writing the records back
+    let upserted = TestRecordPartitioned::new(&mut 2);
+    for result in [
+        strict_db.upsert(upserted.clone()),
+        strict_db.upsert_all_sorted([upserted.clone()]),
+        strict_db.upsert_with(&upserted.unique_value(), |_| {}, || upserted.clone()),
+    ] {
+        assert!(
+            matches!(result, Err(lupabase::Error::DBCorrupt { .. })),
+            "Upserting should detect the duplicate, got: [{result:?}]"
+        );
+    }
+
+    let tx = strict_db.transact()?;
+    tx.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![])?;
+    tx.insert(upserted)?;
+    assert!(matches!(
+        strict_db.merge_commit::<TestRecordPartitioned>(&tx),
+        Err(lupabase::Error::DBTransactionCommitFailure { .. })
+    ));
+
+    // Nothing should have been written
+    assert_eq!(db.get_all::<TestRecordPartitioned>()?.len(), 3);
+
+    Ok(())
+}
+
+fn integrity_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let db_name = &format!("{}-{}", DB::SERDE_FORMAT, DB::NAME);
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("integrity", db_name);
+
+    let db = DB::new(working_dir);
+
+    {
+        span_and_info!("Healthy");
+
+        db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![
+            TestRecordPartitioned::new(&mut 0),
+        ])?;
+
+        assert!(db.integrity_check()?.is_empty());
+    }
+
+    {
+        span_and_info!("Corrupt");
+
+        fs::write(
+            db.file_path(TestRecordPartitioned2::PARTITION),
+            [0x1C, 0x1D, 0x1E, 0x1F],
+        )?;
+
+        let issues = db.integrity_check()?;
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(
+            &issues[0],
+            IntegrityIssue::Corrupt { partition, .. } if partition == TestRecordPartitioned2::PARTITION
+        ));
+
+        // Checking doesn't back up the corrupt partition, so it can be repeated without side effects
+        assert_eq!(db.integrity_check()?.len(), 1);
+        assert!(
+            db.list_backups(db.file_path(TestRecordPartitioned2::PARTITION))?
+                .is_empty()
+        );
+    }
+
+    {
+        span_and_info!("Validate");
+
+        db.try_initialize_storage::<TestRecordPartitioned3, Vec<TestRecordPartitioned3>>(vec![
+            TestRecordPartitioned3::new(&mut 0),
+        ])?;
+        db.validate::<TestRecordPartitioned3>()?;
+
+        // A hand-edited zero id can't be parsed as `NonZero`
+        db.try_write_storage(
+            vec![serde_json::json!({ "id": 0, "data": "Hand-edited" })],
+            db.file_path(TestRecordPartitioned3::PARTITION),
+        )?;
+
+        let message = db
+            .validate::<TestRecordPartitioned3>()
+            .expect_err("Zero id should fail validation")
+            .to_string();
+        assert!(message.contains("[0].id"), "Unclear message: [{message}]");
+        assert!(message.contains("nonzero"), "Unclear message: [{message}]");
+
+        // Validating doesn't back up the unparsable partition
+        assert!(
+            db.list_backups(db.file_path(TestRecordPartitioned3::PARTITION))?
+                .is_empty()
+        );
+
+        db.try_write_storage(
+            vec![
+                TestRecordPartitioned3::new(&mut 0),
+                TestRecordPartitioned3::new(&mut 0),
+            ],
+            db.file_path(TestRecordPartitioned3::PARTITION),
+        )?;
+        assert!(matches!(
+            db.validate::<TestRecordPartitioned3>(),
+            Err(lupabase::Error::DBCorrupt { .. })
+        ));
+    }
+
+    {
+        span_and_info!("Repair");
+
+        let record = |id: u64, data: &str| TestRecordPartitioned {
+            id: NonZero::new(id).expect("ID should not be Zero"),
+            data: data.to_string(),
+        };
+
+        db.try_write_storage(
+            vec![
+                record(3, "First"),
+                record(1, "First"),
+                record(3, "Second"),
+                record(2, "First"),
+                record(3, "Third"),
+            ],
+            db.file_path(TestRecordPartitioned::PARTITION),
+        )?;
+
+        let report = db.repair_partition::<TestRecordPartitioned>(DuplicatePolicy::KeepLast)?;
+        assert_eq!(report.kept, 3);
+        assert_eq!(report.removed, 2);
+
+        // The last duplicate replaces the first one, in its position
+        db.validate::<TestRecordPartitioned>()?;
+        assert_eq!(
+            db.get_all::<TestRecordPartitioned>()?,
+            vec![record(3, "Third"), record(1, "First"), record(2, "First")]
+        );
+
+        // The original partition was backed up
+        let backup_path = report.backup_path.expect("Repair should create a backup");
+        let backups = db.list_backups(db.file_path(TestRecordPartitioned::PARTITION))?;
+        assert!(backups.iter().any(|backup| backup.path == backup_path));
+        assert_eq!(
+            db.try_read_storage::<Vec<TestRecordPartitioned>>(&backup_path)?
+                .len(),
+            5
+        );
+
+        // A partition without duplicates is left untouched
+        let report = db.repair_partition::<TestRecordPartitioned>(DuplicatePolicy::KeepFirst)?;
+        assert_eq!(report.removed, 0);
+        assert!(report.backup_path.is_none());
+
+        // Unless it isn't sorted when repairing it sorted
+        let report =
+            db.repair_partition_sorted::<TestRecordPartitioned>(DuplicatePolicy::KeepFirst)?;
+        assert_eq!(report.removed, 0);
+        assert!(report.backup_path.is_some());
+        assert_eq!(
+            db.get_all::<TestRecordPartitioned>()?,
+            vec![record(1, "First"), record(2, "First"), record(3, "Third")]
+        );
+
+        // KeepFirst drops every later duplicate
+        db.try_write_storage(
+            vec![record(1, "First"), record(1, "Second")],
+            db.file_path(TestRecordPartitioned::PARTITION),
+        )?;
+        db.repair_partition::<TestRecordPartitioned>(DuplicatePolicy::KeepFirst)?;
+        assert_eq!(
+            db.get_all::<TestRecordPartitioned>()?,
+            vec![record(1, "First")]
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,412 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use serde::{Serialize, Serializer, ser};
+use std::{error::Error, io::Write};
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn io_cbor() -> Result<(), Box<dyn Error>> {
+    io_tester::<DiskDB<CborSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn io_json() -> Result<(), Box<dyn Error>> {
+    io_tester::<DiskDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn io_memory() -> Result<(), Box<dyn Error>> {
+    io_tester::<MemoryDB<CborSerde>>()?;
+    io_tester::<MemoryDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn io_cbor_seq() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let read_seq = |mut bytes: &[u8]| {
+        let mut items = vec![];
+        CborSerde::try_deserialize_seq_from_reader(&mut bytes, &mut |item: serde_json::Value| {
+            items.push(item);
+
+            return Ok(());
+        })?;
+
+        return lupabase::Result::Ok(items);
+    };
+
+    // Items of indefinite length, a multi-byte integer and a map holding a half-precision float, in an indefinite array
+    let items = read_seq(&[
+        0x9F, 0x01, 0x9F, 0x02, 0x03, 0xFF, 0x7F, 0x62, b'a', b'b', 0x61, b'c', 0xFF, 0x1A, 0x00,
+        0x01, 0x00, 0x00, 0xA1, 0x61, b'k', 0xF9, 0x3C, 0x00, 0xFF,
+    ])?;
+    assert_eq!(
+        items,
+        serde_json::json!([1, [2, 3], "abc", 65536, { "k": 1.0 }])
+            .as_array()
+            .cloned()
+            .expect("Should be an array")
+    );
+
+    assert!(read_seq(&[0x80])?.is_empty());
+
+    // Missing break, truncated item, not an array, and nested too deeply
+    let deeply_nested = [0x81; 1_000];
+    for malformed in [
+        &[0x9F, 0x01][..],
+        &[0x82, 0x01, 0x19, 0x01],
+        &[0x01],
+        &deeply_nested,
+    ] {
+        assert!(matches!(
+            read_seq(malformed),
+            Err(lupabase::Error::DeserializationFailure(_))
+        ));
+    }
+
+    Ok(())
+}
+
+struct Unserializable;
+
+impl Serialize for Unserializable {
+    fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(ser::Error::custom("Unserializable is never serializable"))
+    }
+}
+
+fn io_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let db_name = &format!("{}-{}", DB::SERDE_FORMAT, DB::NAME);
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("io", db_name);
+
+    let db = DB::new(&working_dir);
+
+    {
+        span_and_info!("Compare and write");
+
+        let db_file_path = db.file_path("TestRecord");
+        let mut id = 0;
+
+        db.compare_and_write(None, TestRecord::new(&mut id), &db_file_path)?;
+        assert!(matches!(
+            db.compare_and_write(None, TestRecord::new(&mut id), &db_file_path),
+            Err(lupabase::Error::DBTransactionConflict { .. })
+        ));
+
+        let current = db.try_read_storage_bytes(&db_file_path)?;
+        let record = TestRecord::new(&mut id);
+        db.compare_and_write(Some(&current), &record, &db_file_path)?;
+        assert_eq!(db.try_read_storage::<TestRecord>(&db_file_path)?, record);
+
+        assert!(matches!(
+            db.compare_and_write(Some(&current), TestRecord::new(&mut id), &db_file_path),
+            Err(lupabase::Error::DBTransactionConflict { .. })
+        ));
+        assert_eq!(db.try_read_storage::<TestRecord>(&db_file_path)?, record);
+    }
+
+    {
+        span_and_info!("Serialization failure");
+
+        let db_file_path = db.file_path("Unserializable");
+
+        let error = db
+            .try_write_storage(Unserializable, &db_file_path)
+            .expect_err("Unserializable should fail to serialize");
+        assert!(matches!(
+            &error,
+            lupabase::Error::DBSerializationFailure { file_path, .. } if *file_path == db_file_path
+        ));
+        assert!(error.to_string().contains("Unserializable"));
+    }
+
+    {
+        span_and_info!("Storage exists");
+
+        let db_file_path = db.file_path("Exists");
+
+        assert!(!db.storage_exists(&db_file_path)?);
+        db.try_initialize_storage_with_path(TestRecord::new(&mut 0), &db_file_path)?;
+        assert!(db.storage_exists(&db_file_path)?);
+    }
+
+    {
+        span_and_info!("Move storage");
+
+        let source_path = db.file_path("MoveSource");
+        let destination_path = db.file_path("MoveDestination");
+
+        let id = &mut 0;
+        let records = (0..10_000)
+            .map(|_| TestRecordPartitioned::new(id))
+            .collect::<Vec<_>>();
+        db.try_initialize_storage_with_path(records.clone(), &source_path)?;
+        // Read the destination once, so a stale cache would be noticed
+        db.try_initialize_storage_with_path(
+            vec![] as Vec<TestRecordPartitioned>,
+            &destination_path,
+        )?;
+        assert!(
+            db.get_all_with_path::<TestRecordPartitioned>(&destination_path)?
+                .is_empty()
+        );
+
+        db.try_move_storage(&source_path, &destination_path)?;
+
+        assert!(!db.storage_exists(&source_path)?);
+        assert!(db.get_all_with_path::<TestRecordPartitioned>(&destination_path)? == records);
+    }
+
+    {
+        span_and_info!("Copy storage onto itself");
+
+        let path = db.file_path("CopySource");
+        let records = vec![TestRecordPartitioned::new(&mut 0)];
+        db.try_initialize_storage_with_path(records.clone(), &path)?;
+
+        assert!(matches!(
+            db.try_copy_storage(&path, &path),
+            Err(lupabase::Error::DBOperationFailure { .. })
+        ));
+        assert!(db.get_all_with_path::<TestRecordPartitioned>(&path)? == records);
+    }
+
+    {
+        span_and_info!("Read modify write");
+
+        let path = db.file_path("ReadModifyWrite");
+        let id = &mut 0;
+
+        // A missing storage starts out empty
+        db.read_modify_write(&path, |records: &mut Vec<TestRecordPartitioned>| {
+            records.extend((0..4).map(|_| TestRecordPartitioned::new(id)));
+
+            Ok(())
+        })?;
+        db.read_modify_write(&path, |records: &mut Vec<TestRecordPartitioned>| {
+            records.retain(|r| r.id.get() % 2 == 0);
+            records.reverse();
+
+            Ok(())
+        })?;
+        assert_eq!(
+            db.get_all_with_path::<TestRecordPartitioned>(&path)?
+                .iter()
+                .map(|r| r.id.get())
+                .collect::<Vec<_>>(),
+            vec![4, 2]
+        );
+
+        // Failed mutations are not written
+        let result = db.read_modify_write(&path, |records: &mut Vec<TestRecordPartitioned>| {
+            records.clear();
+
+            Err(lupabase::Error::DBOperationFailure {
+                path: String::from("ReadModifyWrite"),
+                reason: String::from("Aborted"),
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!(
+            db.get_all_with_path::<TestRecordPartitioned>(&path)?.len(),
+            2
+        );
+    }
+
+    {
+        span_and_info!("Stream storage");
+
+        let path = db.file_path("Streamed");
+        let records = (0..3)
+            .scan(0, |id, _| Some(TestRecordPartitioned::new(id)))
+            .collect::<Vec<_>>();
+        db.try_initialize_storage_with_path(records.clone(), &path)?;
+
+        // The written bytes are in the engine's format, as if written to storage
+        let mut buffer = vec![];
+        db.write_storage_to(&records, &mut buffer)?;
+        assert_eq!(buffer, db.try_read_storage_bytes(&path)?);
+
+        let read_records: Vec<TestRecordPartitioned> =
+            db.read_storage_from(&mut buffer.as_slice())?;
+        assert!(read_records == records);
+
+        assert!(matches!(
+            db.write_storage_to(Unserializable, &mut vec![]),
+            Err(lupabase::Error::SerializationFailure(_))
+        ));
+        assert!(matches!(
+            db.read_storage_from::<_, Vec<TestRecordPartitioned>>(&mut &buffer[..buffer.len() - 1]),
+            Err(lupabase::Error::DeserializationFailure(_))
+        ));
+    }
+
+    {
+        span_and_info!("Append storage");
+
+        // Parent directories are created for file engines
+        let path = db.dir().join("Logs").join("Audit.log");
+
+        let mut writer = db.open_append(&path)?;
+        writer.write_all(b"First line\n")?;
+        writer.flush()?;
+        drop(writer);
+        assert_eq!(db.try_read_storage_bytes(&path)?, b"First line\n");
+
+        // Reopening appends at the end, instead of truncating
+        let mut writer = db.open_append(&path)?;
+        writer.write_all(b"Second line\n")?;
+        writer.write_all(b"Third line\n")?;
+        writer.flush()?;
+        drop(writer);
+        assert_eq!(
+            db.try_read_storage_bytes(&path)?,
+            b"First line\nSecond line\nThird line\n"
+        );
+    }
+
+    {
+        span_and_info!("Optional storage");
+
+        let path = db.file_path("OptionalRecord");
+
+        // A missing storage is absent as well
+        assert!(
+            db.try_read_storage_optional::<TestRecordPartitioned>(&path)?
+                .is_none()
+        );
+
+        db.try_initialize_storage_with_path(None::<TestRecordPartitioned>, &path)?;
+        assert!(db.storage_exists(&path)?);
+
+        // Stored as the format's null
+        let null = match DB::SERDE_FORMAT {
+            "json" => b"null".to_vec(),
+            _ => vec![0xF6],
+        };
+        assert_eq!(db.try_read_storage_bytes(&path)?, null);
+        assert!(
+            db.try_read_storage_optional::<TestRecordPartitioned>(&path)?
+                .is_none()
+        );
+        assert!(
+            db.try_read_storage::<Option<TestRecordPartitioned>>(&path)?
+                .is_none()
+        );
+
+        // Initializing again keeps the stored None, instead of treating it as missing
+        let record = TestRecordPartitioned::new(&mut 0);
+        db.try_initialize_storage_with_path(Some(record.clone()), &path)?;
+        assert!(
+            db.try_read_storage_optional::<TestRecordPartitioned>(&path)?
+                .is_none()
+        );
+
+        db.try_write_storage(Some(&record), &path)?;
+        assert!(db.try_read_storage_optional::<TestRecordPartitioned>(&path)? == Some(record));
+    }
+
+    {
+        span_and_info!("Content hash");
+
+        let path_1 = db.file_path("HashedRecord1");
+        let path_2 = db.file_path("HashedRecord2");
+
+        let mut record = TestRecord::new(&mut 0);
+        db.try_write_storage(&record, &path_1)?;
+        db.try_write_storage(&record, &path_2)?;
+        assert_eq!(db.content_hash(&path_1)?, db.content_hash(&path_2)?);
+
+        // A single byte change alters the hash
+        record.data.pop();
+        record.data.push('#');
+        db.try_write_storage(&record, &path_2)?;
+        assert_eq!(
+            db.try_read_storage_bytes(&path_1)?.len(),
+            db.try_read_storage_bytes(&path_2)?.len()
+        );
+        assert_ne!(db.content_hash(&path_1)?, db.content_hash(&path_2)?);
+
+        let hash_before = db.content_hash(&path_1)?;
+        let mut writer = db.open_append(&path_1)?;
+        writer.write_all(b" ")?;
+        drop(writer);
+        assert_ne!(db.content_hash(&path_1)?, hash_before);
+
+        assert!(matches!(
+            db.content_hash(db.file_path("MissingRecord")),
+            Err(lupabase::Error::DBNotFound { .. })
+        ));
+    }
+
+    {
+        span_and_info!("Sniff format");
+
+        let path = db.file_path("SniffedRecords");
+        let id = &mut 0;
+        let expected = Some(DB::SERDE_FORMAT);
+
+        db.try_write_storage(vec![TestRecord::new(id), TestRecord::new(id)], &path)?;
+        let bytes = db.try_read_storage_bytes(&path)?;
+        assert_eq!(sniff_format(&bytes).map(|f| f.name()), expected);
+
+        db.try_write_storage(Vec::<TestRecord>::new(), &path)?;
+        let bytes = db.try_read_storage_bytes(&path)?;
+        assert_eq!(sniff_format(&bytes).map(|f| f.name()), expected);
+
+        db.try_write_storage(TestRecord::new(id), &path)?;
+        let bytes = db.try_read_storage_bytes(&path)?;
+        assert_eq!(sniff_format(&bytes).map(|f| f.name()), expected);
+
+        db.try_write_storage(None::<TestRecord>, &path)?;
+        let bytes = db.try_read_storage_bytes(&path)?;
+        assert_eq!(sniff_format(&bytes).map(|f| f.name()), expected);
+
+        assert_eq!(
+            sniff_format(&[0x28, 0xB5, 0x2F, 0xFD, 0x00]),
+            Some(FormatKind::Zstd)
+        );
+        assert_eq!(
+            sniff_format(&[0x1F, 0x8B, 0x08, 0x00]),
+            Some(FormatKind::Gzip)
+        );
+        assert_eq!(sniff_format(&[0xFF]), None);
+        assert_eq!(sniff_format(&[]), None);
+    }
+
+    {
+        span_and_info!("Read with raw");
+
+        let path = db.file_path("RawRecords");
+        let id = &mut 0;
+        let records = vec![TestRecord::new(id), TestRecord::new(id)];
+        db.try_write_storage(&records, &path)?;
+
+        let (read, bytes) = db.read_with_raw::<Vec<TestRecord>>(&path)?;
+        assert_eq!(read, records);
+        assert_eq!(bytes, db.try_read_storage_bytes(&path)?);
+
+        // The raw bytes round-trip back to the same value
+        let round_trip: Vec<TestRecord> = db.read_storage_from(&mut bytes.as_slice())?;
+        assert_eq!(round_trip, read);
+
+        assert!(matches!(
+            db.read_with_raw::<Vec<TestRecord>>(db.file_path("MissingRecord")),
+            Err(lupabase::Error::DBNotFound { .. })
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::{transactions::*, *};
+use std::error::Error;
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn logging_cbor() -> Result<(), Box<dyn Error>> {
+    logging_tester::<DiskDB<CborSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn logging_json() -> Result<(), Box<dyn Error>> {
+    logging_tester::<DiskDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn logging_memory() -> Result<(), Box<dyn Error>> {
+    logging_tester::<MemoryDB<CborSerde>>()?;
+    logging_tester::<MemoryDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn logging_transactions() -> Result<(), Box<dyn Error>> {
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("logging", "transactions");
+
+    let db = DiskDB::<JsonSerde>::new(working_dir);
+    db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![])?;
+
+    let id = &mut 0_u64;
+    let tx = db.transact()?;
+    tx.try_initialize_storage::<TestRecordPartitioned, _>(db.get_all::<TestRecordPartitioned>()?)?;
+    tx.insert_all([
+        TestRecordPartitioned::new(id),
+        TestRecordPartitioned::new(id),
+    ])?;
+
+    let (result, logs) = capture_logs(|| db.try_commit::<TestRecordPartitioned>(&tx));
+    result?;
+    let line = logs
+        .lines()
+        .find(|line| line.contains("Committed transaction"))
+        .unwrap_or_else(|| panic!("Committing should be logged: {logs}"));
+    assert!(line.contains("INFO"), "Commit should log at info: {line}");
+    assert!(
+        line.contains("records=2"),
+        "Commit should log the count: {line}"
+    );
+    assert!(
+        line.contains(TestRecordPartitioned::PARTITION),
+        "Commit should log the partition: {line}"
+    );
+
+    let (result, logs) = capture_logs(|| db.try_rollback::<TestRecordPartitioned>(&tx));
+    result?;
+    let line = logs
+        .lines()
+        .find(|line| line.contains("Rolled back transaction"))
+        .unwrap_or_else(|| panic!("Rolling back should be logged: {logs}"));
+    assert!(
+        line.contains("records=0"),
+        "Rollback should log the count: {line}"
+    );
+
+    // A single value has no count, and isn't mistaken for a corrupt list
+    let db_file_path = db.file_path("TestRecord");
+    tx.try_initialize_storage_with_path(TestRecord::new(id), &db_file_path)?;
+    let (result, logs) =
+        capture_logs(|| db.try_commit_with_path::<TestRecord>(&tx, &db_file_path, &db_file_path));
+    result?;
+    assert!(
+        logs.contains("Committed transaction"),
+        "Committing should be logged: {logs}"
+    );
+    assert!(
+        !logs.contains("WARN") && !logs.contains("records="),
+        "A single value should be committed without a count: {logs}"
+    );
+    assert!(db.list_backups(&db_file_path)?.is_empty());
+
+    Ok(())
+}
+
+fn logging_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
+    let db_name = &format!("{}-{}", DB::SERDE_FORMAT, DB::NAME);
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("logging", db_name);
+
+    let db = DB::new(working_dir);
+
+    {
+        let (result, logs) = capture_logs(|| {
+            db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![])
+        });
+        result?;
+
+        assert!(
+            logs.contains("Created ["),
+            "Populating should log creation: {logs}"
+        );
+        assert!(
+            !logs.contains("Found ["),
+            "Populating should not log finding: {logs}"
+        );
+    }
+
+    {
+        let (result, logs) = capture_logs(|| {
+            db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![])
+        });
+        result?;
+
+        assert!(
+            logs.contains("Found ["),
+            "Re-initializing should log finding: {logs}"
+        );
+        assert!(
+            !logs.contains("Created ["),
+            "Re-initializing should not log creation: {logs}"
+        );
+    }
+
+    {
+        let id = &mut 0_u64;
+
+        let (result, logs) = capture_logs(|| {
+            db.insert_all([
+                TestRecordPartitioned::new(id),
+                TestRecordPartitioned::new(id),
+            ])
+        });
+        result?;
+
+        let span = logs
+            .lines()
+            .find(|line| line.contains("insert_all_with_path{"))
+            .unwrap_or_else(|| panic!("Inserting should create a span: {logs}"));
+        assert!(
+            span.contains("records=2"),
+            "Span should record the count: {span}"
+        );
+        assert!(
+            span.contains(TestRecordPartitioned::PARTITION),
+            "Span should record the partition: {span}"
+        );
+    }
+
+    {
+        db.try_write_storage(
+            vec![serde_json::json!({ "id": 1, "data": "Newer schema", "extra": true })],
+            db.file_path(TestRecordPartitioned2::PARTITION),
+        )?;
+
+        let (result, logs) = capture_logs(|| db.get_all::<TestRecordPartitioned2>());
+        assert_eq!(result?.len(), 1, "Unknown fields should not fail reading");
+        assert!(
+            !logs.contains("Ignored unknown field"),
+            "Reading should not log ignored fields: {logs}"
+        );
+
+        let (result, logs) = capture_logs(|| db.get_all_lenient::<TestRecordPartitioned2>());
+        assert_eq!(result?[0].data, "Newer schema");
+        assert!(
+            logs.contains("Ignored unknown field [0.extra]"),
+            "Lenient reading should log ignored fields: {logs}"
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,420 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    error::Error,
+    num::NonZero,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn memory_dump_cbor() -> Result<(), Box<dyn Error>> {
+    memory_dump_tester::<CborSerde>()?;
+
+    Ok(())
+}
+
+#[test]
+fn memory_dump_json() -> Result<(), Box<dyn Error>> {
+    memory_dump_tester::<JsonSerde>()?;
+
+    Ok(())
+}
+
+fn memory_dump_tester<S: BytesSerde>() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    span_and_info!("Dump and Load", "{}", S::FORMAT);
+
+    let db = MemoryDB::<S>::new("Test");
+
+    let id = &mut 0_u64;
+    db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![
+        TestRecordPartitioned::new(id),
+        TestRecordPartitioned::new(id),
+    ])?;
+    db.try_initialize_storage::<TestRecordPartitioned2, Vec<TestRecordPartitioned2>>(vec![
+        TestRecordPartitioned2::new(id),
+    ])?;
+    db.try_initialize_storage_with_path(TestRecord::new(id), db.file_path("TestRecord"))?;
+
+    let blob = db.dump()?;
+
+    let loaded_db = MemoryDB::<S>::new("Test");
+    loaded_db.load(&blob)?;
+
+    assert!(db.content_eq(&loaded_db));
+    assert!(databases_equal::<_, _, TestRecordPartitioned>(
+        &db, &loaded_db
+    )?);
+
+    let mut partitions = db.list_partitions()?;
+    let mut loaded_partitions = loaded_db.list_partitions()?;
+    partitions.sort();
+    loaded_partitions.sort();
+    assert_eq!(partitions, loaded_partitions);
+
+    assert_eq!(
+        db.get_all::<TestRecordPartitioned>()?,
+        loaded_db.get_all::<TestRecordPartitioned>()?
+    );
+    assert_eq!(
+        db.get_all::<TestRecordPartitioned2>()?,
+        loaded_db.get_all::<TestRecordPartitioned2>()?
+    );
+    assert_eq!(
+        db.try_read_storage::<TestRecord>(db.file_path("TestRecord"))?,
+        loaded_db.try_read_storage::<TestRecord>(loaded_db.file_path("TestRecord"))?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn memory_content_eq() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("memory", "content-eq");
+
+    let db = MemoryDB::<CborSerde>::new("Test");
+    let other_db = MemoryDB::<CborSerde>::new("Test");
+    let disk_db = DiskDB::<JsonSerde>::new(working_dir);
+
+    let records = vec![
+        TestRecordPartitioned::new(&mut 0),
+        TestRecordPartitioned::new(&mut 1),
+    ];
+    db.try_initialize_storage::<TestRecordPartitioned, _>(records.clone())?;
+    disk_db.try_initialize_storage::<TestRecordPartitioned, _>(records.clone())?;
+
+    assert!(db.content_eq(&db.clone()));
+    assert!(!db.content_eq(&other_db));
+    assert!(databases_equal::<_, _, TestRecordPartitioned>(
+        &db, &disk_db
+    )?);
+
+    other_db.try_initialize_storage::<TestRecordPartitioned, _>(records)?;
+    assert!(db.content_eq(&other_db));
+
+    other_db.update(TestRecordPartitioned {
+        data: String::from("Data has been updated!"),
+        ..TestRecordPartitioned::new(&mut 0)
+    })?;
+    assert!(!db.content_eq(&other_db));
+    assert!(!databases_equal::<_, _, TestRecordPartitioned>(
+        &db, &other_db
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn memory_stable_ordering() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    span_and_info!("Stable ordering");
+
+    let partitions = (0..32)
+        .map(|i| format!("TestRecords{i:02}"))
+        .collect::<Vec<_>>();
+
+    let db = MemoryDB::<CborSerde>::new("Test");
+    // Initialized out of order
+    for partition in partitions.iter().rev() {
+        db.try_initialize_storage_with_path(
+            vec![TestRecord::new(&mut 0)],
+            db.file_path(partition),
+        )?;
+    }
+
+    for _ in 0..8 {
+        assert_eq!(db.list_partitions()?, partitions);
+    }
+
+    // The same content initialized in another order produces the same dump
+    let other_db = MemoryDB::<CborSerde>::new("Test");
+    for partition in &partitions {
+        other_db.try_initialize_storage_with_path(
+            vec![TestRecord::new(&mut 0)],
+            other_db.file_path(partition),
+        )?;
+    }
+    assert_eq!(db.dump()?, other_db.dump()?);
+
+    Ok(())
+}
+
+#[test]
+fn memory_concurrent_partitions() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    span_and_info!("Concurrent partitions");
+
+    const THREADS: u64 = 8;
+    const RECORDS: u64 = 200;
+
+    let db = MemoryDB::<CborSerde>::new("Test");
+
+    thread::scope(|scope| {
+        let handles = (0..THREADS)
+            .map(|thread| {
+                let db = db.clone();
+                scope.spawn(move || {
+                    let path = db.file_path(format!("TestRecords{thread}"));
+                    db.try_initialize_storage_with_path(vec![] as Vec<TestRecord>, &path)?;
+
+                    let id = &mut 0_u64;
+                    for _ in 0..RECORDS {
+                        db.insert_all_with_path([TestRecord::new(id)], &path)?;
+                    }
+
+                    lupabase::Result::Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().expect("Writer thread should not panic"))
+    })?;
+
+    // Every thread wrote its own partition, so none of their updates may be lost
+    assert_eq!(db.list_partitions()?.len(), THREADS as usize);
+    for thread in 0..THREADS {
+        let records =
+            db.get_all_with_path::<TestRecord>(db.file_path(format!("TestRecords{thread}")))?;
+        assert_eq!(records.len(), RECORDS as usize);
+    }
+
+    Ok(())
+}
+
+static PARSED_RECORDS: AtomicUsize = AtomicUsize::new(0);
+
+fn counted_parse<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NonZero<u64>, D::Error> {
+    PARSED_RECORDS.fetch_add(1, Ordering::Relaxed);
+    NonZero::deserialize(deserializer)
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestRecordCounted {
+    #[serde(deserialize_with = "counted_parse")]
+    pub id: NonZero<u64>,
+}
+
+impl DatabaseRecord for TestRecordCounted {
+    type Unique = NonZero<u64>;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
+impl DatabaseRecordPartitioned for TestRecordCounted {
+    const PARTITION: &str = "TestRecordCounted";
+}
+
+#[test]
+fn memory_typed_store() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    span_and_info!("Typed store");
+
+    let db = MemoryDB::<CborSerde>::new("Test");
+
+    let records = (1..=3)
+        .map(|id| TestRecordCounted {
+            id: NonZero::new(id).expect("ID should not be Zero"),
+        })
+        .collect::<Vec<_>>();
+    db.try_initialize_storage::<TestRecordCounted, _>(records.clone())?;
+
+    let parsed_before = PARSED_RECORDS.load(Ordering::Relaxed);
+    assert_eq!(db.get_all_cached::<TestRecordCounted>()?, records);
+    assert_eq!(PARSED_RECORDS.load(Ordering::Relaxed), parsed_before + 3);
+
+    // Repeated reads clone the stored records instead of deserializing again
+    assert_eq!(db.get_all_cached::<TestRecordCounted>()?, records);
+    assert_eq!(db.get_all_cached::<TestRecordCounted>()?, records);
+    assert_eq!(PARSED_RECORDS.load(Ordering::Relaxed), parsed_before + 3);
+
+    // Reading as another type deserializes, without evicting the stored records
+    let alternate_records = db.get_all_cached_with_path::<TestRecordAlternate>(
+        db.file_path(TestRecordCounted::PARTITION),
+    )?;
+    assert_eq!(alternate_records.len(), 3);
+    assert_eq!(db.get_all_cached::<TestRecordCounted>()?, records);
+    assert_eq!(PARSED_RECORDS.load(Ordering::Relaxed), parsed_before + 3);
+
+    // Loading a dump replaces the raw bytes, discarding the stored records
+    db.load(&db.dump()?)?;
+    let parsed_before = PARSED_RECORDS.load(Ordering::Relaxed);
+    assert_eq!(db.get_all_cached::<TestRecordCounted>()?, records);
+    assert_eq!(PARSED_RECORDS.load(Ordering::Relaxed), parsed_before + 3);
+
+    // Writing records discards the stored records
+    db.insert(TestRecordCounted {
+        id: NonZero::new(4).expect("ID should not be Zero"),
+    })?;
+    let parsed_before = PARSED_RECORDS.load(Ordering::Relaxed);
+    assert_eq!(db.get_all_cached::<TestRecordCounted>()?.len(), 4);
+    assert_eq!(PARSED_RECORDS.load(Ordering::Relaxed), parsed_before + 4);
+
+    Ok(())
+}
+
+#[test]
+fn memory_capacity() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    span_and_info!("Max entries");
+
+    let db = MemoryDB::<JsonSerde>::new("Test").with_max_entries(3);
+    let write = |partition: &str| {
+        return db.try_write_storage(vec![TestRecord::new(&mut 0)], db.file_path(partition));
+    };
+
+    write("Oldest")?;
+    write("Older")?;
+    write("Newer")?;
+    assert_eq!(db.list_partitions()?, vec!["Newer", "Older", "Oldest"]);
+
+    // Reading marks the oldest partition as recently used, so the next oldest is evicted instead
+    let _ = db.get_all_by_name::<TestRecord>("Oldest")?;
+    write("Newest")?;
+    assert_eq!(db.list_partitions()?, vec!["Newer", "Newest", "Oldest"]);
+
+    write("Latest")?;
+    assert_eq!(db.list_partitions()?, vec!["Latest", "Newest", "Oldest"]);
+    assert!(!db.storage_exists(db.file_path("Newer"))?);
+
+    // Removed storages free their slot
+    db.try_remove_storage(db.file_path("Latest"))?;
+    write("Replacement")?;
+    assert_eq!(
+        db.list_partitions()?,
+        vec!["Newest", "Oldest", "Replacement"]
+    );
+
+    span_and_info!("Max bytes");
+
+    let db = MemoryDB::<JsonSerde>::new("Test");
+    let path = |partition: &str| Path::new("Test").join(format!("{partition}.json"));
+    db.try_write_storage(vec![0_u8; 10], path("First"))?;
+    db.try_write_storage(vec![0_u8; 10], path("Second"))?;
+    let size = db.storage_size(path("First"))?;
+
+    // Existing storages are tracked once limited
+    let db = db.with_max_bytes(size * 2);
+    assert_eq!(db.list_partitions()?, vec!["First", "Second"]);
+
+    db.try_write_storage(vec![0_u8; 10], path("Third"))?;
+    assert_eq!(db.list_partitions()?, vec!["Second", "Third"]);
+
+    // A storage larger than the limit evicts everything else, but is kept itself
+    db.try_write_storage(vec![0_u8; 100], path("Large"))?;
+    assert_eq!(db.list_partitions()?, vec!["Large"]);
+
+    span_and_info!("Concurrent eviction");
+
+    const THREADS: usize = 8;
+    const WRITES: usize = 200;
+
+    let db = MemoryDB::<CborSerde>::new("Test").with_max_entries(4);
+    thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let db = db.clone();
+            let _ = scope.spawn(move || {
+                for write in 0..WRITES {
+                    let path = db.file_path(format!("Concurrent{}", (thread + write) % 6));
+                    db.try_write_storage(vec![TestRecord::new(&mut 0)], &path)
+                        .expect("Write should succeed");
+                }
+            });
+        }
+    });
+    assert!(db.list_partitions()?.len() <= 4);
+
+    // Every stored partition is still tracked exactly once, so fresh partitions evict all of them
+    for fresh in 0..4 {
+        db.try_write_storage(
+            vec![TestRecord::new(&mut 0)],
+            db.file_path(format!("Fresh{fresh}")),
+        )?;
+    }
+    assert_eq!(
+        db.list_partitions()?,
+        vec!["Fresh0", "Fresh1", "Fresh2", "Fresh3"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn memory_version() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let db = MemoryDB::<JsonSerde>::new("Version");
+    let path = db.file_path(TestRecordPartitioned::PARTITION);
+    let other_path = db.file_path("Other");
+
+    db.try_write_storage(Vec::<TestRecordPartitioned>::new(), &path)?;
+    db.try_write_storage(Vec::<TestRecordPartitioned>::new(), &other_path)?;
+    db.try_write_storage(Vec::<TestRecordPartitioned>::new(), &other_path)?;
+    assert_eq!(db.version(&path)?, 1);
+
+    // Moving or copying onto a storage bumps its version, past the source's
+    db.try_copy_storage(&other_path, &path)?;
+    assert_eq!(db.version(&path)?, 3);
+    db.try_move_storage(&path, &other_path)?;
+    assert_eq!(db.version(&other_path)?, 4);
+
+    Ok(())
+}
+
+#[test]
+fn memory_touch() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    span_and_info!("Touch");
+
+    let db = MemoryDB::<CborSerde>::new("Test");
+    let id = &mut 0;
+    let (first, second) = (
+        TestRecordPartitioned::new(id),
+        TestRecordPartitioned::new(id),
+    );
+    db.try_initialize_storage::<TestRecordPartitioned, _>(vec![first.clone()])?;
+
+    // The mutation may use the database, a write to the partition fails the touch instead of being lost
+    let result = db.touch::<TestRecordPartitioned, _>(&first.id, |record| {
+        assert_eq!(
+            db.get_all::<TestRecordPartitioned>()
+                .expect("Reading within the mutation should succeed"),
+            vec![record.clone()]
+        );
+        db.insert(second.clone())
+            .expect("Writing within the mutation should succeed");
+
+        record.data = String::from("Touched");
+    });
+    assert!(matches!(
+        result,
+        Err(lupabase::Error::DBTransactionConflict { .. })
+    ));
+    assert_eq!(
+        db.get_all::<TestRecordPartitioned>()?,
+        vec![first.clone(), second]
+    );
+
+    db.touch::<TestRecordPartitioned, _>(&first.id, |record| {
+        record.data = String::from("Touched")
+    })?;
+    assert_eq!(db.get_all::<TestRecordPartitioned>()?[0].data, "Touched");
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "metrics")]
+
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use std::error::Error;
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn metrics_insert() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("metrics", "insert");
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir);
+    db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![])?;
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+
+    metrics::with_local_recorder(&recorder, || db.insert(TestRecordPartitioned::new(&mut 0)))?;
+
+    let inserts = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .filter(|(key, ..)| key.key().name() == "lupabase_operations_total")
+        .filter(|(key, ..)| {
+            key.key()
+                .labels()
+                .any(|label| label.key() == "operation" && label.value() == "insert_all")
+                && key.key().labels().any(|label| {
+                    label.key() == "partition" && label.value() == TestRecordPartitioned::PARTITION
+                })
+        })
+        .map(|(.., value)| value)
+        .collect::<Vec<_>>();
+
+    assert!(matches!(inserts.as_slice(), [DebugValue::Counter(1)]));
+
+    Ok(())
+}
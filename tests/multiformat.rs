@@ -0,0 +1,139 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::{transactions::*, *};
+use std::{error::Error, fs};
+use tests_records::*;
+use tests_utils::*;
+
+type CborJsonDB = MultiFormatDB<DiskDB<CborSerde>, DiskDB<JsonSerde>>;
+
+#[test]
+fn multiformat() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("multiformat", "cbor-json");
+
+    let db = CborJsonDB::new(&working_dir);
+
+    let cbor_file_path = working_dir.join("TestRecordPartitioned.cbor");
+    let json_file_path = working_dir.join("TestRecordPartitioned.json");
+
+    let id = &mut 0_u64;
+
+    {
+        span_and_info!("Operation", "Insert");
+
+        db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(vec![])?;
+        db.insert_all([
+            TestRecordPartitioned::new(id),
+            TestRecordPartitioned::new(id),
+        ])?;
+
+        assert!(cbor_file_path.is_file());
+        assert!(json_file_path.is_file());
+
+        let records = db.get_all::<TestRecordPartitioned>()?;
+        assert_eq!(records.len(), 2);
+        assert!(db.preferred().get_all::<TestRecordPartitioned>()? == records);
+        assert!(db.fallback().get_all::<TestRecordPartitioned>()? == records);
+    }
+
+    {
+        span_and_info!("Operation", "Read preference");
+
+        let records = db.get_all::<TestRecordPartitioned>()?;
+
+        // Reads should come from CBOR, leaving the JSON storage untouched
+        fs::write(&json_file_path, "[]")?;
+        assert!(db.get_all::<TestRecordPartitioned>()? == records);
+
+        // Reads should fall back to JSON when CBOR is missing
+        fs::remove_file(&cbor_file_path)?;
+        assert!(db.get_all::<TestRecordPartitioned>()?.is_empty());
+        assert_eq!(
+            db.storage_size(&cbor_file_path)?,
+            fs::metadata(&json_file_path)?.len()
+        );
+    }
+
+    {
+        span_and_info!("Transaction", "Commit");
+
+        db.replace_all::<TestRecordPartitioned>([])?;
+
+        let tx = db.transact()?;
+        let tx_file_path = tx.file_path(TestRecordPartitioned::PARTITION);
+        tx.try_initialize_storage_with_path(vec![TestRecordPartitioned::new(id)], &tx_file_path)?;
+
+        db.try_commit_with_path::<Vec<TestRecordPartitioned>>(
+            &tx,
+            &tx_file_path,
+            db.file_path(TestRecordPartitioned::PARTITION),
+        )?;
+
+        let records = tx.get_all_with_path::<TestRecordPartitioned>(&tx_file_path)?;
+        assert!(db.preferred().get_all::<TestRecordPartitioned>()? == records);
+        assert!(db.fallback().get_all::<TestRecordPartitioned>()? == records);
+    }
+
+    {
+        span_and_info!("Operation", "Fallback write failure");
+
+        let records = db.get_all::<TestRecordPartitioned>()?;
+        let cbor_bytes = fs::read(&cbor_file_path)?;
+
+        // A directory in place of the JSON storage can't be written
+        fs::remove_file(&json_file_path)?;
+        fs::create_dir(&json_file_path)?;
+
+        assert!(db.insert(TestRecordPartitioned::new(id)).is_err());
+        assert!(
+            db.compare_and_write(
+                Some(&cbor_bytes),
+                Vec::<TestRecordPartitioned>::new(),
+                &cbor_file_path
+            )
+            .is_err()
+        );
+
+        // The preferred storage should be restored, without leaving its backup behind
+        assert_eq!(fs::read(&cbor_file_path)?, cbor_bytes);
+        assert!(db.get_all::<TestRecordPartitioned>()? == records);
+
+        // A preferred storage which didn't exist should be removed again
+        let missing_cbor_file_path = working_dir.join("Missing.cbor");
+        fs::create_dir(working_dir.join("Missing.json"))?;
+        assert!(
+            db.try_write_storage(Vec::<TestRecordPartitioned>::new(), &missing_cbor_file_path)
+                .is_err()
+        );
+        assert!(!missing_cbor_file_path.exists());
+
+        // Copying, moving and removing should restore the preferred storages too
+        let copy_cbor_file_path = working_dir.join("Copy.cbor");
+        assert!(
+            db.try_copy_storage(&cbor_file_path, &copy_cbor_file_path)
+                .is_err()
+        );
+        assert!(!copy_cbor_file_path.exists());
+
+        // A non-empty directory in place of the JSON destination can't be replaced
+        let moved_cbor_file_path = working_dir.join("Moved.cbor");
+        fs::create_dir(working_dir.join("Moved.json"))?;
+        fs::write(working_dir.join("Moved.json").join("Blocker"), "")?;
+        assert!(
+            db.try_move_storage(&cbor_file_path, &moved_cbor_file_path)
+                .is_err()
+        );
+        assert!(!moved_cbor_file_path.exists());
+        assert_eq!(fs::read(&cbor_file_path)?, cbor_bytes);
+
+        assert!(db.try_remove_storage(&cbor_file_path).is_err());
+        assert_eq!(fs::read(&cbor_file_path)?, cbor_bytes);
+
+        assert!(db.preferred().list_backups(&cbor_file_path)?.is_empty());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use std::error::Error;
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn namespaces_cbor() -> Result<(), Box<dyn Error>> {
+    namespaces_tester::<DiskDB<CborSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn namespaces_json() -> Result<(), Box<dyn Error>> {
+    namespaces_tester::<DiskDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn namespaces_memory() -> Result<(), Box<dyn Error>> {
+    namespaces_tester::<MemoryDB<CborSerde>>()?;
+    namespaces_tester::<MemoryDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+fn namespaces_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let db_name = &format!("{}-{}", DB::SERDE_FORMAT, DB::NAME);
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("namespaces", db_name);
+
+    let db = DB::new(working_dir);
+
+    {
+        span_and_info!("Namespaces", "{db_name}");
+
+        let tenant_a = db.with_namespace("TenantA");
+        let tenant_b = db.with_namespace("TenantB");
+        assert_eq!(tenant_a.dir(), db.dir().join("TenantA"));
+
+        let id = &mut 0;
+        let records_a = vec![TestRecordPartitioned::new(id)];
+        let records_b = vec![
+            TestRecordPartitioned::new(id),
+            TestRecordPartitioned::new(id),
+        ];
+        tenant_a.try_initialize_storage::<TestRecordPartitioned, _>(records_a.clone())?;
+        tenant_b.try_initialize_storage::<TestRecordPartitioned, _>(records_b.clone())?;
+
+        assert_eq!(tenant_a.get_all::<TestRecordPartitioned>()?, records_a);
+        assert_eq!(tenant_b.get_all::<TestRecordPartitioned>()?, records_b);
+
+        // Namespaced partitions are nested, not part of the base directory
+        assert!(db.list_partitions()?.is_empty());
+        assert_eq!(
+            tenant_a.list_partitions()?,
+            vec![TestRecordPartitioned::PARTITION]
+        );
+
+        // The same namespace resolves to the same storage
+        assert_eq!(
+            db.with_namespace("TenantA")
+                .get_all::<TestRecordPartitioned>()?,
+            records_a
+        );
+    }
+
+    Ok(())
+}
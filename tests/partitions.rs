@@ -0,0 +1,230 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, error::Error};
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn partitions_cbor() -> Result<(), Box<dyn Error>> {
+    partitions_tester::<DiskDB<CborSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn partitions_json() -> Result<(), Box<dyn Error>> {
+    partitions_tester::<DiskDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn partitions_memory() -> Result<(), Box<dyn Error>> {
+    partitions_tester::<MemoryDB<CborSerde>>()?;
+    partitions_tester::<MemoryDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+fn partitions_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let db_name = &format!("{}-{}", DB::SERDE_FORMAT, DB::NAME);
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("partitions", db_name);
+
+    let db = DB::new(working_dir);
+
+    {
+        span_and_info!("Partition stats");
+
+        assert!(db.partition_stats()?.is_empty());
+
+        let mut id = 0;
+        db.try_initialize_storage::<TestRecordPartitioned, Vec<TestRecordPartitioned>>(
+            (0..3)
+                .map(|_| TestRecordPartitioned::new(&mut id))
+                .collect(),
+        )?;
+        db.try_initialize_storage::<TestRecordPartitioned2, Vec<TestRecordPartitioned2>>(vec![])?;
+        db.try_initialize_storage::<TestRecordPartitioned3, Vec<TestRecordPartitioned3>>(
+            (0..5)
+                .map(|_| TestRecordPartitioned3::new(&mut id))
+                .collect(),
+        )?;
+
+        // Backups are not partitions
+        db.try_backup_storage(db.file_path(TestRecordPartitioned::PARTITION), "Manual")?;
+
+        assert_eq!(
+            db.partition_stats()?,
+            BTreeMap::from([
+                (TestRecordPartitioned::PARTITION.to_string(), 3),
+                (TestRecordPartitioned2::PARTITION.to_string(), 0),
+                (TestRecordPartitioned3::PARTITION.to_string(), 5),
+            ])
+        );
+    }
+
+    {
+        span_and_info!("Invalid partition names");
+
+        let invalid_partition = |result: lupabase::Result<_>, partition: &str| {
+            return matches!(
+                result,
+                Err(lupabase::Error::DBInvalidPartition { partition: p }) if p == partition
+            );
+        };
+
+        let partitions_before = db.list_partitions()?;
+
+        assert!(invalid_partition(
+            db.try_initialize_storage::<TestRecordUnnamed, Vec<TestRecordUnnamed>>(vec![]),
+            ""
+        ));
+        assert!(invalid_partition(
+            db.insert(TestRecordUnnamed { id: 1 }),
+            ""
+        ));
+        assert!(invalid_partition(
+            db.get_all::<TestRecordUnnamed>().map(drop),
+            ""
+        ));
+        assert!(invalid_partition(
+            db.insert(TestRecordNested { id: 1 }),
+            "Nested/Records"
+        ));
+
+        // Names resolving to the database's directory, its parent or a sibling of it
+        for partition in [".", "..", "./Records", "Records/."] {
+            assert!(invalid_partition(
+                validate_partition_name(partition),
+                partition
+            ));
+            assert!(invalid_partition(
+                db.clone_partition(TestRecordPartitioned::PARTITION, partition),
+                partition
+            ));
+        }
+        assert!(validate_partition_name("Records.2024").is_ok());
+
+        // Nothing was created for the invalid partitions
+        assert_eq!(db.list_partitions()?, partitions_before);
+    }
+
+    {
+        span_and_info!("Partition id");
+
+        let partition = PartitionId::<TestRecordPartitioned>::new();
+        assert_eq!(partition.to_string(), TestRecordPartitioned::PARTITION);
+        assert_eq!(
+            db.file_path(partition),
+            db.partition_path::<TestRecordPartitioned>()?
+        );
+        assert_eq!(
+            db.get_all_by_name::<TestRecordPartitioned>(partition)?,
+            db.get_all::<TestRecordPartitioned>()?
+        );
+    }
+
+    {
+        span_and_info!("Is empty");
+
+        // Missing partition
+        assert!(db.is_empty::<TestRecordUninitialized>()?);
+        assert!(!db.storage_exists(db.partition_path::<TestRecordUninitialized>()?)?);
+
+        // Empty partition
+        db.try_initialize_storage::<TestRecordUninitialized, Vec<TestRecordUninitialized>>(vec![])?;
+        assert!(db.is_empty::<TestRecordUninitialized>()?);
+        assert!(db.is_empty::<TestRecordPartitioned2>()?);
+
+        // Populated partition
+        db.insert(TestRecordUninitialized { id: 1 })?;
+        assert!(!db.is_empty::<TestRecordUninitialized>()?);
+        assert!(!db.is_empty::<TestRecordPartitioned>()?);
+    }
+
+    {
+        span_and_info!("Clone partition");
+
+        let original = TestRecordPartitioned::PARTITION;
+        let records = db.get_all::<TestRecordPartitioned>()?;
+
+        db.clone_partition(original, "ClonedPartition")?;
+        assert!(db.get_all_as::<TestRecordPartitioned>("ClonedPartition")? == records);
+
+        // The clone is independent of the original
+        let mut id = 10_000;
+        db.insert(TestRecordPartitioned::new(&mut id))?;
+        assert_eq!(
+            db.get_all::<TestRecordPartitioned>()?.len(),
+            records.len() + 1
+        );
+        assert!(db.get_all_as::<TestRecordPartitioned>("ClonedPartition")? == records);
+
+        assert!(matches!(
+            db.clone_partition(original, "ClonedPartition"),
+            Err(lupabase::Error::DBOperationFailure { .. })
+        ));
+        assert!(matches!(
+            db.clone_partition(original, "Cloned/Partition"),
+            Err(lupabase::Error::DBInvalidPartition { .. })
+        ));
+        assert!(
+            db.clone_partition("MissingPartition", "ClonedMissingPartition")
+                .is_err(),
+            "Cloning a missing partition should fail"
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TestRecordUnnamed {
+    pub id: u64,
+}
+
+impl DatabaseRecord for TestRecordUnnamed {
+    type Unique = u64;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
+impl DatabaseRecordPartitioned for TestRecordUnnamed {
+    const PARTITION: &str = "";
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TestRecordNested {
+    pub id: u64,
+}
+
+impl DatabaseRecord for TestRecordNested {
+    type Unique = u64;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
+impl DatabaseRecordPartitioned for TestRecordNested {
+    const PARTITION: &str = "Nested/Records";
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TestRecordUninitialized {
+    pub id: u64,
+}
+
+impl DatabaseRecord for TestRecordUninitialized {
+    type Unique = u64;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
+impl DatabaseRecordPartitioned for TestRecordUninitialized {
+    const PARTITION: &str = "TestRecordUninitialized";
+}
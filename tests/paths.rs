@@ -0,0 +1,76 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use std::{error::Error, path::Path};
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn paths_cbor() -> Result<(), Box<dyn Error>> {
+    paths_tester::<DiskDB<CborSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn paths_json() -> Result<(), Box<dyn Error>> {
+    paths_tester::<DiskDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn paths_memory() -> Result<(), Box<dyn Error>> {
+    paths_tester::<MemoryDB<CborSerde>>()?;
+    paths_tester::<MemoryDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+fn paths_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let db_name = &format!("{}-{}", DB::SERDE_FORMAT, DB::NAME);
+    let extension = DB::EXTENSION;
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("paths", db_name);
+
+    let db = DB::new(&working_dir);
+
+    {
+        span_and_info!("File path");
+
+        assert_eq!(
+            db.file_path("TestRecords"),
+            working_dir.join(format!("TestRecords.{extension}"))
+        );
+        assert_eq!(
+            db.file_path("TestRecords.v2"),
+            working_dir.join(format!("TestRecords.v2.{extension}"))
+        );
+        assert_eq!(
+            db.file_path(Path::new("archive").join("TestRecords.tar")),
+            working_dir.join(format!("archive/TestRecords.tar.{extension}"))
+        );
+    }
+
+    {
+        span_and_info!("Backup path");
+
+        let db_file_path = db.file_path("TestRecord");
+        db.try_initialize_storage_with_path(TestRecord::new(&mut 0), &db_file_path)?;
+
+        let backup_path = db.try_backup_storage(&db_file_path, "Manual")?;
+        let backup_file_name = backup_path
+            .file_name()
+            .expect("Backup should have a file name")
+            .to_string_lossy();
+
+        assert_eq!(backup_path.parent(), Some(working_dir.as_path()));
+        assert!(backup_file_name.starts_with(&format!("TestRecord.{extension}.")));
+        assert!(backup_file_name.ends_with("-Manual.bak"));
+    }
+
+    Ok(())
+}
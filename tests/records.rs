@@ -0,0 +1,224 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::{prelude::*, record::utils::DatabaseRecordsUtils};
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    num::NonZero,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tests_records::*;
+use tests_utils::*;
+
+static UNIQUE_VALUE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestRecordString {
+    pub id: String,
+}
+
+impl DatabaseRecord for TestRecordString {
+    type Unique = String;
+
+    fn unique_value(&self) -> Self::Unique {
+        UNIQUE_VALUE_CALLS.fetch_add(1, Ordering::Relaxed);
+        self.id.clone()
+    }
+
+    fn unique_ref(&self) -> Option<&Self::Unique> { Some(&self.id) }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestRecordStringOwned {
+    pub id: String,
+}
+
+impl DatabaseRecord for TestRecordStringOwned {
+    type Unique = String;
+
+    fn unique_value(&self) -> Self::Unique { self.id.clone() }
+}
+
+#[test]
+fn records_unique_ref() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    span_and_info!("Unique ref", "String uniques");
+
+    const RECORDS_COUNT: usize = 10_000;
+
+    let records = (0..RECORDS_COUNT)
+        .map(|i| TestRecordString {
+            id: format!("record-{i}"),
+        })
+        .collect::<Vec<_>>();
+    let records_owned = records
+        .iter()
+        .map(|r| TestRecordStringOwned { id: r.id.clone() })
+        .collect::<Vec<_>>();
+
+    for i in (0..RECORDS_COUNT).step_by(1_000) {
+        let unique = format!("record-{i}");
+
+        let found = records.find_by_unique(&unique).map(|r| &r.id);
+        let found_owned = records_owned.find_by_unique(&unique).map(|r| &r.id);
+        assert_eq!(found, found_owned);
+        assert_eq!(found, Some(&unique));
+    }
+    assert_eq!(records.find_by_unique(&String::from("missing")), None);
+
+    assert_eq!(
+        UNIQUE_VALUE_CALLS.load(Ordering::Relaxed),
+        0,
+        "Lookups should borrow through unique_ref instead of cloning with unique_value"
+    );
+
+    // Checking for duplicates borrows as well
+    let db = MemoryDB::<JsonSerde>::new("RecordsUniqueRef").with_strict_reads(true);
+    let db_file_path = db.file_path("TestRecordString");
+    db.try_write_storage(&records, &db_file_path)?;
+    assert_eq!(
+        db.get_all_with_path::<TestRecordString>(&db_file_path)?
+            .len(),
+        RECORDS_COUNT
+    );
+    db.insert_all_with_path(
+        [TestRecordString {
+            id: String::from("new"),
+        }],
+        &db_file_path,
+    )?;
+    assert_eq!(UNIQUE_VALUE_CALLS.load(Ordering::Relaxed), 0);
+
+    // Indexing only computes the unique value of the first record sharing it
+    let duplicates = vec![records[0].clone(); 100];
+    let _ = Records::new(duplicates.clone());
+    let _ = duplicates.index_positions();
+    let _ = duplicates.as_map();
+    assert_eq!(UNIQUE_VALUE_CALLS.load(Ordering::Relaxed), 3);
+
+    Ok(())
+}
+
+#[test]
+fn records_index_positions() {
+    init_tracing_for_tests();
+
+    span_and_info!("Index positions");
+
+    let records = (0..1_000)
+        .map(|i| TestRecordStringOwned {
+            id: format!("record-{}", i % 900),
+        })
+        .collect::<Vec<_>>();
+
+    let positions = records.index_positions();
+    assert_eq!(positions.len(), 900);
+
+    for (unique, position) in &positions {
+        assert_eq!(&records[*position].id, unique);
+        // The last of the duplicates wins
+        assert_eq!(
+            records.iter().rposition(|r| &r.id == unique),
+            Some(*position)
+        );
+    }
+}
+
+#[test]
+fn records_indexed() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    span_and_info!("Indexed");
+
+    let db = MemoryDB::<CborSerde>::new("records-indexed");
+
+    let id = &mut 0;
+    let records = (0..1_000)
+        .map(|_| TestRecordPartitioned::new(id))
+        .collect::<Vec<_>>();
+    db.try_initialize_storage::<TestRecordPartitioned, _>(records.clone())?;
+
+    let indexed = db.get_all_indexed::<TestRecordPartitioned>()?;
+    assert!(*indexed == *records);
+
+    for i in 0..2_000 {
+        let Some(unique) = NonZero::new(i) else {
+            continue;
+        };
+
+        assert_eq!(indexed.get(&unique), records.find_by_unique(&unique));
+        assert_eq!(
+            indexed.contains(&unique),
+            records.find_by_unique(&unique).is_some()
+        );
+    }
+
+    // The first of the duplicates wins, as with a linear scan
+    let duplicates = (0..1_000)
+        .map(|i| TestRecordStringOwned {
+            id: format!("record-{}", i % 900),
+        })
+        .collect::<Vec<_>>();
+    let indexed = Records::new(duplicates.clone());
+    for record in &duplicates {
+        assert!(std::ptr::eq(
+            indexed.get(&record.id).expect("Record should be indexed"),
+            indexed
+                .find_by_unique(&record.id)
+                .expect("Record should be found")
+        ));
+    }
+    assert!(indexed.into_inner() == duplicates);
+
+    Ok(())
+}
+
+#[test]
+fn records_duplicates_message() -> Result<(), Box<dyn Error>> {
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("records", "duplicates-message");
+
+    records_duplicates_message_tester(DiskDB::<JsonSerde>::new(&working_dir), false)?;
+    records_duplicates_message_tester(MemoryDB::<CborSerde>::new(&working_dir), false)?;
+
+    records_duplicates_message_tester(
+        DiskDB::<JsonSerde>::new(working_dir.join("verbose")).with_verbose_errors(true),
+        true,
+    )?;
+
+    Ok(())
+}
+
+fn records_duplicates_message_tester<DB: Database>(
+    db: DB,
+    verbose: bool,
+) -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    span_and_info!("Duplicates message", "{}-{}", DB::SERDE_FORMAT, DB::NAME);
+
+    const RECORDS_COUNT: usize = 100;
+
+    let id = &mut 0;
+    let records = (0..RECORDS_COUNT)
+        .map(|_| TestRecordPartitioned::new(id))
+        .collect::<Vec<_>>();
+    db.try_initialize_storage::<TestRecordPartitioned, _>(records.clone())?;
+
+    let message = db
+        .insert_all(records)
+        .expect_err("Inserting duplicates should fail")
+        .to_string();
+
+    if verbose {
+        // Every duplicate is listed
+        assert!(message.contains(&format!("{RECORDS_COUNT},")));
+    } else {
+        assert!(message.len() < 1_000, "Message is too long: [{message}]");
+        assert!(message.contains(&format!("and {} more", RECORDS_COUNT - 10)));
+    }
+
+    Ok(())
+}
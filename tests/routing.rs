@@ -0,0 +1,225 @@
+pub mod tests_utils;
+
+use lupabase::{prelude::*, record::utils::DatabaseRecordsUtils};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use tests_utils::*;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestRecordRouted {
+    pub id: u64,
+    pub month: String,
+}
+
+impl DatabaseRecord for TestRecordRouted {
+    type Unique = u64;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
+impl DatabaseRecordPartitioned for TestRecordRouted {
+    const PARTITION: &str = "TestRecordRouted";
+    const ROUTED: bool = true;
+
+    fn route(&self) -> String { Self::routed_partition(&self.month) }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestRecordMisrouted {
+    pub id: u64,
+    pub route: String,
+}
+
+impl DatabaseRecord for TestRecordMisrouted {
+    type Unique = u64;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
+impl DatabaseRecordPartitioned for TestRecordMisrouted {
+    const PARTITION: &str = "TestRecordMisrouted";
+    const ROUTED: bool = true;
+
+    fn route(&self) -> String { self.route.clone() }
+}
+
+#[test]
+fn routing_cbor() -> Result<(), Box<dyn Error>> {
+    routing_tester::<DiskDB<CborSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn routing_json() -> Result<(), Box<dyn Error>> {
+    routing_tester::<DiskDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn routing_memory() -> Result<(), Box<dyn Error>> {
+    routing_tester::<MemoryDB<CborSerde>>()?;
+    routing_tester::<MemoryDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+fn routing_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let db_name = &format!("{}-{}", DB::SERDE_FORMAT, DB::NAME);
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("routing", db_name);
+
+    let db = DB::new(working_dir);
+
+    let record = |id: u64, month: &str| TestRecordRouted {
+        id,
+        month: month.to_string(),
+    };
+
+    {
+        span_and_info!("Insert");
+
+        db.insert_all([
+            record(1, "2024-02"),
+            record(2, "2024-01"),
+            record(3, "2024-02"),
+        ])?;
+        db.insert(record(4, "2024-01"))?;
+
+        // Missing routed partitions are created by both insert APIs
+        assert_eq!(db.try_insert(record(5, "2024-03"))?, Ok(()));
+        assert_eq!(
+            db.try_insert(record(5, "2024-03"))?,
+            Err(record(5, "2024-03"))
+        );
+        assert_eq!(
+            db.get_all_by_name::<TestRecordRouted>("TestRecordRouted@2024-03")?,
+            vec![record(5, "2024-03")]
+        );
+        db.try_remove_storage(db.file_path("TestRecordRouted@2024-03"))?;
+
+        let mut partitions = db.list_partitions()?;
+        partitions.sort();
+        assert_eq!(
+            partitions,
+            vec!["TestRecordRouted@2024-01", "TestRecordRouted@2024-02"]
+        );
+
+        assert_eq!(
+            db.get_all_by_name::<TestRecordRouted>("TestRecordRouted@2024-01")?,
+            vec![record(2, "2024-01"), record(4, "2024-01")]
+        );
+    }
+
+    {
+        span_and_info!("Get all");
+
+        assert_eq!(
+            db.get_all::<TestRecordRouted>()?.as_uniques(),
+            vec![2, 4, 1, 3]
+        );
+
+        // Only the routed partitions hold records
+        assert!(!db.is_empty::<TestRecordRouted>()?);
+    }
+
+    {
+        span_and_info!("Insert duplicates");
+
+        assert!(
+            db.insert_all([record(5, "2024-03"), record(1, "2024-02")])
+                .is_err(),
+            "Inserting a duplicate into a routed partition should fail"
+        );
+        assert!(
+            db.insert_all([record(6, "2024-03"), record(6, "2024-03")])
+                .is_err(),
+            "Inserting duplicates into a new routed partition should fail"
+        );
+
+        // Nothing should have been written
+        assert_eq!(db.get_all::<TestRecordRouted>()?.len(), 4);
+        assert_eq!(db.list_partitions()?.len(), 2);
+    }
+
+    {
+        span_and_info!("Invalid routes");
+
+        let misrouted = |route: &str| TestRecordMisrouted {
+            id: 1,
+            route: route.to_string(),
+        };
+
+        for route in [
+            "TestRecordRouted@2024-01",
+            "TestRecordMisroutedOther",
+            "TestRecordMisrouted.Other",
+            "TestRecordMisrouted@Other@Nested",
+            "TestRecordMisrouted@",
+            "",
+        ] {
+            assert!(
+                matches!(
+                    db.insert(misrouted(route)),
+                    Err(lupabase::Error::DBInvalidPartition { .. })
+                ),
+                "Route [{route}] should be refused"
+            );
+            assert!(matches!(
+                db.try_insert(misrouted(route)),
+                Err(lupabase::Error::DBInvalidPartition { .. })
+            ));
+        }
+
+        db.insert(misrouted("TestRecordMisrouted"))?;
+        db.insert(misrouted("TestRecordMisrouted@Other"))?;
+        assert_eq!(db.get_all::<TestRecordMisrouted>()?.len(), 2);
+
+        // Nothing should have been written into another type's partitions
+        assert_eq!(db.get_all::<TestRecordRouted>()?.len(), 4);
+    }
+
+    {
+        span_and_info!("Unrelated partitions");
+
+        // Partitions sharing the name's prefix aren't routed partitions, so they are never read as such
+        let snapshot_path = db.file_path("TestRecordRouted.snapshot");
+        db.try_write_storage(vec![record(9, "2024-09")], &snapshot_path)?;
+        assert_eq!(db.get_all::<TestRecordRouted>()?.len(), 4);
+
+        // Nor can partitions be created within a routed namespace
+        assert!(matches!(
+            db.clone_partition("TestRecordRouted.snapshot", "TestRecordRouted@snapshot"),
+            Err(lupabase::Error::DBInvalidPartition { .. })
+        ));
+        db.try_remove_storage(&snapshot_path)?;
+    }
+
+    {
+        span_and_info!("Unrouted operations");
+
+        let updated = record(1, "2024-02");
+        for result in [
+            db.update(updated.clone()),
+            db.upsert(updated.clone()),
+            db.touch::<TestRecordRouted, _>(&1, |r| r.month = String::from("2024-02")),
+            db.rekey(&1, updated.clone()),
+            db.batch().update(updated.clone()).apply(),
+        ] {
+            assert!(
+                matches!(result, Err(lupabase::Error::DBOperationFailure { .. })),
+                "Operations on a single partition should refuse routed records, got: [{result:?}]"
+            );
+        }
+
+        // Routed partitions can still be targeted directly
+        db.update_with_path(updated.clone(), db.file_path(updated.route()))?;
+        assert_eq!(db.get_all::<TestRecordRouted>()?.len(), 4);
+        assert!(!db.storage_exists(db.file_path(TestRecordRouted::PARTITION))?);
+    }
+
+    Ok(())
+}
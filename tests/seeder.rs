@@ -0,0 +1,85 @@
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use std::error::Error;
+use tests_records::*;
+use tests_utils::*;
+
+#[test]
+fn seeder_cbor() -> Result<(), Box<dyn Error>> {
+    seeder_tester::<DiskDB<CborSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn seeder_json() -> Result<(), Box<dyn Error>> {
+    seeder_tester::<DiskDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn seeder_memory() -> Result<(), Box<dyn Error>> {
+    seeder_tester::<MemoryDB<CborSerde>>()?;
+    seeder_tester::<MemoryDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+fn seeder_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let db_name = &format!("{}-{}", DB::SERDE_FORMAT, DB::NAME);
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("seeder", db_name);
+
+    let db = DB::new(working_dir);
+
+    let id = &mut 0;
+    let records_1 = vec![TestRecordPartitioned::new(id)];
+    let records_2 = vec![
+        TestRecordPartitioned2::new(id),
+        TestRecordPartitioned2::new(id),
+    ];
+    let records_3 = vec![TestRecordPartitioned3::new(id)];
+
+    let seeder = Seeder::new()
+        .with(records_1.clone())
+        .with(records_2.clone())
+        .with(records_3.clone());
+    assert_eq!(
+        seeder.partitions(),
+        vec![
+            TestRecordPartitioned::PARTITION,
+            TestRecordPartitioned2::PARTITION,
+            TestRecordPartitioned3::PARTITION,
+        ]
+    );
+
+    {
+        span_and_info!("Seed");
+
+        seeder.apply(&db)?;
+
+        assert!(db.get_all::<TestRecordPartitioned>()? == records_1);
+        assert!(db.get_all::<TestRecordPartitioned2>()? == records_2);
+        assert!(db.get_all::<TestRecordPartitioned3>()? == records_3);
+    }
+
+    {
+        span_and_info!("Seed existing");
+
+        let record = TestRecordPartitioned::new(id);
+        db.insert(record.clone())?;
+
+        // Existing partitions are left untouched
+        seeder.apply(&db)?;
+
+        assert!(db.get_all::<TestRecordPartitioned>()? == [records_1, vec![record]].concat());
+        assert!(db.get_all::<TestRecordPartitioned2>()? == records_2);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "testing")]
+
+pub mod tests_records;
+pub mod tests_utils;
+
+use lupabase::{
+    prelude::*,
+    testing::{TestGen, TestableRecord, gen_records, run_basics, run_transactions},
+};
+use std::error::Error;
+use tests_records::*;
+use tests_utils::*;
+
+impl TestGen for TestRecordPartitioned {
+    fn generate(seed: u64) -> Self {
+        let mut id = seed - 1;
+
+        Self::new(&mut id)
+    }
+}
+
+impl TestableRecord for TestRecordPartitioned {
+    fn modified(&self) -> Self {
+        Self {
+            id: self.id,
+            data: format!("{} has been modified!", self.data),
+        }
+    }
+}
+
+#[test]
+fn testing_gen_records() {
+    init_tracing_for_tests();
+
+    let records = gen_records::<TestRecordPartitioned>(100);
+    assert_eq!(records.len(), 100);
+    assert!(
+        records
+            .iter()
+            .zip(1..)
+            .all(|(record, id)| record.id.get() == id)
+    );
+}
+
+#[test]
+fn testing_cbor() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    run_basics::<TestRecordPartitioned, DiskDB<CborSerde>>()?;
+    run_transactions::<TestRecordPartitioned, DiskDB<CborSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn testing_json() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    run_basics::<TestRecordPartitioned, DiskDB<JsonSerde>>()?;
+    run_transactions::<TestRecordPartitioned, DiskDB<JsonSerde>>()?;
+
+    Ok(())
+}
+
+#[test]
+fn testing_memory() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    run_basics::<TestRecordPartitioned, MemoryDB<CborSerde>>()?;
+    run_transactions::<TestRecordPartitioned, MemoryDB<JsonSerde>>()?;
+
+    Ok(())
+}
@@ -41,6 +41,12 @@ impl DatabaseRecordPartitioned for TestRecordPartitioned {
     const PARTITION: &str = "TestRecordPartitioned";
 }
 
+impl RangeQueryable for TestRecordPartitioned {
+    fn unique_in_range(unique: &Self::Unique, lo: &Self::Unique, hi: &Self::Unique) -> bool {
+        (lo..=hi).contains(&unique)
+    }
+}
+
 impl TestRecordPartitioned {
     pub fn new(id: &mut u64) -> Self {
         *id += 1;
@@ -52,6 +58,18 @@ impl TestRecordPartitioned {
     }
 }
 
+/// Structurally compatible with [`TestRecordPartitioned`], but without a partition of its own
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestRecordAlternate {
+    pub id: NonZero<u64>,
+}
+
+impl DatabaseRecord for TestRecordAlternate {
+    type Unique = NonZero<u64>;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TestRecordPartitioned2 {
     pub id: NonZero<u64>,
@@ -1,7 +1,15 @@
-use std::{fmt::Display, path::PathBuf, sync::OnceLock};
+use std::{
+    fmt::Display,
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex, OnceLock},
+};
 use tempfile::TempDir;
-use tracing::info;
-use tracing_subscriber::{EnvFilter, fmt};
+use tracing::{Level, info};
+use tracing_subscriber::{
+    EnvFilter, fmt,
+    fmt::{MakeWriter, format::FmtSpan},
+};
 
 static TRACING_INIT: OnceLock<()> = OnceLock::new();
 
@@ -31,6 +39,42 @@ pub fn create_temp_working_dir(tester: impl Display, prefix: impl Display) -> (P
     (pathbuf, temp_dir)
 }
 
+#[derive(Clone, Default)]
+pub struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer { self.clone() }
+}
+
+/// Runs the closure with a thread-local subscriber, returning its output alongside every log
+/// and span creation emitted
+pub fn capture_logs<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let logs = CapturedLogs::default();
+
+    let subscriber = fmt()
+        .with_writer(logs.clone())
+        .with_ansi(false)
+        .with_max_level(Level::TRACE)
+        .with_span_events(FmtSpan::NEW)
+        .finish();
+
+    let output = tracing::subscriber::with_default(subscriber, f);
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).expect("Logs should be UTF-8");
+
+    (output, logs)
+}
+
 #[macro_export]
 macro_rules! span_and_info {
     ($name:literal) => {
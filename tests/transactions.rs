@@ -2,7 +2,7 @@ pub mod tests_records;
 pub mod tests_utils;
 
 use insta::assert_debug_snapshot;
-use lupabase::prelude::*;
+use lupabase::prelude::{transactions::*, *};
 use std::{error::Error, fs};
 use tests_records::*;
 use tests_utils::*;
@@ -29,6 +29,381 @@ fn transactions_memory() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn transactions_rollback_failure() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("transactions", "rollback-failure");
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir);
+    let tx = db.transact()?;
+
+    let tx_file_path = tx.file_path("TestRecords");
+    let db_file_path = db.file_path("TestRecords");
+
+    tx.try_initialize_storage_with_path(vec![TestRecord::new(&mut 0)], &tx_file_path)?;
+
+    // A directory in place of the storage file makes both the commit and the rollback writes fail
+    fs::create_dir_all(&db_file_path)?;
+
+    let error = db
+        .try_commit_with_path::<Vec<TestRecord>>(&tx, &tx_file_path, &db_file_path)
+        .expect_err("Commit and rollback should both fail");
+
+    let lupabase::Error::DBTransactionRollbackFailure {
+        reason,
+        commit_reason,
+        ..
+    } = &error
+    else {
+        panic!("Expected DBTransactionRollbackFailure, got: [{error}]");
+    };
+
+    assert!(!reason.is_empty());
+    assert!(!commit_reason.is_empty());
+    assert!(error.to_string().contains(reason.as_str()));
+    assert!(error.to_string().contains(commit_reason.as_str()));
+
+    Ok(())
+}
+
+#[test]
+fn transactions_snapshot_before() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("transactions", "snapshot-before");
+
+    let db = DiskDB::<CborSerde>::new(&working_dir);
+
+    let id = &mut 0;
+    let records = vec![
+        TestRecordPartitioned::new(id),
+        TestRecordPartitioned::new(id),
+    ];
+    let records_2 = vec![TestRecordPartitioned2::new(id)];
+    let record = TestRecord::new(id);
+
+    let tx = db.transact()?;
+    tx.try_initialize_storage::<TestRecordPartitioned, _>(records.clone())?;
+    tx.try_initialize_storage::<TestRecordPartitioned2, _>(records_2.clone())?;
+    tx.try_initialize_storage_with_path(record.clone(), tx.file_path("TestRecord"))?;
+
+    tx.insert(TestRecordPartitioned::new(id))?;
+    tx.replace_all::<TestRecordPartitioned2>([])?;
+
+    let snapshot = tx.snapshot_before()?;
+    assert_eq!(
+        snapshot.keys().collect::<Vec<_>>(),
+        vec![
+            "TestRecord",
+            TestRecordPartitioned::PARTITION,
+            TestRecordPartitioned2::PARTITION
+        ]
+    );
+    assert_eq!(
+        snapshot[TestRecordPartitioned::PARTITION],
+        serde_json::to_value(&records)?
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+    );
+    assert_eq!(
+        snapshot[TestRecordPartitioned2::PARTITION],
+        serde_json::to_value(&records_2)?
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+    );
+    assert_eq!(snapshot["TestRecord"], vec![serde_json::to_value(&record)?]);
+
+    Ok(())
+}
+
+#[test]
+fn transactions_readonly() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) = create_temp_working_dir("transactions", "readonly");
+
+    let db = DiskDB::<CborSerde>::new(&working_dir);
+
+    let id = &mut 0;
+    let records = vec![TestRecordPartitioned::new(id)];
+    db.try_initialize_storage::<TestRecordPartitioned, _>(records.clone())?;
+
+    let tx = db.transact_readonly()?;
+    assert_eq!(tx.get_all::<TestRecordPartitioned>()?, records);
+
+    db.insert(TestRecordPartitioned::new(id))?;
+    let records_2 = vec![TestRecordPartitioned2::new(id)];
+    db.try_initialize_storage::<TestRecordPartitioned2, _>(records_2.clone())?;
+
+    // Already snapshotted partitions are unaffected by writes
+    assert_eq!(tx.get_all::<TestRecordPartitioned>()?, records);
+    assert_eq!(db.get_all::<TestRecordPartitioned>()?.len(), 2);
+
+    // Other partitions are snapshotted on their first read, so they see writes made after the transaction began,
+    // as snapshots are repeatable per partition rather than a point-in-time view across partitions
+    assert_eq!(tx.get_all::<TestRecordPartitioned2>()?, records_2);
+    db.replace_all::<TestRecordPartitioned2>([])?;
+    assert_eq!(tx.get_all::<TestRecordPartitioned2>()?, records_2);
+
+    tx.try_commit()?;
+
+    Ok(())
+}
+
+#[test]
+fn transactions_partition_paths() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("transactions", "partition-paths");
+
+    let db = DiskDB::<CborSerde>::new(&working_dir);
+
+    let id = &mut 0;
+    db.try_initialize_storage::<TestRecordPartitioned, _>(vec![TestRecordPartitioned::new(id)])?;
+
+    let tx = db.transact()?;
+
+    // The transaction resolves partitions the same way as its database
+    assert_eq!(tx.dir(), db.dir());
+    assert_eq!(
+        tx.file_path(TestRecordPartitioned::PARTITION),
+        db.file_path(TestRecordPartitioned::PARTITION)
+    );
+
+    tx.try_initialize_storage::<TestRecordPartitioned, _>(db.get_all::<TestRecordPartitioned>()?)?;
+    tx.insert(TestRecordPartitioned::new(id))?;
+    db.try_commit::<TestRecordPartitioned>(&tx)?;
+
+    let committed = db.try_read_storage::<Vec<TestRecordPartitioned>>(
+        working_dir.join(format!("{}.cbor", TestRecordPartitioned::PARTITION)),
+    )?;
+    assert_eq!(committed, tx.get_all::<TestRecordPartitioned>()?);
+    assert_eq!(committed.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn transactions_merge_commit() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("transactions", "merge-commit");
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir);
+
+    let id = &mut 0;
+    let mut records = vec![
+        TestRecordPartitioned::new(id),
+        TestRecordPartitioned::new(id),
+        TestRecordPartitioned::new(id),
+    ];
+    db.try_initialize_storage::<TestRecordPartitioned, _>(records.clone())?;
+
+    let tx_1 = db.transact()?;
+    let tx_2 = db.transact()?;
+    tx_1.try_initialize_storage::<TestRecordPartitioned, _>(
+        db.get_all::<TestRecordPartitioned>()?,
+    )?;
+    tx_2.try_initialize_storage::<TestRecordPartitioned, _>(
+        db.get_all::<TestRecordPartitioned>()?,
+    )?;
+
+    records[0].data = String::from("Updated by the first transaction");
+    tx_1.update(records[0].clone())?;
+
+    records[1].data = String::from("Updated by the second transaction");
+    let inserted = TestRecordPartitioned::new(id);
+    tx_2.apply_changes(
+        vec![inserted.clone()],
+        vec![records[1].clone()],
+        vec![records[2].unique_value()],
+    )?;
+
+    db.merge_commit::<TestRecordPartitioned>(&tx_1)?;
+    db.merge_commit::<TestRecordPartitioned>(&tx_2)?;
+
+    // Both transactions' changes survive, as they touched disjoint records
+    let expected = vec![records[0].clone(), records[1].clone(), inserted];
+    assert_eq!(db.get_all::<TestRecordPartitioned>()?, expected);
+
+    // A plain commit overwrites the partition with the transaction's records instead
+    db.try_commit::<TestRecordPartitioned>(&tx_1)?;
+    assert_eq!(
+        db.get_all::<TestRecordPartitioned>()?,
+        tx_1.get_all::<TestRecordPartitioned>()?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn transactions_commit_with_check() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("transactions", "commit-with-check");
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir);
+
+    let id = &mut 0;
+    let records = vec![
+        TestRecordPartitioned::new(id),
+        TestRecordPartitioned::new(id),
+    ];
+    db.try_initialize_storage::<TestRecordPartitioned, _>(records.clone())?;
+
+    // At most 3 records are allowed
+    let check = |records: &[TestRecordPartitioned]| {
+        if records.len() > 3 {
+            return Err(lupabase::Error::DBOperationFailure {
+                path: String::from(TestRecordPartitioned::PARTITION),
+                reason: format!("Found [{}] records, at most 3 are allowed.", records.len()),
+            });
+        }
+
+        Ok(())
+    };
+
+    let tx = db.transact()?;
+    tx.try_initialize_storage::<TestRecordPartitioned, _>(db.get_all::<TestRecordPartitioned>()?)?;
+    tx.insert_all([
+        TestRecordPartitioned::new(id),
+        TestRecordPartitioned::new(id),
+    ])?;
+
+    let error = db
+        .try_commit_with_check::<TestRecordPartitioned, _>(&tx, check)
+        .expect_err("The check should reject 4 records");
+    assert!(error.to_string().contains("at most 3 are allowed"));
+    // The database is unchanged
+    assert_eq!(db.get_all::<TestRecordPartitioned>()?, records);
+
+    tx.replace_all(tx.get_all::<TestRecordPartitioned>()?.split_off(1))?;
+    db.try_commit_with_check::<TestRecordPartitioned, _>(&tx, check)?;
+    assert_eq!(
+        db.get_all::<TestRecordPartitioned>()?,
+        tx.get_all::<TestRecordPartitioned>()?
+    );
+    assert_eq!(db.get_all::<TestRecordPartitioned>()?.len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn transactions_commit_operatable() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("transactions", "commit-operatable");
+
+    type Partitions = (TestRecordPartitioned, TestRecordPartitioned2);
+
+    let db = DiskDB::<JsonSerde>::new(&working_dir);
+
+    let id = &mut 0;
+    db.try_initialize_storage_with_operatable::<Partitions>((
+        vec![TestRecordPartitioned::new(id)],
+        vec![TestRecordPartitioned2::new(id)],
+    ))?;
+    let records_before = db.get_all_with_operatable::<Partitions>()?;
+
+    let tx = db.transact()?;
+    tx.try_initialize_storage_with_operatable::<Partitions>(records_before.clone())?;
+    tx.insert_all_with_operatable::<Partitions>((
+        vec![TestRecordPartitioned::new(id)],
+        vec![
+            TestRecordPartitioned2::new(id),
+            TestRecordPartitioned2::new(id),
+        ],
+    ))?;
+
+    // Nothing is written before committing
+    assert!(db.get_all_with_operatable::<Partitions>()? == records_before);
+
+    db.try_commit_operatable::<Partitions>(&tx)?;
+    let records_committed = db.get_all_with_operatable::<Partitions>()?;
+    assert!(records_committed == tx.get_all_with_operatable::<Partitions>()?);
+    assert_eq!(
+        (records_committed.0.len(), records_committed.1.len()),
+        (2, 3)
+    );
+
+    let tx = db.transact()?;
+    tx.try_initialize_storage_with_operatable::<Partitions>(records_committed.clone())?;
+    tx.insert_all_with_operatable::<Partitions>((
+        vec![TestRecordPartitioned::new(id)],
+        vec![TestRecordPartitioned2::new(id)],
+    ))?;
+
+    // A directory in place of the second partition makes its commit fail, rolling back the first partition
+    let second_partition_path = db.partition_path::<TestRecordPartitioned2>()?;
+    fs::remove_file(&second_partition_path)?;
+    fs::create_dir_all(&second_partition_path)?;
+
+    assert!(
+        db.try_commit_operatable::<Partitions>(&tx).is_err(),
+        "Committing into a directory should fail"
+    );
+    assert!(db.get_all::<TestRecordPartitioned>()? == records_committed.0);
+
+    Ok(())
+}
+
+#[test]
+fn transactions_render_diff() -> Result<(), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let (working_dir, _temp_dir_drop_guard) =
+        create_temp_working_dir("transactions", "render-diff");
+
+    let db = MemoryDB::<JsonSerde>::new(&working_dir);
+
+    let id = &mut 0;
+    let records = vec![
+        TestRecordPartitioned::new(id),
+        TestRecordPartitioned::new(id),
+        TestRecordPartitioned::new(id),
+    ];
+
+    let tx = db.transact()?;
+    tx.try_initialize_storage::<TestRecordPartitioned, _>(records.clone())?;
+    assert!(tx.diff::<TestRecordPartitioned>()?.is_empty());
+    assert_eq!(render_diff(&tx.diff::<TestRecordPartitioned>()?), "");
+
+    let mut changed = records[1].clone();
+    changed.data = String::from("Data has been changed!");
+    let added = TestRecordPartitioned::new(id);
+    tx.apply_changes(
+        vec![added.clone()],
+        vec![changed.clone()],
+        vec![records[0].unique_value()],
+    )?;
+
+    let diff = tx.diff::<TestRecordPartitioned>()?;
+    assert_eq!(diff.added, vec![added.clone()]);
+    assert_eq!(diff.removed, vec![records[0].clone()]);
+    assert_eq!(diff.changed, vec![(records[1].clone(), changed.clone())]);
+
+    let rendered = render_diff(&diff);
+    let lines = rendered.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines.contains(&format!("+ {added:?}").as_str()));
+    assert!(lines.contains(&format!("- {:?}", records[0]).as_str()));
+    assert!(lines.contains(&format!("~ {:?} -> {changed:?}", records[1]).as_str()));
+
+    // Unchanged records aren't rendered
+    assert!(!rendered.contains(&format!("{:?}", records[2])));
+
+    Ok(())
+}
+
 fn transactions_tester<DB: DatabaseTransaction>() -> Result<(), Box<dyn Error>> {
     init_tracing_for_tests();
 
@@ -0,0 +1,194 @@
+pub mod tests_utils;
+
+use lupabase::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fs};
+use tempfile::TempDir;
+use tests_utils::*;
+
+/// A unique type exercised by [`uniques_tester`], with its own partition
+pub trait TestUnique: IntoUnique + Copy + for<'a> Deserialize<'a> {
+    const PARTITION: &str;
+
+    /// Returns a distinct unique for each `n`
+    fn nth(n: u8) -> Self;
+}
+
+#[cfg(feature = "uuid")]
+impl TestUnique for Uuid {
+    const PARTITION: &str = "TestRecordUuid";
+
+    fn nth(n: u8) -> Self { Uuid::from_u128(n.into()) }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Kind {
+    Alpha,
+    Beta,
+    Gamma,
+}
+
+impl TestUnique for Kind {
+    const PARTITION: &str = "TestRecordKind";
+
+    fn nth(n: u8) -> Self {
+        match n {
+            1 => Kind::Alpha,
+            2 => Kind::Beta,
+            _ => Kind::Gamma,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "U: TestUnique")]
+pub struct TestRecordUnique<U> {
+    pub id: U,
+    pub data: String,
+}
+
+impl<U: TestUnique> DatabaseRecord for TestRecordUnique<U> {
+    type Unique = U;
+
+    fn unique_value(&self) -> Self::Unique { self.id }
+}
+
+impl<U: TestUnique> DatabaseRecordPartitioned for TestRecordUnique<U> {
+    const PARTITION: &str = U::PARTITION;
+}
+
+impl<U: TestUnique> TestRecordUnique<U> {
+    pub fn new(n: u8) -> Self {
+        Self {
+            id: U::nth(n),
+            data: format!("My data of {n}"),
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn uniques_uuid_cbor() -> Result<(), Box<dyn Error>> {
+    let (db, _temp_dir_drop_guard) = uniques_tester::<DiskDB<CborSerde>, Uuid>()?;
+
+    let bytes = fs::read(db.file_path(Uuid::PARTITION))?;
+    let uuid = Uuid::nth(1);
+    assert!(
+        bytes.windows(16).any(|window| window == uuid.as_bytes()),
+        "CBOR should encode the Uuid as raw bytes"
+    );
+    assert!(
+        !bytes
+            .windows(36)
+            .any(|window| window == uuid.hyphenated().to_string().as_bytes()),
+        "CBOR should not encode the Uuid as a string"
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn uniques_uuid_json() -> Result<(), Box<dyn Error>> {
+    let (db, _temp_dir_drop_guard) = uniques_tester::<DiskDB<JsonSerde>, Uuid>()?;
+
+    let content = fs::read_to_string(db.file_path(Uuid::PARTITION))?;
+    assert!(content.contains(&Uuid::nth(1).hyphenated().to_string()));
+
+    Ok(())
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn uniques_uuid_memory() -> Result<(), Box<dyn Error>> {
+    uniques_tester::<MemoryDB<CborSerde>, Uuid>()?;
+    uniques_tester::<MemoryDB<JsonSerde>, Uuid>()?;
+
+    Ok(())
+}
+
+#[test]
+fn uniques_enum_cbor() -> Result<(), Box<dyn Error>> {
+    let (db, _temp_dir_drop_guard) = uniques_tester::<DiskDB<CborSerde>, Kind>()?;
+
+    // Unit variants are encoded by name, as a CBOR text string, rather than by discriminant
+    let bytes = fs::read(db.file_path(Kind::PARTITION))?;
+    assert!(
+        bytes
+            .windows(5)
+            .any(|window| window == [&[0x64][..], b"Beta"].concat()),
+        "CBOR should encode the Kind as its variant name"
+    );
+
+    // A fresh database decodes the stored variants back
+    assert_eq!(
+        DiskDB::<CborSerde>::new(db.dir()).get_all::<TestRecordUnique<Kind>>()?,
+        db.get_all::<TestRecordUnique<Kind>>()?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn uniques_enum_json() -> Result<(), Box<dyn Error>> {
+    let (db, _temp_dir_drop_guard) = uniques_tester::<DiskDB<JsonSerde>, Kind>()?;
+
+    let content = fs::read_to_string(db.file_path(Kind::PARTITION))?;
+    assert!(content.contains(r#""id":"Beta""#));
+
+    Ok(())
+}
+
+#[test]
+fn uniques_enum_memory() -> Result<(), Box<dyn Error>> {
+    uniques_tester::<MemoryDB<CborSerde>, Kind>()?;
+    uniques_tester::<MemoryDB<JsonSerde>, Kind>()?;
+
+    Ok(())
+}
+
+fn uniques_tester<DB: Database, U: TestUnique>() -> Result<(DB, TempDir), Box<dyn Error>> {
+    init_tracing_for_tests();
+
+    let db_name = &format!("{}-{}-{}", U::PARTITION, DB::SERDE_FORMAT, DB::NAME);
+
+    // The drop guard is returned for the caller to inspect the stored files
+    let (working_dir, temp_dir_drop_guard) = create_temp_working_dir("uniques", db_name);
+
+    let db = DB::new(working_dir);
+
+    {
+        span_and_info!("Roundtrip");
+
+        let records = vec![TestRecordUnique::<U>::new(1), TestRecordUnique::new(2)];
+        db.try_initialize_storage::<TestRecordUnique<U>, _>(records.clone())?;
+        assert_eq!(db.get_all::<TestRecordUnique<U>>()?, records);
+
+        let mut record = TestRecordUnique::<U>::new(2);
+        record.data = String::from("Data has been updated!");
+        db.update(record.clone())?;
+        assert_eq!(db.get_all::<TestRecordUnique<U>>()?[1], record);
+    }
+
+    {
+        span_and_info!("Errors");
+
+        let error = db
+            .insert(TestRecordUnique::<U>::new(1))
+            .expect_err("Duplicate unique should be rejected");
+        assert!(
+            error.to_string().contains(&format!("[{:?}]", U::nth(1))),
+            "{error}"
+        );
+
+        let error = db
+            .update(TestRecordUnique::<U>::new(3))
+            .expect_err("Missing unique should be rejected");
+        assert!(
+            error.to_string().contains(&format!("[{:?}]", U::nth(3))),
+            "{error}"
+        );
+    }
+
+    Ok((db, temp_dir_drop_guard))
+}
@@ -49,6 +49,26 @@ type Partition132 = (
 
 type Partition12 = (TestRecordPartitioned, TestRecordPartitioned2);
 
+struct ConfigName;
+
+impl StorageName for ConfigName {
+    const NAME: &str = "Config";
+}
+
+struct TestRecordsName;
+
+impl StorageName for TestRecordsName {
+    const NAME: &str = "TestRecords";
+}
+
+type Mixed = (
+    TestRecordPartitioned,
+    (
+        Single<TestRecord, ConfigName>,
+        Collection<TestRecord, TestRecordsName>,
+    ),
+);
+
 fn variadics_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
     init_tracing_for_tests();
 
@@ -251,14 +271,43 @@ fn variadics_tester<DB: Database>() -> Result<(), Box<dyn Error>> {
         }
     }
 
-    //TODO: Collection and Single wrapper
-    // {
-    //     span_and_info!("Collection");
-    // }
+    {
+        span_and_info!("Mixed storages");
+
+        let id = &mut 100_u64;
+
+        {
+            span_and_info!("Initialize");
+
+            let record = TestRecord::new(id);
+            db.try_initialize_storage_with_storage_operatable::<Mixed>((
+                vec![],
+                (record.clone(), vec![]),
+            ))?;
+
+            let (_, (single, collection)) = db.get_with_storage_operatable::<Mixed>()?;
+            assert_eq!(single, record);
+            assert!(collection.is_empty());
+        }
+
+        {
+            span_and_info!("Operation", "Writing");
 
-    // {
-    //     span_and_info!("Single");
-    // }
+            let data = (
+                vec![TestRecordPartitioned::new(id)],
+                (
+                    TestRecord::new(id),
+                    vec![TestRecord::new(id), TestRecord::new(id)],
+                ),
+            );
+            db.write_with_storage_operatable::<Mixed>(data.clone())?;
+            assert!(db.get_with_storage_operatable::<Mixed>()? == data);
+            assert_debug_snapshot!(
+                format!("{db_name} mixed storages written"),
+                db.get_with_storage_operatable::<Mixed>()?
+            );
+        }
+    }
 
     Ok(())
 }